@@ -13,7 +13,7 @@ pub struct Model {
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
-    // TODO: 
+    // TODO:
     // #[sea_orm(has_many = "super::pantry_item_categories::Entity")]
     // PantryItemCategories,
     #[sea_orm(has_many = "super::recipe_categories::Entity")]
@@ -8,6 +8,7 @@ pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub id: Uuid,
     pub name: String,
+    pub freezer_shelf_life_days: Option<i32>,
     pub created_at: DateTime,
 }
 
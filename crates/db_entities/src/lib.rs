@@ -2,10 +2,21 @@
 
 pub mod prelude;
 
+pub mod api_tokens;
 pub mod categories;
+pub mod household_members;
+pub mod households;
+pub mod ingredient_aliases;
 pub mod ingredients;
+pub mod locations;
+pub mod meal_plans;
+pub mod pantry_item_events;
 pub mod pantry_items;
+pub mod products;
 pub mod recipe_categories;
 pub mod recipe_ingredients;
 pub mod recipes;
+pub mod shopping_list_items;
+pub mod shopping_lists;
+pub mod user_identities;
 pub mod users;
@@ -8,6 +8,7 @@ pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub id: Uuid,
     pub ingredient_id: Uuid,
+    pub purchase_date: Option<Date>,
     pub expiration_date: Option<Date>,
     pub quantity: Option<i32>,
     pub weight_grams: Option<i32>,
@@ -15,12 +16,22 @@ pub struct Model {
     pub essential: bool,
     pub running_low: Option<i32>,
     pub user_id: Uuid,
+    pub household_id: Option<Uuid>,
+    pub location_id: Option<Uuid>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::households::Entity",
+        from = "Column::HouseholdId",
+        to = "super::households::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    Households,
     #[sea_orm(
         belongs_to = "super::ingredients::Entity",
         from = "Column::IngredientId",
@@ -29,6 +40,14 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Ingredients,
+    #[sea_orm(
+        belongs_to = "super::locations::Entity",
+        from = "Column::LocationId",
+        to = "super::locations::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    Locations,
     #[sea_orm(
         belongs_to = "super::users::Entity",
         from = "Column::UserId",
@@ -39,12 +58,24 @@ pub enum Relation {
     Users,
 }
 
+impl Related<super::households::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Households.def()
+    }
+}
+
 impl Related<super::ingredients::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Ingredients.def()
     }
 }
 
+impl Related<super::locations::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Locations.def()
+    }
+}
+
 impl Related<super::users::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Users.def()
@@ -1,7 +1,20 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.6
 
+pub use super::api_tokens::Entity as ApiTokens;
+pub use super::categories::Entity as Categories;
+pub use super::household_members::Entity as HouseholdMembers;
+pub use super::households::Entity as Households;
+pub use super::ingredient_aliases::Entity as IngredientAliases;
 pub use super::ingredients::Entity as Ingredients;
+pub use super::locations::Entity as Locations;
+pub use super::meal_plans::Entity as MealPlans;
+pub use super::pantry_item_events::Entity as PantryItemEvents;
 pub use super::pantry_items::Entity as PantryItems;
+pub use super::products::Entity as Products;
+pub use super::recipe_categories::Entity as RecipeCategories;
 pub use super::recipe_ingredients::Entity as RecipeIngredients;
 pub use super::recipes::Entity as Recipes;
+pub use super::shopping_list_items::Entity as ShoppingListItems;
+pub use super::shopping_lists::Entity as ShoppingLists;
+pub use super::user_identities::Entity as UserIdentities;
 pub use super::users::Entity as Users;
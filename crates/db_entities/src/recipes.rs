@@ -8,6 +8,7 @@ pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub id: Uuid,
     pub user_id: Uuid,
+    pub household_id: Option<Uuid>,
     pub name: String,
     pub prep_time_mins: Option<i32>,
     pub total_time_mins: Option<i32>,
@@ -24,6 +25,16 @@ pub struct Model {
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::households::Entity",
+        from = "Column::HouseholdId",
+        to = "super::households::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    Households,
+    #[sea_orm(has_many = "super::recipe_categories::Entity")]
+    RecipeCategories,
     #[sea_orm(has_many = "super::recipe_ingredients::Entity")]
     RecipeIngredients,
     #[sea_orm(
@@ -36,6 +47,18 @@ pub enum Relation {
     Users,
 }
 
+impl Related<super::households::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Households.def()
+    }
+}
+
+impl Related<super::recipe_categories::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RecipeCategories.def()
+    }
+}
+
 impl Related<super::recipe_ingredients::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::RecipeIngredients.def()
@@ -1,12 +1,36 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20240107_000001_base;
+mod m20240901_000002_shopping_lists;
+mod m20240915_000003_meal_plans;
+mod m20241001_000004_households;
+mod m20241015_000005_pantry_item_events;
+mod m20241101_000006_products;
+mod m20241115_000007_ingredient_aliases;
+mod m20241201_000008_pantry_lots;
+mod m20241215_000009_locations;
+mod m20241229_000010_api_tokens;
+mod m20250105_000011_user_identities;
+mod m20250112_000012_lot_locations;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20240107_000001_base::Migration)]
+        vec![
+            Box::new(m20240107_000001_base::Migration),
+            Box::new(m20240901_000002_shopping_lists::Migration),
+            Box::new(m20240915_000003_meal_plans::Migration),
+            Box::new(m20241001_000004_households::Migration),
+            Box::new(m20241015_000005_pantry_item_events::Migration),
+            Box::new(m20241101_000006_products::Migration),
+            Box::new(m20241115_000007_ingredient_aliases::Migration),
+            Box::new(m20241201_000008_pantry_lots::Migration),
+            Box::new(m20241215_000009_locations::Migration),
+            Box::new(m20241229_000010_api_tokens::Migration),
+            Box::new(m20250105_000011_user_identities::Migration),
+            Box::new(m20250112_000012_lot_locations::Migration),
+        ]
     }
 }
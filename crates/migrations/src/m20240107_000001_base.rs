@@ -330,7 +330,7 @@ pub enum Categories {
 }
 
 #[derive(Iden)]
-enum Users {
+pub enum Users {
     Table,
     Id,
     Name,
@@ -0,0 +1,142 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240107_000001_base::{Ingredients, Recipes, Users};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ShoppingLists::Table)
+                    .col(ColumnDef::new(ShoppingLists::Id).uuid().primary_key())
+                    .col(ColumnDef::new(ShoppingLists::UserId).uuid().not_null())
+                    .col(ColumnDef::new(ShoppingLists::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(ShoppingLists::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .col(
+                        ColumnDef::new(ShoppingLists::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(ShoppingLists::Table)
+                            .from_col(ShoppingLists::UserId)
+                            .to_tbl(Users::Table)
+                            .to_col(Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ShoppingListItems::Table)
+                    .col(ColumnDef::new(ShoppingListItems::Id).uuid().primary_key())
+                    .col(
+                        ColumnDef::new(ShoppingListItems::ShoppingListId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ShoppingListItems::IngredientId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ShoppingListItems::RecipeId).uuid())
+                    .col(ColumnDef::new(ShoppingListItems::Amount).string())
+                    .col(ColumnDef::new(ShoppingListItems::Unit).string())
+                    .col(
+                        ColumnDef::new(ShoppingListItems::Checked)
+                            .boolean()
+                            .default(false)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ShoppingListItems::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .col(
+                        ColumnDef::new(ShoppingListItems::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(ShoppingListItems::Table)
+                            .from_col(ShoppingListItems::ShoppingListId)
+                            .to_tbl(ShoppingLists::Table)
+                            .to_col(ShoppingLists::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(ShoppingListItems::Table)
+                            .from_col(ShoppingListItems::IngredientId)
+                            .to_tbl(Ingredients::Table)
+                            .to_col(Ingredients::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(ShoppingListItems::Table)
+                            .from_col(ShoppingListItems::RecipeId)
+                            .to_tbl(Recipes::Table)
+                            .to_col(Recipes::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ShoppingListItems::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ShoppingLists::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum ShoppingLists {
+    Table,
+    Id,
+    UserId,
+    Name,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+pub enum ShoppingListItems {
+    Table,
+    Id,
+    ShoppingListId,
+    IngredientId,
+    RecipeId,
+    Amount,
+    Unit,
+    Checked,
+    CreatedAt,
+    UpdatedAt,
+}
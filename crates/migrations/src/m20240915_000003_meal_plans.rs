@@ -0,0 +1,91 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240107_000001_base::{Recipes, Users};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MealPlans::Table)
+                    .col(ColumnDef::new(MealPlans::Id).uuid().primary_key())
+                    .col(ColumnDef::new(MealPlans::UserId).uuid().not_null())
+                    .col(ColumnDef::new(MealPlans::RecipeId).uuid().not_null())
+                    .col(ColumnDef::new(MealPlans::Date).date().not_null())
+                    .col(ColumnDef::new(MealPlans::Meal).string_len(16).not_null())
+                    .col(
+                        ColumnDef::new(MealPlans::Cooked)
+                            .boolean()
+                            .default(false)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MealPlans::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .col(
+                        ColumnDef::new(MealPlans::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(MealPlans::Table)
+                            .from_col(MealPlans::UserId)
+                            .to_tbl(Users::Table)
+                            .to_col(Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(MealPlans::Table)
+                            .from_col(MealPlans::RecipeId)
+                            .to_tbl(Recipes::Table)
+                            .to_col(Recipes::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-meal_plans-user_id-date")
+                    .table(MealPlans::Table)
+                    .col(MealPlans::UserId)
+                    .col(MealPlans::Date)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MealPlans::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum MealPlans {
+    Table,
+    Id,
+    UserId,
+    RecipeId,
+    Date,
+    Meal,
+    Cooked,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,179 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240107_000001_base::{PantryItems, Recipes, Users};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Households::Table)
+                    .col(ColumnDef::new(Households::Id).uuid().primary_key())
+                    .col(ColumnDef::new(Households::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(Households::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .col(
+                        ColumnDef::new(Households::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(HouseholdMembers::Table)
+                    .col(ColumnDef::new(HouseholdMembers::Id).uuid().primary_key())
+                    .col(
+                        ColumnDef::new(HouseholdMembers::HouseholdId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(HouseholdMembers::UserId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(HouseholdMembers::Role)
+                            .string_len(16)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(HouseholdMembers::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .col(
+                        ColumnDef::new(HouseholdMembers::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(HouseholdMembers::Table)
+                            .from_col(HouseholdMembers::HouseholdId)
+                            .to_tbl(Households::Table)
+                            .to_col(Households::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(HouseholdMembers::Table)
+                            .from_col(HouseholdMembers::UserId)
+                            .to_tbl(Users::Table)
+                            .to_col(Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-household_members-household_id-user_id")
+                    .table(HouseholdMembers::Table)
+                    .col(HouseholdMembers::HouseholdId)
+                    .col(HouseholdMembers::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PantryItems::Table)
+                    .add_column(ColumnDef::new(Alias::new("household_id")).uuid())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk-pantry_items-household_id")
+                    .from(PantryItems::Table, Alias::new("household_id"))
+                    .to(Households::Table, Households::Id)
+                    .on_delete(ForeignKeyAction::SetNull)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Recipes::Table)
+                    .add_column(ColumnDef::new(Alias::new("household_id")).uuid())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk-recipes-household_id")
+                    .from(Recipes::Table, Alias::new("household_id"))
+                    .to(Households::Table, Households::Id)
+                    .on_delete(ForeignKeyAction::SetNull)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Recipes::Table)
+                    .drop_column(Alias::new("household_id"))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PantryItems::Table)
+                    .drop_column(Alias::new("household_id"))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(HouseholdMembers::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Households::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum Households {
+    Table,
+    Id,
+    Name,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+pub enum HouseholdMembers {
+    Table,
+    Id,
+    HouseholdId,
+    UserId,
+    Role,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,123 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240107_000001_base::{Ingredients, Users};
+use crate::m20241001_000004_households::Households;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PantryItemEvents::Table)
+                    .col(ColumnDef::new(PantryItemEvents::Id).uuid().primary_key())
+                    // No foreign key, the history outlives deleted pantry items
+                    .col(
+                        ColumnDef::new(PantryItemEvents::PantryItemId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PantryItemEvents::IngredientId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(PantryItemEvents::UserId).uuid().not_null())
+                    .col(ColumnDef::new(PantryItemEvents::HouseholdId).uuid())
+                    .col(
+                        ColumnDef::new(PantryItemEvents::Kind)
+                            .string_len(16)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(PantryItemEvents::QuantityBefore).integer())
+                    .col(ColumnDef::new(PantryItemEvents::QuantityAfter).integer())
+                    .col(ColumnDef::new(PantryItemEvents::WeightGramsBefore).integer())
+                    .col(ColumnDef::new(PantryItemEvents::WeightGramsAfter).integer())
+                    .col(ColumnDef::new(PantryItemEvents::VolumeMilliLitresBefore).integer())
+                    .col(ColumnDef::new(PantryItemEvents::VolumeMilliLitresAfter).integer())
+                    .col(
+                        ColumnDef::new(PantryItemEvents::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(PantryItemEvents::Table)
+                            .from_col(PantryItemEvents::IngredientId)
+                            .to_tbl(Ingredients::Table)
+                            .to_col(Ingredients::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(PantryItemEvents::Table)
+                            .from_col(PantryItemEvents::UserId)
+                            .to_tbl(Users::Table)
+                            .to_col(Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(PantryItemEvents::Table)
+                            .from_col(PantryItemEvents::HouseholdId)
+                            .to_tbl(Households::Table)
+                            .to_col(Households::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-pantry_item_events-pantry_item_id-created_at")
+                    .table(PantryItemEvents::Table)
+                    .col(PantryItemEvents::PantryItemId)
+                    .col(PantryItemEvents::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-pantry_item_events-ingredient_id-created_at")
+                    .table(PantryItemEvents::Table)
+                    .col(PantryItemEvents::IngredientId)
+                    .col(PantryItemEvents::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PantryItemEvents::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum PantryItemEvents {
+    Table,
+    Id,
+    PantryItemId,
+    IngredientId,
+    UserId,
+    HouseholdId,
+    Kind,
+    QuantityBefore,
+    QuantityAfter,
+    WeightGramsBefore,
+    WeightGramsAfter,
+    VolumeMilliLitresBefore,
+    VolumeMilliLitresAfter,
+    CreatedAt,
+}
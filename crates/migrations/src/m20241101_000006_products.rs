@@ -0,0 +1,68 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240107_000001_base::Ingredients;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Products::Table)
+                    .col(ColumnDef::new(Products::Id).uuid().primary_key())
+                    .col(
+                        ColumnDef::new(Products::Gtin)
+                            .string_len(14)
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(Products::IngredientId).uuid().not_null())
+                    .col(ColumnDef::new(Products::Name).string())
+                    // Default package size, at most one of these is set
+                    .col(ColumnDef::new(Products::Quantity).integer())
+                    .col(ColumnDef::new(Products::WeightGrams).integer())
+                    .col(ColumnDef::new(Products::VolumeMilliLitres).integer())
+                    .col(
+                        ColumnDef::new(Products::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(Products::Table)
+                            .from_col(Products::IngredientId)
+                            .to_tbl(Ingredients::Table)
+                            .to_col(Ingredients::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Products::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum Products {
+    Table,
+    Id,
+    Gtin,
+    IngredientId,
+    Name,
+    Quantity,
+    WeightGrams,
+    VolumeMilliLitres,
+    CreatedAt,
+}
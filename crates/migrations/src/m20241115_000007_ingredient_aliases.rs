@@ -0,0 +1,75 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240107_000001_base::{Ingredients, Users};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(IngredientAliases::Table)
+                    .col(ColumnDef::new(IngredientAliases::Id).uuid().primary_key())
+                    // Stored normalised: lowercase and singular
+                    .col(
+                        ColumnDef::new(IngredientAliases::Alias)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(IngredientAliases::IngredientId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    // User who confirmed the alias
+                    .col(ColumnDef::new(IngredientAliases::UserId).uuid())
+                    .col(
+                        ColumnDef::new(IngredientAliases::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(IngredientAliases::Table)
+                            .from_col(IngredientAliases::IngredientId)
+                            .to_tbl(Ingredients::Table)
+                            .to_col(Ingredients::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(IngredientAliases::Table)
+                            .from_col(IngredientAliases::UserId)
+                            .to_tbl(Users::Table)
+                            .to_col(Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(IngredientAliases::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum IngredientAliases {
+    Table,
+    Id,
+    Alias,
+    IngredientId,
+    UserId,
+    CreatedAt,
+}
@@ -0,0 +1,61 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240107_000001_base::PantryItems;
+
+/// Name Postgres gave the unique key on `pantry_items.ingredient_id` in the base migration
+const INGREDIENT_ID_KEY: &str = "pantry_items_ingredient_id_key";
+const LOT_INDEX: &str = "idx-pantry_items-user_id-ingredient_id-expiration_date";
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        // Each pantry item is a lot, a user can hold several lots of an ingredient
+        db.execute_unprepared(&format!(
+            r#"ALTER TABLE "pantry_items" DROP CONSTRAINT IF EXISTS "{INGREDIENT_ID_KEY}""#
+        ))
+        .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PantryItems::Table)
+                    .add_column(ColumnDef::new(Alias::new("purchase_date")).date())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Lots of an ingredient that expire on the same day are one lot.
+        // Lots without an expiration date are compared as equal, Postgres 13
+        // has no NULLS NOT DISTINCT.
+        db.execute_unprepared(&format!(
+            r#"CREATE UNIQUE INDEX "{LOT_INDEX}" ON "pantry_items"
+            ("user_id", "ingredient_id", COALESCE("expiration_date", 'infinity'::date))"#
+        ))
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared(&format!(r#"DROP INDEX IF EXISTS "{LOT_INDEX}""#))
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PantryItems::Table)
+                    .drop_column(Alias::new("purchase_date"))
+                    .to_owned(),
+            )
+            .await?;
+        db.execute_unprepared(&format!(
+            r#"ALTER TABLE "pantry_items" ADD CONSTRAINT "{INGREDIENT_ID_KEY}" UNIQUE ("ingredient_id")"#
+        ))
+        .await?;
+        Ok(())
+    }
+}
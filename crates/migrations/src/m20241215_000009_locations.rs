@@ -0,0 +1,137 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240107_000001_base::{Ingredients, PantryItems, Users};
+use crate::m20241001_000004_households::Households;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Locations::Table)
+                    .col(ColumnDef::new(Locations::Id).uuid().primary_key())
+                    .col(ColumnDef::new(Locations::Name).string().not_null())
+                    // Items moved into a freezer may get a later expiration date
+                    .col(
+                        ColumnDef::new(Locations::Freezer)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(ColumnDef::new(Locations::UserId).uuid().not_null())
+                    .col(ColumnDef::new(Locations::HouseholdId).uuid())
+                    .col(
+                        ColumnDef::new(Locations::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .col(
+                        ColumnDef::new(Locations::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(Locations::Table)
+                            .from_col(Locations::UserId)
+                            .to_tbl(Users::Table)
+                            .to_col(Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(Locations::Table)
+                            .from_col(Locations::HouseholdId)
+                            .to_tbl(Households::Table)
+                            .to_col(Households::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-locations-user_id-name")
+                    .table(Locations::Table)
+                    .col(Locations::UserId)
+                    .col(Locations::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PantryItems::Table)
+                    .add_column(ColumnDef::new(Alias::new("location_id")).uuid())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk-pantry_items-location_id")
+                    .from(PantryItems::Table, Alias::new("location_id"))
+                    .to(Locations::Table, Locations::Id)
+                    .on_delete(ForeignKeyAction::SetNull)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Days an ingredient keeps once frozen
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Ingredients::Table)
+                    .add_column(ColumnDef::new(Alias::new("freezer_shelf_life_days")).integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Ingredients::Table)
+                    .drop_column(Alias::new("freezer_shelf_life_days"))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PantryItems::Table)
+                    .drop_column(Alias::new("location_id"))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Locations::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum Locations {
+    Table,
+    Id,
+    Name,
+    Freezer,
+    UserId,
+    HouseholdId,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,76 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240107_000001_base::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ApiTokens::Table)
+                    .col(ColumnDef::new(ApiTokens::Id).uuid().primary_key())
+                    .col(ColumnDef::new(ApiTokens::UserId).uuid().not_null())
+                    .col(ColumnDef::new(ApiTokens::Name).string().not_null())
+                    // Only the argon2 hash of the secret is kept, the token is shown once
+                    .col(ColumnDef::new(ApiTokens::SecretHash).string().not_null())
+                    .col(
+                        ColumnDef::new(ApiTokens::ReadOnly)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(ApiTokens::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(ApiTokens::Table)
+                            .from_col(ApiTokens::UserId)
+                            .to_tbl(Users::Table)
+                            .to_col(Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-api_tokens-user_id-name")
+                    .table(ApiTokens::Table)
+                    .col(ApiTokens::UserId)
+                    .col(ApiTokens::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ApiTokens::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum ApiTokens {
+    Table,
+    Id,
+    UserId,
+    Name,
+    SecretHash,
+    ReadOnly,
+    CreatedAt,
+}
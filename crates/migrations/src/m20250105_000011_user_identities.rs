@@ -0,0 +1,69 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20240107_000001_base::Users;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserIdentities::Table)
+                    .col(ColumnDef::new(UserIdentities::Id).uuid().primary_key())
+                    .col(ColumnDef::new(UserIdentities::UserId).uuid().not_null())
+                    // OpenID Connect `iss` and `sub` claims, together they identify the user
+                    .col(ColumnDef::new(UserIdentities::Issuer).string().not_null())
+                    .col(ColumnDef::new(UserIdentities::Subject).string().not_null())
+                    .col(
+                        ColumnDef::new(UserIdentities::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(SimpleExpr::Keyword(Keyword::CurrentTimestamp)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(UserIdentities::Table)
+                            .from_col(UserIdentities::UserId)
+                            .to_tbl(Users::Table)
+                            .to_col(Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-user_identities-issuer-subject")
+                    .table(UserIdentities::Table)
+                    .col(UserIdentities::Issuer)
+                    .col(UserIdentities::Subject)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(UserIdentities::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum UserIdentities {
+    Table,
+    Id,
+    UserId,
+    Issuer,
+    Subject,
+    CreatedAt,
+}
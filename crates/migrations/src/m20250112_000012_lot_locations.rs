@@ -0,0 +1,40 @@
+use sea_orm_migration::prelude::*;
+
+const LOT_INDEX: &str = "idx-pantry_items-user_id-ingredient_id-expiration_date";
+const LOCATION_LOT_INDEX: &str =
+    "idx-pantry_items-user_id-ingredient_id-location_id-expiration_date";
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared(&format!(r#"DROP INDEX IF EXISTS "{LOT_INDEX}""#))
+            .await?;
+        // Lots expiring on the same day are only one lot at the same location, the fridge
+        // and the freezer can both hold some. Lots without a location are compared as equal.
+        db.execute_unprepared(&format!(
+            r#"CREATE UNIQUE INDEX "{LOCATION_LOT_INDEX}" ON "pantry_items"
+            ("user_id", "ingredient_id",
+            COALESCE("location_id", '00000000-0000-0000-0000-000000000000'::uuid),
+            COALESCE("expiration_date", 'infinity'::date))"#
+        ))
+        .await?;
+        Ok(())
+    }
+
+    /// Fails while lots of an ingredient at different locations expire on the same day
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared(&format!(r#"DROP INDEX IF EXISTS "{LOCATION_LOT_INDEX}""#))
+            .await?;
+        db.execute_unprepared(&format!(
+            r#"CREATE UNIQUE INDEX "{LOT_INDEX}" ON "pantry_items"
+            ("user_id", "ingredient_id", COALESCE("expiration_date", 'infinity'::date))"#
+        ))
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use db_entities::api_tokens::Model;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CreateDto {
+    pub user_id: Uuid,
+    pub name: String,
+    pub secret_hash: String,
+    pub read_only: bool,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct ListParamsDto {
+    pub user_id: Option<Uuid>,
+    pub limit: u64,
+    pub offset: u64,
+}
+
+#[derive(Serialize, Debug, Clone, Eq, PartialEq)]
+pub struct ApiTokenDto {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub secret_hash: String,
+    pub read_only: bool,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct ApiTokensListDto {
+    pub items: Vec<ApiTokenDto>,
+}
+
+impl From<CreateDto> for Model {
+    fn from(value: CreateDto) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id: value.user_id,
+            name: value.name,
+            secret_hash: value.secret_hash,
+            read_only: value.read_only,
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+}
+
+impl From<Model> for ApiTokenDto {
+    fn from(value: Model) -> Self {
+        Self {
+            id: value.id,
+            user_id: value.user_id,
+            name: value.name,
+            secret_hash: value.secret_hash,
+            read_only: value.read_only,
+            created_at: value.created_at,
+        }
+    }
+}
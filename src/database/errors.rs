@@ -42,6 +42,10 @@ pub enum GetError {
 pub enum UpdateError {
     #[error("Item with id {id:?} not found in database")]
     NotFound { id: Uuid },
+    #[error("Could not update {id:?}: {reason}")]
+    Conflict { id: Uuid, reason: String },
+    #[error("Invalid update of {id:?}: {reason}")]
+    Invalid { id: Uuid, reason: String },
     #[error("Unexpected error during {id:?} item update: {error}")]
     Unexpected { id: Uuid, error: AnyError },
 }
@@ -54,6 +58,16 @@ pub enum DeleteError {
     Unexpected { id: Uuid, error: AnyError },
 }
 
+#[derive(Error, Debug)]
+pub enum MergeError {
+    #[error("Item with id {id:?} not found in database")]
+    NotFound { id: Uuid },
+    #[error("Could not merge into {id:?}: {reason}")]
+    Conflict { id: Uuid, reason: String },
+    #[error("Unexpected error during merge into {id:?}: {error}")]
+    Unexpected { id: Uuid, error: AnyError },
+}
+
 #[derive(Error, Debug)]
 pub enum HealthcheckError {
     #[error("Unexpected error during healthcheck: {error}")]
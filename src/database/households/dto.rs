@@ -0,0 +1,123 @@
+use chrono::{NaiveDateTime, Utc};
+use sea_orm::FromQueryResult;
+use serde::Serialize;
+use uuid::Uuid;
+
+pub use db_entities::household_members::HouseholdRole;
+use db_entities::households::Model;
+
+#[derive(Debug, Clone)]
+pub struct CreateDto {
+    pub name: String,
+    /// User that becomes the household owner
+    pub owner_id: Uuid,
+}
+
+#[derive(Debug, Clone)]
+pub struct UpdateDto {
+    pub name: String,
+}
+
+#[derive(Debug, Default)]
+pub struct ListParamsDto {
+    /// Only households the user is a member of
+    pub user_id: Option<Uuid>,
+    pub limit: u64,
+    pub offset: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateMemberDto {
+    pub household_id: Uuid,
+    pub user_id: Uuid,
+    pub role: HouseholdRole,
+}
+
+#[derive(Serialize, Debug, Clone, Eq, PartialEq)]
+pub struct HouseholdDto {
+    pub id: Uuid,
+    pub name: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct HouseholdsListDto {
+    pub items: Vec<HouseholdDto>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct HouseholdMemberDto {
+    pub id: Uuid,
+    pub household_id: Uuid,
+    pub user_id: Uuid,
+    pub role: HouseholdRole,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, FromQueryResult)]
+pub struct HouseholdMemberJoinDto {
+    pub id: Uuid,
+    pub household_id: Uuid,
+    pub user_id: Uuid,
+    pub user_name: String,
+    pub role: HouseholdRole,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug)]
+pub struct HouseholdMembersListDto {
+    pub items: Vec<HouseholdMemberJoinDto>,
+}
+
+impl From<CreateDto> for Model {
+    fn from(value: CreateDto) -> Self {
+        let now = Utc::now().naive_utc();
+        Self {
+            id: Uuid::new_v4(),
+            name: value.name,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+impl From<Model> for HouseholdDto {
+    fn from(value: Model) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+impl From<CreateMemberDto> for db_entities::household_members::Model {
+    fn from(value: CreateMemberDto) -> Self {
+        let now = Utc::now().naive_utc();
+        Self {
+            id: Uuid::new_v4(),
+            household_id: value.household_id,
+            user_id: value.user_id,
+            role: value.role,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+impl From<db_entities::household_members::Model> for HouseholdMemberDto {
+    fn from(value: db_entities::household_members::Model) -> Self {
+        Self {
+            id: value.id,
+            household_id: value.household_id,
+            user_id: value.user_id,
+            role: value.role,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
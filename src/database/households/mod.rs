@@ -0,0 +1,357 @@
+pub mod dto;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, DbErr, EntityTrait, JoinType, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, RelationTrait, Select, Set, TransactionTrait,
+};
+use uuid::Uuid;
+
+use self::dto::{
+    CreateDto, CreateMemberDto, HouseholdDto, HouseholdMemberDto, HouseholdMemberJoinDto,
+    HouseholdMembersListDto, HouseholdRole, HouseholdsListDto, ListParamsDto, UpdateDto,
+};
+use crate::database::dto::MetadataDto;
+use crate::database::errors::{error_code, UNIQUE_VIOLATION_CODE};
+use crate::database::{
+    errors::{CreateError, DeleteError, GetError, ListError, UpdateError},
+    DBClient,
+};
+use db_entities::households::{ActiveModel, Column, Entity, Model};
+use migrations::{Query, SelectStatement};
+
+#[async_trait]
+pub trait DatabaseCRUD {
+    /// Creates the household with `owner_id` as its owner
+    async fn create_household(&self, request: CreateDto) -> Result<HouseholdDto, CreateError>;
+    async fn get_household(&self, id: Uuid) -> Result<HouseholdDto, GetError>;
+    async fn list_households(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<HouseholdsListDto, ListError>;
+    async fn get_households_metadata(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<MetadataDto, ListError>;
+    async fn update_household(
+        &self,
+        id: Uuid,
+        request: UpdateDto,
+    ) -> Result<HouseholdDto, UpdateError>;
+    async fn delete_household(&self, id: Uuid) -> Result<(), DeleteError>;
+    /// `CreateError` holds the `user_id`
+    async fn create_household_member(
+        &self,
+        request: CreateMemberDto,
+    ) -> Result<HouseholdMemberDto, CreateError>;
+    /// `GetError` holds the `user_id`
+    async fn get_household_member(
+        &self,
+        household_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<HouseholdMemberDto, GetError>;
+    async fn list_household_members(
+        &self,
+        household_id: Uuid,
+    ) -> Result<HouseholdMembersListDto, ListError>;
+    /// `UpdateError` holds the `user_id`
+    async fn update_household_member(
+        &self,
+        household_id: Uuid,
+        user_id: Uuid,
+        role: HouseholdRole,
+    ) -> Result<HouseholdMemberDto, UpdateError>;
+    /// `DeleteError` holds the `user_id`
+    async fn delete_household_member(
+        &self,
+        household_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), DeleteError>;
+}
+
+#[async_trait]
+impl DatabaseCRUD for DBClient {
+    async fn create_household(&self, request: CreateDto) -> Result<HouseholdDto, CreateError> {
+        let owner_id = request.owner_id;
+        let model: Model = request.into();
+        let id = model.id;
+        let active_model: ActiveModel = model.into();
+        let txn = self
+            .database_connection
+            .begin()
+            .await
+            .map_err(|err| CreateError::Unexpected { error: err.into() })?;
+        let household = active_model.insert(&txn).await.map_err(|err| {
+            if error_code(&err) == Some(UNIQUE_VIOLATION_CODE.to_owned()) {
+                CreateError::AlreadyExist { id }
+            } else {
+                CreateError::Unexpected { error: err.into() }
+            }
+        })?;
+        let owner: db_entities::household_members::Model = CreateMemberDto {
+            household_id: id,
+            user_id: owner_id,
+            role: HouseholdRole::Owner,
+        }
+        .into();
+        db_entities::household_members::ActiveModel::from(owner)
+            .insert(&txn)
+            .await
+            .map_err(|err| CreateError::Unexpected { error: err.into() })?;
+        txn.commit()
+            .await
+            .map_err(|err| CreateError::Unexpected { error: err.into() })?;
+        Ok(household.into())
+    }
+    async fn get_household(&self, id: Uuid) -> Result<HouseholdDto, GetError> {
+        Ok(Entity::find_by_id(id)
+            .one(&self.database_connection)
+            .await
+            .map_err(|err| GetError::Unexpected {
+                id,
+                error: err.into(),
+            })?
+            .ok_or(GetError::NotFound { id })?
+            .into())
+    }
+    async fn list_households(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<HouseholdsListDto, ListError> {
+        Ok(HouseholdsListDto {
+            items: list_entity(list_params)
+                .limit(list_params.limit)
+                .offset(list_params.offset)
+                .order_by_asc(Column::Name)
+                .all(&self.database_connection)
+                .await
+                .map_err(|err| ListError::Unexpected { error: err.into() })?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        })
+    }
+    async fn get_households_metadata(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<MetadataDto, ListError> {
+        let total_count = list_entity(list_params)
+            .count(&self.database_connection)
+            .await
+            .map_err(|err| ListError::Unexpected { error: err.into() })?;
+        Ok(MetadataDto {
+            page: list_params.offset / list_params.limit + 1,
+            per_page: list_params.limit,
+            page_count: total_count / list_params.limit + 1,
+            total_count,
+        })
+    }
+    async fn update_household(
+        &self,
+        id: Uuid,
+        request: UpdateDto,
+    ) -> Result<HouseholdDto, UpdateError> {
+        let household: Model = Entity::find_by_id(id)
+            .one(&self.database_connection)
+            .await
+            .map_err(|err| UpdateError::Unexpected {
+                id,
+                error: err.into(),
+            })?
+            .ok_or(UpdateError::NotFound { id })?;
+        let mut household: ActiveModel = household.into();
+        household.name = Set(request.name);
+        household.updated_at = Set(Utc::now().naive_utc());
+
+        Ok(Entity::update(household)
+            .filter(Column::Id.eq(id))
+            .exec(&self.database_connection)
+            .await
+            .map_err(|err| {
+                if let DbErr::RecordNotUpdated = err {
+                    UpdateError::NotFound { id }
+                } else {
+                    UpdateError::Unexpected {
+                        id,
+                        error: err.into(),
+                    }
+                }
+            })?
+            .into())
+    }
+    async fn delete_household(&self, id: Uuid) -> Result<(), DeleteError> {
+        if Entity::delete_by_id(id)
+            .exec(&self.database_connection)
+            .await
+            .map_err(|err| DeleteError::Unexpected {
+                id,
+                error: err.into(),
+            })?
+            .rows_affected
+            == 0
+        {
+            Err(DeleteError::NotFound { id })
+        } else {
+            Ok(())
+        }
+    }
+    async fn create_household_member(
+        &self,
+        request: CreateMemberDto,
+    ) -> Result<HouseholdMemberDto, CreateError> {
+        let id = request.user_id;
+        let model: db_entities::household_members::Model = request.into();
+        let active_model: db_entities::household_members::ActiveModel = model.into();
+        Ok(active_model
+            .insert(&self.database_connection)
+            .await
+            .map_err(|err| {
+                if error_code(&err) == Some(UNIQUE_VIOLATION_CODE.to_owned()) {
+                    CreateError::AlreadyExist { id }
+                } else {
+                    CreateError::Unexpected { error: err.into() }
+                }
+            })?
+            .into())
+    }
+    async fn get_household_member(
+        &self,
+        household_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<HouseholdMemberDto, GetError> {
+        Ok(find_member(household_id, user_id)
+            .one(&self.database_connection)
+            .await
+            .map_err(|err| GetError::Unexpected {
+                id: user_id,
+                error: err.into(),
+            })?
+            .ok_or(GetError::NotFound { id: user_id })?
+            .into())
+    }
+    async fn list_household_members(
+        &self,
+        household_id: Uuid,
+    ) -> Result<HouseholdMembersListDto, ListError> {
+        Ok(HouseholdMembersListDto {
+            items: db_entities::household_members::Entity::find()
+                .filter(db_entities::household_members::Column::HouseholdId.eq(household_id))
+                .join(
+                    JoinType::InnerJoin,
+                    db_entities::household_members::Relation::Users.def(),
+                )
+                .column_as(db_entities::users::Column::Name, "user_name")
+                .order_by_asc(db_entities::users::Column::Name)
+                .into_model::<HouseholdMemberJoinDto>()
+                .all(&self.database_connection)
+                .await
+                .map_err(|err| ListError::Unexpected { error: err.into() })?,
+        })
+    }
+    async fn update_household_member(
+        &self,
+        household_id: Uuid,
+        user_id: Uuid,
+        role: HouseholdRole,
+    ) -> Result<HouseholdMemberDto, UpdateError> {
+        let member = find_member(household_id, user_id)
+            .one(&self.database_connection)
+            .await
+            .map_err(|err| UpdateError::Unexpected {
+                id: user_id,
+                error: err.into(),
+            })?
+            .ok_or(UpdateError::NotFound { id: user_id })?;
+        let mut member: db_entities::household_members::ActiveModel = member.into();
+        member.role = Set(role);
+        member.updated_at = Set(Utc::now().naive_utc());
+
+        Ok(member
+            .update(&self.database_connection)
+            .await
+            .map_err(|err| UpdateError::Unexpected {
+                id: user_id,
+                error: err.into(),
+            })?
+            .into())
+    }
+    async fn delete_household_member(
+        &self,
+        household_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), DeleteError> {
+        if db_entities::household_members::Entity::delete_many()
+            .filter(db_entities::household_members::Column::HouseholdId.eq(household_id))
+            .filter(db_entities::household_members::Column::UserId.eq(user_id))
+            .exec(&self.database_connection)
+            .await
+            .map_err(|err| DeleteError::Unexpected {
+                id: user_id,
+                error: err.into(),
+            })?
+            .rows_affected
+            == 0
+        {
+            Err(DeleteError::NotFound { id: user_id })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Rows owned by `user_id` or shared with one of the user's households
+pub(crate) fn visible_to<C: ColumnTrait>(
+    user_column: C,
+    household_column: C,
+    user_id: Uuid,
+) -> Condition {
+    Condition::any()
+        .add(user_column.eq(user_id))
+        .add(household_column.in_subquery(household_ids_of(user_id)))
+}
+
+/// Rows owned by `user_id` or shared with a household the user is an owner or member of,
+/// viewers may not change them
+pub(crate) fn writable_by<C: ColumnTrait>(
+    user_column: C,
+    household_column: C,
+    user_id: Uuid,
+) -> Condition {
+    Condition::any().add(user_column.eq(user_id)).add(
+        household_column.in_subquery(
+            household_ids_of(user_id)
+                .and_where(
+                    db_entities::household_members::Column::Role
+                        .is_in([HouseholdRole::Owner, HouseholdRole::Member]),
+                )
+                .to_owned(),
+        ),
+    )
+}
+
+/// Ids of the households `user_id` is a member of, for use in subqueries
+pub(crate) fn household_ids_of(user_id: Uuid) -> SelectStatement {
+    Query::select()
+        .column(db_entities::household_members::Column::HouseholdId)
+        .from(db_entities::household_members::Entity)
+        .and_where(db_entities::household_members::Column::UserId.eq(user_id))
+        .to_owned()
+}
+
+fn find_member(
+    household_id: Uuid,
+    user_id: Uuid,
+) -> Select<db_entities::household_members::Entity> {
+    db_entities::household_members::Entity::find()
+        .filter(db_entities::household_members::Column::HouseholdId.eq(household_id))
+        .filter(db_entities::household_members::Column::UserId.eq(user_id))
+}
+
+fn list_entity(list_params: &ListParamsDto) -> Select<Entity> {
+    let mut entity = Entity::find();
+    if let Some(user_id) = list_params.user_id {
+        entity = entity.filter(Column::Id.in_subquery(household_ids_of(user_id)));
+    }
+    entity
+}
@@ -0,0 +1,60 @@
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use db_entities::ingredient_aliases::Model;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CreateDto {
+    /// Normalised with `matching::normalize_name`
+    pub alias: String,
+    pub ingredient_id: Uuid,
+    pub user_id: Uuid,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct ListParamsDto {
+    pub ingredient_id: Option<Uuid>,
+    pub limit: u64,
+    pub offset: u64,
+}
+
+#[derive(Serialize, Debug, Clone, Eq, PartialEq)]
+pub struct IngredientAliasDto {
+    pub id: Uuid,
+    pub alias: String,
+    pub ingredient_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct IngredientAliasesListDto {
+    pub items: Vec<IngredientAliasDto>,
+}
+
+impl From<CreateDto> for Model {
+    fn from(value: CreateDto) -> Self {
+        let now = Utc::now().naive_utc();
+
+        Self {
+            id: Uuid::new_v4(),
+            alias: value.alias,
+            ingredient_id: value.ingredient_id,
+            user_id: Some(value.user_id),
+            created_at: now,
+        }
+    }
+}
+
+impl From<Model> for IngredientAliasDto {
+    fn from(value: Model) -> Self {
+        Self {
+            id: value.id,
+            alias: value.alias,
+            ingredient_id: value.ingredient_id,
+            user_id: value.user_id,
+            created_at: value.created_at,
+        }
+    }
+}
@@ -9,6 +9,12 @@ pub struct CreateDto {
     pub name: String,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+pub struct UpdateDto {
+    pub name: String,
+    pub freezer_shelf_life_days: Option<i32>,
+}
+
 #[derive(Deserialize, Debug, Default)]
 pub struct ListParamsDto {
     pub name: Option<String>,
@@ -21,6 +27,8 @@ pub struct ListParamsDto {
 pub struct IngredientDto {
     pub id: Uuid,
     pub name: String,
+    /// Days the ingredient keeps once frozen
+    pub freezer_shelf_life_days: Option<i32>,
     pub created_at: NaiveDateTime,
 }
 
@@ -29,6 +37,17 @@ pub struct IngredientsListDto {
     pub items: Vec<IngredientDto>,
 }
 
+/// Result of merging duplicate ingredients into one
+#[derive(Serialize, Debug)]
+pub struct MergeDto {
+    pub ingredient: IngredientDto,
+    pub merged_ids: Vec<Uuid>,
+    pub recipe_ingredients_moved: u64,
+    pub recipe_ingredients_combined: u64,
+    pub pantry_items_moved: u64,
+    pub pantry_items_combined: u64,
+}
+
 impl From<CreateDto> for Model {
     fn from(value: CreateDto) -> Self {
         let now = Utc::now().naive_utc();
@@ -36,6 +55,7 @@ impl From<CreateDto> for Model {
         Self {
             id: Uuid::new_v4(),
             name: value.name,
+            freezer_shelf_life_days: None,
             created_at: now,
         }
     }
@@ -46,6 +66,7 @@ impl From<Model> for IngredientDto {
         Self {
             id: value.id,
             name: value.name,
+            freezer_shelf_life_days: value.freezer_shelf_life_days,
             created_at: value.created_at,
         }
     }
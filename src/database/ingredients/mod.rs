@@ -1,35 +1,60 @@
 pub mod dto;
 
 use async_trait::async_trait;
+use chrono::Utc;
 use sea_orm::{
-    ActiveModelTrait, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect,
-    Select,
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, Select, Set, TransactionTrait,
 };
 use uuid::Uuid;
 
-use self::dto::{CreateDto, IngredientDto, IngredientsListDto, ListParamsDto};
+use self::dto::{CreateDto, IngredientDto, IngredientsListDto, ListParamsDto, MergeDto, UpdateDto};
 use crate::database::dto::MetadataDto;
-use crate::database::errors::{error_code, UNIQUE_VIOLATION_CODE};
+use crate::database::errors::{error_code, MergeError, UNIQUE_VIOLATION_CODE};
+use crate::database::ingredient_aliases::dto::CreateDto as AliasCreateDto;
+use crate::database::pantry_item_events::{self, dto::EventKind};
 use crate::database::{
-    errors::{CreateError, DeleteError, GetError, ListError},
+    errors::{CreateError, DeleteError, GetError, ListError, UpdateError},
     DBClient,
 };
+use crate::matching::normalize_name;
+use crate::units::{Amount, Measure, Measurement};
 use db_entities::ingredients::{ActiveModel, Column, Entity, Model};
-use migrations::{Expr, Func};
+use db_entities::{
+    ingredient_aliases, pantry_items, products, recipe_ingredients, shopping_list_items,
+};
+use migrations::{Expr, Func, OnConflict};
 
 #[async_trait]
 pub trait DatabaseCRUD {
     async fn create_ingredient(&self, request: CreateDto) -> Result<IngredientDto, CreateError>;
     async fn get_ingredient(&self, id: Uuid) -> Result<IngredientDto, GetError>;
+    /// Returns the ingredient with the name, ignoring case, creating it if there is none
+    async fn get_or_create_ingredient(&self, name: String) -> Result<IngredientDto, CreateError>;
     async fn list_ingredients(
         &self,
         list_params: &ListParamsDto,
     ) -> Result<IngredientsListDto, ListError>;
+    /// Returns every ingredient, for matching ingredient names
+    async fn list_all_ingredients(&self) -> Result<IngredientsListDto, ListError>;
     async fn get_ingredients_metadata(
         &self,
         list_params: &ListParamsDto,
     ) -> Result<MetadataDto, ListError>;
+    async fn update_ingredient(
+        &self,
+        id: Uuid,
+        request: UpdateDto,
+    ) -> Result<IngredientDto, UpdateError>;
     async fn delete_ingredient(&self, id: Uuid) -> Result<(), DeleteError>;
+    /// Moves every reference to the duplicates onto the ingredient and deletes the duplicates.
+    /// `user_id` is recorded as the user who changed the pantry items.
+    async fn merge_ingredients(
+        &self,
+        id: Uuid,
+        duplicate_ids: Vec<Uuid>,
+        user_id: Uuid,
+    ) -> Result<MergeDto, MergeError>;
 }
 
 #[async_trait]
@@ -61,6 +86,29 @@ impl DatabaseCRUD for DBClient {
             .ok_or(GetError::NotFound { id })?
             .into())
     }
+    async fn get_or_create_ingredient(&self, name: String) -> Result<IngredientDto, CreateError> {
+        let list_params = ListParamsDto {
+            name: Some(name.clone()),
+            ..Default::default()
+        };
+        if let Some(ingredient) = list_entity(&list_params)
+            .one(&self.database_connection)
+            .await
+            .map_err(|err| CreateError::Unexpected { error: err.into() })?
+        {
+            return Ok(ingredient.into());
+        }
+        match self.create_ingredient(CreateDto { name }).await {
+            // Created concurrently since the lookup
+            Err(CreateError::AlreadyExist { id }) => list_entity(&list_params)
+                .one(&self.database_connection)
+                .await
+                .map_err(|err| CreateError::Unexpected { error: err.into() })?
+                .map(Into::into)
+                .ok_or(CreateError::AlreadyExist { id }),
+            result => result,
+        }
+    }
     async fn list_ingredients(
         &self,
         list_params: &ListParamsDto,
@@ -78,6 +126,17 @@ impl DatabaseCRUD for DBClient {
                 .collect(),
         })
     }
+    async fn list_all_ingredients(&self) -> Result<IngredientsListDto, ListError> {
+        Ok(IngredientsListDto {
+            items: Entity::find()
+                .all(&self.database_connection)
+                .await
+                .map_err(|err| ListError::Unexpected { error: err.into() })?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        })
+    }
     async fn get_ingredients_metadata(
         &self,
         list_params: &ListParamsDto,
@@ -93,6 +152,39 @@ impl DatabaseCRUD for DBClient {
             total_count,
         })
     }
+    async fn update_ingredient(
+        &self,
+        id: Uuid,
+        request: UpdateDto,
+    ) -> Result<IngredientDto, UpdateError> {
+        let ingredient: Model = Entity::find_by_id(id)
+            .one(&self.database_connection)
+            .await
+            .map_err(|err| UpdateError::Unexpected {
+                id,
+                error: err.into(),
+            })?
+            .ok_or(UpdateError::NotFound { id })?;
+        let mut ingredient: ActiveModel = ingredient.into();
+        ingredient.name = Set(request.name);
+        ingredient.freezer_shelf_life_days = Set(request.freezer_shelf_life_days);
+
+        Ok(Entity::update(ingredient)
+            .filter(Column::Id.eq(id))
+            .exec(&self.database_connection)
+            .await
+            .map_err(|err| {
+                if let DbErr::RecordNotUpdated = err {
+                    UpdateError::NotFound { id }
+                } else {
+                    UpdateError::Unexpected {
+                        id,
+                        error: err.into(),
+                    }
+                }
+            })?
+            .into())
+    }
     async fn delete_ingredient(&self, id: Uuid) -> Result<(), DeleteError> {
         if Entity::delete_by_id(id)
             .exec(&self.database_connection)
@@ -109,16 +201,349 @@ impl DatabaseCRUD for DBClient {
             Ok(())
         }
     }
+    async fn merge_ingredients(
+        &self,
+        id: Uuid,
+        duplicate_ids: Vec<Uuid>,
+        user_id: Uuid,
+    ) -> Result<MergeDto, MergeError> {
+        let txn = self
+            .database_connection
+            .begin()
+            .await
+            .map_err(|err| unexpected(id, err))?;
+        let merged = merge(&txn, id, duplicate_ids, user_id).await?;
+        txn.commit().await.map_err(|err| unexpected(id, err))?;
+        Ok(merged)
+    }
+}
+
+async fn merge<C: ConnectionTrait>(
+    db: &C,
+    id: Uuid,
+    mut duplicate_ids: Vec<Uuid>,
+    user_id: Uuid,
+) -> Result<MergeDto, MergeError> {
+    if duplicate_ids.contains(&id) {
+        return Err(MergeError::Conflict {
+            id,
+            reason: "an ingredient cannot be merged into itself".to_owned(),
+        });
+    }
+    duplicate_ids.sort_unstable();
+    duplicate_ids.dedup();
+    let ingredient = Entity::find_by_id(id)
+        .one(db)
+        .await
+        .map_err(|err| unexpected(id, err))?
+        .ok_or(MergeError::NotFound { id })?;
+    let duplicates = Entity::find()
+        .filter(Column::Id.is_in(duplicate_ids.clone()))
+        .all(db)
+        .await
+        .map_err(|err| unexpected(id, err))?;
+    if let Some(missing) = duplicate_ids.iter().find(|duplicate_id| {
+        !duplicates
+            .iter()
+            .any(|duplicate| duplicate.id == **duplicate_id)
+    }) {
+        return Err(MergeError::NotFound { id: *missing });
+    }
+    let mut merged = MergeDto {
+        ingredient: ingredient.clone().into(),
+        merged_ids: duplicate_ids.clone(),
+        recipe_ingredients_moved: 0,
+        recipe_ingredients_combined: 0,
+        pantry_items_moved: 0,
+        pantry_items_combined: 0,
+    };
+
+    merge_recipe_ingredients(db, id, &duplicate_ids, &mut merged).await?;
+    merge_pantry_items(db, id, &duplicate_ids, user_id, &mut merged).await?;
+
+    // References without uniqueness constraints are simply moved
+    pantry_item_events::repoint_ingredient(db, &duplicate_ids, id)
+        .await
+        .map_err(|err| unexpected(id, err))?;
+    shopping_list_items::Entity::update_many()
+        .col_expr(shopping_list_items::Column::IngredientId, Expr::value(id))
+        .filter(shopping_list_items::Column::IngredientId.is_in(duplicate_ids.clone()))
+        .exec(db)
+        .await
+        .map_err(|err| unexpected(id, err))?;
+    products::Entity::update_many()
+        .col_expr(products::Column::IngredientId, Expr::value(id))
+        .filter(products::Column::IngredientId.is_in(duplicate_ids.clone()))
+        .exec(db)
+        .await
+        .map_err(|err| unexpected(id, err))?;
+    ingredient_aliases::Entity::update_many()
+        .col_expr(ingredient_aliases::Column::IngredientId, Expr::value(id))
+        .filter(ingredient_aliases::Column::IngredientId.is_in(duplicate_ids.clone()))
+        .exec(db)
+        .await
+        .map_err(|err| unexpected(id, err))?;
+
+    // The duplicate names keep matching the surviving ingredient
+    let name = normalize_name(&ingredient.name);
+    for duplicate in &duplicates {
+        let alias = normalize_name(&duplicate.name);
+        if alias.is_empty() || alias == name {
+            continue;
+        }
+        let model: ingredient_aliases::Model = AliasCreateDto {
+            alias,
+            ingredient_id: id,
+            user_id,
+        }
+        .into();
+        let active_model: ingredient_aliases::ActiveModel = model.into();
+        ingredient_aliases::Entity::insert(active_model)
+            .on_conflict(
+                OnConflict::column(ingredient_aliases::Column::Alias)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await
+            .map_err(|err| unexpected(id, err))?;
+    }
+
+    Entity::delete_many()
+        .filter(Column::Id.is_in(duplicate_ids))
+        .exec(db)
+        .await
+        .map_err(|err| unexpected(id, err))?;
+    Ok(merged)
+}
+
+async fn merge_recipe_ingredients<C: ConnectionTrait>(
+    db: &C,
+    id: Uuid,
+    duplicate_ids: &[Uuid],
+    merged: &mut MergeDto,
+) -> Result<(), MergeError> {
+    // A recipe can list an ingredient only once, so rows for recipes that already have
+    // the surviving ingredient are folded into that row
+    let recipe_ingredients = recipe_ingredients::Entity::find()
+        .filter(recipe_ingredients::Column::IngredientId.is_in(duplicate_ids.to_vec()))
+        .all(db)
+        .await
+        .map_err(|err| unexpected(id, err))?;
+    for recipe_ingredient in recipe_ingredients {
+        let same_row = recipe_ingredients::Column::IngredientId
+            .eq(recipe_ingredient.ingredient_id)
+            .and(recipe_ingredients::Column::RecipeId.eq(recipe_ingredient.recipe_id));
+        let existing = recipe_ingredients::Entity::find()
+            .filter(recipe_ingredients::Column::IngredientId.eq(id))
+            .filter(recipe_ingredients::Column::RecipeId.eq(recipe_ingredient.recipe_id))
+            .one(db)
+            .await
+            .map_err(|err| unexpected(id, err))?;
+        if let Some(existing) = existing {
+            let mut update = recipe_ingredients::Entity::update_many()
+                .col_expr(
+                    recipe_ingredients::Column::Optional,
+                    Expr::value(existing.optional && recipe_ingredient.optional),
+                )
+                .col_expr(
+                    recipe_ingredients::Column::UpdatedAt,
+                    Expr::value(Utc::now().naive_utc()),
+                );
+            if existing.amount.is_none() {
+                update = update
+                    .col_expr(
+                        recipe_ingredients::Column::Amount,
+                        Expr::value(recipe_ingredient.amount),
+                    )
+                    .col_expr(
+                        recipe_ingredients::Column::Unit,
+                        Expr::value(recipe_ingredient.unit),
+                    );
+            }
+            update
+                .filter(recipe_ingredients::Column::IngredientId.eq(id))
+                .filter(recipe_ingredients::Column::RecipeId.eq(existing.recipe_id))
+                .exec(db)
+                .await
+                .map_err(|err| unexpected(id, err))?;
+            recipe_ingredients::Entity::delete_many()
+                .filter(same_row)
+                .exec(db)
+                .await
+                .map_err(|err| unexpected(id, err))?;
+            merged.recipe_ingredients_combined += 1;
+        } else {
+            recipe_ingredients::Entity::update_many()
+                .col_expr(recipe_ingredients::Column::IngredientId, Expr::value(id))
+                .filter(same_row)
+                .exec(db)
+                .await
+                .map_err(|err| unexpected(id, err))?;
+            merged.recipe_ingredients_moved += 1;
+        }
+    }
+    Ok(())
+}
+
+async fn merge_pantry_items<C: ConnectionTrait>(
+    db: &C,
+    id: Uuid,
+    duplicate_ids: &[Uuid],
+    user_id: Uuid,
+    merged: &mut MergeDto,
+) -> Result<(), MergeError> {
+    let pantry_items = pantry_items::Entity::find()
+        .filter(pantry_items::Column::IngredientId.is_in(duplicate_ids.to_vec()))
+        .all(db)
+        .await
+        .map_err(|err| unexpected(id, err))?;
+    for pantry_item in pantry_items {
+        let now = Utc::now().naive_utc();
+        // A user has one lot of an ingredient per location and expiration date, so amounts
+        // are added to it
+        let survivor = survivor_lot_query(id, &pantry_item)
+            .one(db)
+            .await
+            .map_err(|err| unexpected(id, err))?;
+        if let Some(before) = survivor {
+            let mut active_model: pantry_items::ActiveModel = before.clone().into();
+            let (quantity, weight_grams, volume_milli_litres) =
+                combined_amounts(&before, &pantry_item).ok_or_else(|| MergeError::Conflict {
+                    id,
+                    reason: format!(
+                        "pantry item {} is measured differently from pantry item {}",
+                        pantry_item.id, before.id
+                    ),
+                })?;
+            active_model.quantity = Set(quantity);
+            active_model.weight_grams = Set(weight_grams);
+            active_model.volume_milli_litres = Set(volume_milli_litres);
+            active_model.purchase_date =
+                Set(match (before.purchase_date, pantry_item.purchase_date) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                });
+            active_model.essential = Set(before.essential || pantry_item.essential);
+            active_model.running_low = Set(before.running_low.or(pantry_item.running_low));
+            active_model.updated_at = Set(now);
+            let after = active_model
+                .update(db)
+                .await
+                .map_err(|err| unexpected(id, err))?;
+            pantry_items::Entity::delete_by_id(pantry_item.id)
+                .exec(db)
+                .await
+                .map_err(|err| unexpected(id, err))?;
+            pantry_item_events::record(db, EventKind::Deleted, user_id, Some(&pantry_item), None)
+                .await
+                .map_err(|err| unexpected(id, err))?;
+            pantry_item_events::record(
+                db,
+                EventKind::Updated,
+                user_id,
+                Some(&before),
+                Some(&after),
+            )
+            .await
+            .map_err(|err| unexpected(id, err))?;
+            merged.pantry_items_combined += 1;
+        } else {
+            let mut active_model: pantry_items::ActiveModel = pantry_item.clone().into();
+            active_model.ingredient_id = Set(id);
+            active_model.updated_at = Set(now);
+            let after = active_model
+                .update(db)
+                .await
+                .map_err(|err| unexpected(id, err))?;
+            pantry_item_events::record(
+                db,
+                EventKind::Updated,
+                user_id,
+                Some(&pantry_item),
+                Some(&after),
+            )
+            .await
+            .map_err(|err| unexpected(id, err))?;
+            merged.pantry_items_moved += 1;
+        }
+    }
+    Ok(())
+}
+
+/// The user's lot of the ingredient that the duplicate's amounts are added to, the one at the
+/// same location expiring on the same day
+fn survivor_lot_query(id: Uuid, duplicate: &pantry_items::Model) -> Select<pantry_items::Entity> {
+    let expiration_date = match duplicate.expiration_date {
+        Some(date) => pantry_items::Column::ExpirationDate.eq(date),
+        None => pantry_items::Column::ExpirationDate.is_null(),
+    };
+    let location_id = match duplicate.location_id {
+        Some(location_id) => pantry_items::Column::LocationId.eq(location_id),
+        None => pantry_items::Column::LocationId.is_null(),
+    };
+    pantry_items::Entity::find()
+        .filter(pantry_items::Column::IngredientId.eq(id))
+        .filter(pantry_items::Column::UserId.eq(duplicate.user_id))
+        .filter(expiration_date)
+        .filter(location_id)
+}
+
+/// Adds the duplicate's amount to the survivor's in the survivor's measure.
+/// Returns `None` when one is counted and the other weighed or measured by volume.
+fn combined_amounts(
+    survivor: &pantry_items::Model,
+    duplicate: &pantry_items::Model,
+) -> Option<(Option<i32>, Option<i32>, Option<i32>)> {
+    let amounts = (
+        survivor.quantity,
+        survivor.weight_grams,
+        survivor.volume_milli_litres,
+    );
+    let Some((duplicate_measure, duplicate_amount)) = Measure::of_pantry_item(
+        duplicate.quantity,
+        duplicate.weight_grams,
+        duplicate.volume_milli_litres,
+    ) else {
+        return Some(amounts);
+    };
+    let Some((measure, amount)) = Measure::of_pantry_item(
+        survivor.quantity,
+        survivor.weight_grams,
+        survivor.volume_milli_litres,
+    ) else {
+        return Some((
+            duplicate.quantity,
+            duplicate.weight_grams,
+            duplicate.volume_milli_litres,
+        ));
+    };
+    let added = Measurement {
+        amount: Amount::from_integer(i64::from(duplicate_amount)),
+        unit: duplicate_measure.unit(),
+    }
+    .in_measure(measure)?;
+    let total = Some(amount.saturating_add(added));
+    Some(match measure {
+        Measure::Quantity => (total, amounts.1, amounts.2),
+        Measure::WeightGrams => (amounts.0, total, amounts.2),
+        Measure::VolumeMilliLitres => (amounts.0, amounts.1, total),
+    })
+}
+
+fn unexpected(id: Uuid, err: DbErr) -> MergeError {
+    MergeError::Unexpected {
+        id,
+        error: err.into(),
+    }
 }
 
 fn list_entity(list_params: &ListParamsDto) -> Select<Entity> {
     let mut entity = Entity::find();
     match &list_params.name {
         Some(value) => {
-            entity = entity.filter(
-                Expr::expr(Func::lower(Expr::col(Column::Name)))
-                    .eq(value.to_lowercase().to_string()),
-            );
+            entity = entity
+                .filter(Expr::expr(Func::lower(Expr::col(Column::Name))).eq(value.to_lowercase()));
         }
         None => {
             if let Some(value) = &list_params.name_contains {
@@ -131,3 +556,56 @@ fn list_entity(list_params: &ListParamsDto) -> Select<Entity> {
     }
     entity
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use sea_orm::{DbBackend, QueryTrait};
+
+    use super::*;
+
+    fn lot(location_id: Option<Uuid>) -> pantry_items::Model {
+        let now = Utc::now().naive_utc();
+        pantry_items::Model {
+            id: Uuid::new_v4(),
+            ingredient_id: Uuid::new_v4(),
+            purchase_date: None,
+            expiration_date: NaiveDate::from_ymd_opt(2025, 1, 12),
+            quantity: Some(1),
+            weight_grams: None,
+            volume_milli_litres: None,
+            essential: false,
+            running_low: None,
+            user_id: Uuid::new_v4(),
+            household_id: None,
+            location_id,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn lots_are_merged_at_their_location() {
+        let id = Uuid::new_v4();
+        let freezer_id = Uuid::new_v4();
+        let freezer_lot = lot(Some(freezer_id));
+        let sql = survivor_lot_query(id, &freezer_lot)
+            .build(DbBackend::Postgres)
+            .to_string();
+        // A fridge lot expiring on the same day is not the freezer lot's survivor
+        assert!(
+            sql.contains(&format!(
+                r#""pantry_items"."expiration_date" = '2025-01-12' AND "pantry_items"."location_id" = '{freezer_id}'"#
+            )),
+            "{sql}"
+        );
+
+        let sql = survivor_lot_query(id, &lot(None))
+            .build(DbBackend::Postgres)
+            .to_string();
+        assert!(
+            sql.ends_with(r#""pantry_items"."location_id" IS NULL"#),
+            "{sql}"
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use db_entities::locations::Model;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CreateDto {
+    pub user_id: Uuid,
+    pub household_id: Option<Uuid>,
+    pub name: String,
+    pub freezer: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct UpdateDto {
+    pub household_id: Option<Uuid>,
+    pub name: String,
+    pub freezer: bool,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct ListParamsDto {
+    pub user_id: Option<Uuid>,
+    pub limit: u64,
+    pub offset: u64,
+}
+
+#[derive(Serialize, Debug, Clone, Eq, PartialEq)]
+pub struct LocationDto {
+    pub id: Uuid,
+    pub name: String,
+    pub freezer: bool,
+    pub user_id: Uuid,
+    pub household_id: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct LocationsListDto {
+    pub items: Vec<LocationDto>,
+}
+
+impl From<CreateDto> for Model {
+    fn from(value: CreateDto) -> Self {
+        let now = Utc::now().naive_utc();
+
+        Self {
+            id: Uuid::new_v4(),
+            name: value.name,
+            freezer: value.freezer,
+            user_id: value.user_id,
+            household_id: value.household_id,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+impl From<Model> for LocationDto {
+    fn from(value: Model) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            freezer: value.freezer,
+            user_id: value.user_id,
+            household_id: value.household_id,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
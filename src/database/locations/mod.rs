@@ -0,0 +1,163 @@
+pub mod dto;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DbErr, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect, Select, Set,
+};
+use uuid::Uuid;
+
+use self::dto::{CreateDto, ListParamsDto, LocationDto, LocationsListDto, UpdateDto};
+use crate::database::dto::MetadataDto;
+use crate::database::errors::{error_code, UNIQUE_VIOLATION_CODE};
+use crate::database::households::visible_to;
+use crate::database::{
+    errors::{CreateError, DeleteError, GetError, ListError, UpdateError},
+    DBClient,
+};
+use db_entities::locations::{ActiveModel, Column, Entity, Model};
+
+#[async_trait]
+pub trait DatabaseCRUD {
+    async fn create_location(&self, request: CreateDto) -> Result<LocationDto, CreateError>;
+    async fn get_location(&self, id: Uuid) -> Result<LocationDto, GetError>;
+    /// Returns the locations of the user and of their households
+    async fn list_locations(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<LocationsListDto, ListError>;
+    async fn get_locations_metadata(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<MetadataDto, ListError>;
+    async fn update_location(
+        &self,
+        id: Uuid,
+        request: UpdateDto,
+    ) -> Result<LocationDto, UpdateError>;
+    /// Deletes the location, its pantry items are kept without a location
+    async fn delete_location(&self, id: Uuid) -> Result<(), DeleteError>;
+}
+
+#[async_trait]
+impl DatabaseCRUD for DBClient {
+    async fn create_location(&self, request: CreateDto) -> Result<LocationDto, CreateError> {
+        let model: Model = request.into();
+        let id = model.id;
+        let active_model: ActiveModel = model.into();
+        Ok(active_model
+            .insert(&self.database_connection)
+            .await
+            .map_err(|err| {
+                if error_code(&err) == Some(UNIQUE_VIOLATION_CODE.to_owned()) {
+                    CreateError::AlreadyExist { id }
+                } else {
+                    CreateError::Unexpected { error: err.into() }
+                }
+            })?
+            .into())
+    }
+    async fn get_location(&self, id: Uuid) -> Result<LocationDto, GetError> {
+        Ok(Entity::find_by_id(id)
+            .one(&self.database_connection)
+            .await
+            .map_err(|err| GetError::Unexpected {
+                id,
+                error: err.into(),
+            })?
+            .ok_or(GetError::NotFound { id })?
+            .into())
+    }
+    async fn list_locations(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<LocationsListDto, ListError> {
+        Ok(LocationsListDto {
+            items: list_entity(list_params)
+                .limit(list_params.limit)
+                .offset(list_params.offset)
+                .order_by_asc(Column::Name)
+                .all(&self.database_connection)
+                .await
+                .map_err(|err| ListError::Unexpected { error: err.into() })?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        })
+    }
+    async fn get_locations_metadata(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<MetadataDto, ListError> {
+        let total_count = list_entity(list_params)
+            .count(&self.database_connection)
+            .await
+            .map_err(|err| ListError::Unexpected { error: err.into() })?;
+        Ok(MetadataDto {
+            page: list_params.offset / list_params.limit + 1,
+            per_page: list_params.limit,
+            page_count: total_count / list_params.limit + 1,
+            total_count,
+        })
+    }
+    async fn update_location(
+        &self,
+        id: Uuid,
+        request: UpdateDto,
+    ) -> Result<LocationDto, UpdateError> {
+        let location: Model = Entity::find_by_id(id)
+            .one(&self.database_connection)
+            .await
+            .map_err(|err| UpdateError::Unexpected {
+                id,
+                error: err.into(),
+            })?
+            .ok_or(UpdateError::NotFound { id })?;
+        let mut location: ActiveModel = location.into();
+        location.household_id = Set(request.household_id);
+        location.name = Set(request.name);
+        location.freezer = Set(request.freezer);
+        location.updated_at = Set(Utc::now().naive_utc());
+
+        Ok(Entity::update(location)
+            .filter(Column::Id.eq(id))
+            .exec(&self.database_connection)
+            .await
+            .map_err(|err| {
+                if let DbErr::RecordNotUpdated = err {
+                    UpdateError::NotFound { id }
+                } else {
+                    UpdateError::Unexpected {
+                        id,
+                        error: err.into(),
+                    }
+                }
+            })?
+            .into())
+    }
+    async fn delete_location(&self, id: Uuid) -> Result<(), DeleteError> {
+        if Entity::delete_by_id(id)
+            .exec(&self.database_connection)
+            .await
+            .map_err(|err| DeleteError::Unexpected {
+                id,
+                error: err.into(),
+            })?
+            .rows_affected
+            == 0
+        {
+            Err(DeleteError::NotFound { id })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn list_entity(list_params: &ListParamsDto) -> Select<Entity> {
+    let mut entity = Entity::find();
+    if let Some(value) = list_params.user_id {
+        entity = entity.filter(visible_to(Column::UserId, Column::HouseholdId, value));
+    }
+    entity
+}
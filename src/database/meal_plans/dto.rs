@@ -0,0 +1,93 @@
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use sea_orm::FromQueryResult;
+use serde::Deserialize;
+use uuid::Uuid;
+
+pub use db_entities::meal_plans::Meal;
+use db_entities::meal_plans::Model;
+
+#[derive(Debug, Clone)]
+pub struct CreateDto {
+    pub user_id: Uuid,
+    pub recipe_id: Uuid,
+    pub date: NaiveDate,
+    pub meal: Meal,
+}
+
+#[derive(Debug, Clone)]
+pub struct UpdateDto {
+    pub recipe_id: Uuid,
+    pub date: NaiveDate,
+    pub meal: Meal,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct ListParamsDto {
+    pub user_id: Option<Uuid>,
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+    pub limit: u64,
+    pub offset: u64,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MealPlanDto {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub recipe_id: Uuid,
+    pub date: NaiveDate,
+    pub meal: Meal,
+    pub cooked: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, FromQueryResult)]
+pub struct MealPlanJoinDto {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub recipe_id: Uuid,
+    pub recipe_name: String,
+    pub date: NaiveDate,
+    pub meal: Meal,
+    pub cooked: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct MealPlansListDto {
+    pub items: Vec<MealPlanJoinDto>,
+}
+
+impl From<CreateDto> for Model {
+    fn from(value: CreateDto) -> Self {
+        let now = Utc::now().naive_utc();
+
+        Self {
+            id: Uuid::new_v4(),
+            user_id: value.user_id,
+            recipe_id: value.recipe_id,
+            date: value.date,
+            meal: value.meal,
+            cooked: false,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+impl From<Model> for MealPlanDto {
+    fn from(value: Model) -> Self {
+        Self {
+            id: value.id,
+            user_id: value.user_id,
+            recipe_id: value.recipe_id,
+            date: value.date,
+            meal: value.meal,
+            cooked: value.cooked,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
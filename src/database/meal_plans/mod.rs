@@ -0,0 +1,236 @@
+pub mod dto;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DbErr, EntityTrait, JoinType, PaginatorTrait, QueryFilter,
+    QueryOrder, QuerySelect, RelationTrait, Select, Set, TransactionTrait,
+};
+use uuid::Uuid;
+
+use self::dto::{
+    CreateDto, ListParamsDto, MealPlanDto, MealPlanJoinDto, MealPlansListDto, UpdateDto,
+};
+use crate::database::dto::MetadataDto;
+use crate::database::errors::{error_code, UNIQUE_VIOLATION_CODE};
+use crate::database::recipes::dto::CookDto;
+use crate::database::recipes::{cook, cook_error};
+use crate::database::{
+    errors::{CreateError, DeleteError, GetError, ListError, UpdateError},
+    DBClient,
+};
+use db_entities::meal_plans::{ActiveModel, Column, Entity, Model};
+
+#[async_trait]
+pub trait DatabaseCRUD {
+    async fn create_meal_plan(&self, request: CreateDto) -> Result<MealPlanDto, CreateError>;
+    async fn get_meal_plan(&self, id: Uuid) -> Result<MealPlanDto, GetError>;
+    async fn list_meal_plans(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<MealPlansListDto, ListError>;
+    async fn get_meal_plans_metadata(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<MetadataDto, ListError>;
+    async fn update_meal_plan(
+        &self,
+        id: Uuid,
+        request: UpdateDto,
+    ) -> Result<MealPlanDto, UpdateError>;
+    /// Marks the plan entry and its recipe as cooked in one transaction
+    async fn cook_meal_plan(
+        &self,
+        id: Uuid,
+        deduct_ingredients: bool,
+    ) -> Result<MealPlanDto, UpdateError>;
+    async fn delete_meal_plan(&self, id: Uuid) -> Result<(), DeleteError>;
+}
+
+#[async_trait]
+impl DatabaseCRUD for DBClient {
+    async fn create_meal_plan(&self, request: CreateDto) -> Result<MealPlanDto, CreateError> {
+        let model: Model = request.into();
+        let id = model.id;
+        let active_model: ActiveModel = model.into();
+        Ok(active_model
+            .insert(&self.database_connection)
+            .await
+            .map_err(|err| {
+                if error_code(&err) == Some(UNIQUE_VIOLATION_CODE.to_owned()) {
+                    CreateError::AlreadyExist { id }
+                } else {
+                    CreateError::Unexpected { error: err.into() }
+                }
+            })?
+            .into())
+    }
+    async fn get_meal_plan(&self, id: Uuid) -> Result<MealPlanDto, GetError> {
+        Ok(Entity::find_by_id(id)
+            .one(&self.database_connection)
+            .await
+            .map_err(|err| GetError::Unexpected {
+                id,
+                error: err.into(),
+            })?
+            .ok_or(GetError::NotFound { id })?
+            .into())
+    }
+    async fn list_meal_plans(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<MealPlansListDto, ListError> {
+        Ok(MealPlansListDto {
+            items: list_entity(list_params)
+                .limit(list_params.limit)
+                .offset(list_params.offset)
+                .order_by_asc(Column::Date)
+                .order_by_asc(Column::CreatedAt)
+                .into_model::<MealPlanJoinDto>()
+                .all(&self.database_connection)
+                .await
+                .map_err(|err| ListError::Unexpected { error: err.into() })?,
+        })
+    }
+    async fn get_meal_plans_metadata(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<MetadataDto, ListError> {
+        let total_count = list_entity(list_params)
+            .count(&self.database_connection)
+            .await
+            .map_err(|err| ListError::Unexpected { error: err.into() })?;
+        Ok(MetadataDto {
+            page: list_params.offset / list_params.limit + 1,
+            per_page: list_params.limit,
+            page_count: total_count / list_params.limit + 1,
+            total_count,
+        })
+    }
+    async fn update_meal_plan(
+        &self,
+        id: Uuid,
+        request: UpdateDto,
+    ) -> Result<MealPlanDto, UpdateError> {
+        let meal_plan: Model = Entity::find_by_id(id)
+            .one(&self.database_connection)
+            .await
+            .map_err(|err| UpdateError::Unexpected {
+                id,
+                error: err.into(),
+            })?
+            .ok_or(UpdateError::NotFound { id })?;
+        let mut meal_plan: ActiveModel = meal_plan.into();
+        meal_plan.recipe_id = Set(request.recipe_id);
+        meal_plan.date = Set(request.date);
+        meal_plan.meal = Set(request.meal);
+        meal_plan.updated_at = Set(Utc::now().naive_utc());
+
+        Ok(Entity::update(meal_plan)
+            .filter(Column::Id.eq(id))
+            .exec(&self.database_connection)
+            .await
+            .map_err(|err| {
+                if let DbErr::RecordNotUpdated = err {
+                    UpdateError::NotFound { id }
+                } else {
+                    UpdateError::Unexpected {
+                        id,
+                        error: err.into(),
+                    }
+                }
+            })?
+            .into())
+    }
+    async fn cook_meal_plan(
+        &self,
+        id: Uuid,
+        deduct_ingredients: bool,
+    ) -> Result<MealPlanDto, UpdateError> {
+        let txn =
+            self.database_connection
+                .begin()
+                .await
+                .map_err(|err| UpdateError::Unexpected {
+                    id,
+                    error: err.into(),
+                })?;
+        // Locked so that concurrent requests cannot both cook the meal
+        let meal_plan: Model = Entity::find_by_id(id)
+            .lock_exclusive()
+            .one(&txn)
+            .await
+            .map_err(|err| UpdateError::Unexpected {
+                id,
+                error: err.into(),
+            })?
+            .ok_or(UpdateError::NotFound { id })?;
+        if meal_plan.cooked {
+            return Err(UpdateError::Conflict {
+                id,
+                reason: "Meal plan was already cooked".to_owned(),
+            });
+        }
+        cook(
+            &txn,
+            &CookDto {
+                recipe_id: meal_plan.recipe_id,
+                user_id: meal_plan.user_id,
+                cooked_on: meal_plan.date,
+                deduct_ingredients,
+            },
+        )
+        .await
+        .map_err(|err| cook_error(meal_plan.recipe_id, err))?;
+        let mut meal_plan: ActiveModel = meal_plan.into();
+        meal_plan.cooked = Set(true);
+        meal_plan.updated_at = Set(Utc::now().naive_utc());
+        let meal_plan = meal_plan
+            .update(&txn)
+            .await
+            .map_err(|err| UpdateError::Unexpected {
+                id,
+                error: err.into(),
+            })?;
+        txn.commit().await.map_err(|err| UpdateError::Unexpected {
+            id,
+            error: err.into(),
+        })?;
+        Ok(meal_plan.into())
+    }
+    async fn delete_meal_plan(&self, id: Uuid) -> Result<(), DeleteError> {
+        if Entity::delete_by_id(id)
+            .exec(&self.database_connection)
+            .await
+            .map_err(|err| DeleteError::Unexpected {
+                id,
+                error: err.into(),
+            })?
+            .rows_affected
+            == 0
+        {
+            Err(DeleteError::NotFound { id })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn list_entity(list_params: &ListParamsDto) -> Select<Entity> {
+    let mut entity = Entity::find();
+    if let Some(value) = list_params.user_id {
+        entity = entity.filter(Column::UserId.eq(value));
+    }
+    if let Some(value) = list_params.from_date {
+        entity = entity.filter(Column::Date.gte(value));
+    }
+    if let Some(value) = list_params.to_date {
+        entity = entity.filter(Column::Date.lte(value));
+    }
+    entity
+        .join(
+            JoinType::InnerJoin,
+            db_entities::meal_plans::Relation::Recipes.def(),
+        )
+        .column_as(db_entities::recipes::Column::Name, "recipe_name")
+}
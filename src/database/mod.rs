@@ -1,9 +1,20 @@
+pub mod api_tokens;
+pub mod categories;
 pub mod dto;
 pub mod errors;
+pub mod households;
+pub mod ingredient_aliases;
 pub mod ingredients;
+pub mod locations;
+pub mod meal_plans;
+pub mod pantry_item_events;
 pub mod pantry_items;
+pub mod products;
+pub mod recipe_categories;
 pub mod recipe_ingredients;
 pub mod recipes;
+pub mod shopping_lists;
+pub mod user_identities;
 pub mod users;
 
 use crate::database::errors::HealthcheckError;
@@ -39,10 +50,21 @@ impl DBHealth for DBClient {
 
 pub trait DBTrait:
     DBHealth
+    + api_tokens::DatabaseCRUD
+    + categories::DatabaseCRUD
+    + households::DatabaseCRUD
+    + ingredient_aliases::DatabaseCRUD
     + ingredients::DatabaseCRUD
+    + locations::DatabaseCRUD
+    + meal_plans::DatabaseCRUD
+    + pantry_item_events::DatabaseCRUD
     + pantry_items::DatabaseCRUD
+    + products::DatabaseCRUD
+    + recipe_categories::DatabaseCRUD
     + recipe_ingredients::DatabaseCRUD
     + recipes::DatabaseCRUD
+    + shopping_lists::DatabaseCRUD
+    + user_identities::DatabaseCRUD
     + users::DatabaseCRUD
 {
 }
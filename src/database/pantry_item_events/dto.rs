@@ -0,0 +1,121 @@
+use chrono::{Datelike, Days, NaiveDate, NaiveDateTime};
+use sea_orm::FromQueryResult;
+use uuid::Uuid;
+
+pub use db_entities::pantry_item_events::EventKind;
+
+#[derive(Debug, Default)]
+pub struct ListParamsDto {
+    pub pantry_item_id: Uuid,
+    /// Only events made by the user or in households they belong to, for items that no
+    /// longer exist to check access against
+    pub visible_to: Option<Uuid>,
+    pub limit: u64,
+    pub offset: u64,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, FromQueryResult)]
+pub struct PantryItemEventJoinDto {
+    pub id: Uuid,
+    pub pantry_item_id: Uuid,
+    pub ingredient_id: Uuid,
+    pub ingredient_name: String,
+    pub user_id: Uuid,
+    pub household_id: Option<Uuid>,
+    pub kind: EventKind,
+    pub quantity_before: Option<i32>,
+    pub quantity_after: Option<i32>,
+    pub weight_grams_before: Option<i32>,
+    pub weight_grams_after: Option<i32>,
+    pub volume_milli_litres_before: Option<i32>,
+    pub volume_milli_litres_after: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug)]
+pub struct PantryItemEventsListDto {
+    pub items: Vec<PantryItemEventJoinDto>,
+}
+
+/// Length of the buckets consumption is summed over
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Period {
+    Day,
+    Week,
+    Month,
+}
+
+impl Period {
+    /// First day of the period `date` falls into. Weeks start on Monday.
+    pub fn start(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Day => date,
+            Period::Week => date
+                .checked_sub_days(Days::new(u64::from(date.weekday().num_days_from_monday())))
+                .unwrap_or(date),
+            Period::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ConsumptionParamsDto {
+    /// Only events of pantry items the user can see
+    pub user_id: Uuid,
+    pub ingredient_id: Option<Uuid>,
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    pub period: Period,
+}
+
+/// Amounts taken out of the pantry
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ConsumedDto {
+    pub quantity: i64,
+    pub weight_grams: i64,
+    pub volume_milli_litres: i64,
+}
+
+impl ConsumedDto {
+    pub fn add(&mut self, other: &ConsumedDto) {
+        self.quantity += other.quantity;
+        self.weight_grams += other.weight_grams;
+        self.volume_milli_litres += other.volume_milli_litres;
+    }
+}
+
+impl From<&PantryItemEventJoinDto> for ConsumedDto {
+    fn from(event: &PantryItemEventJoinDto) -> Self {
+        let decrease = |before: Option<i32>, after: Option<i32>| match (before, after) {
+            (Some(before), Some(after)) if before > after => i64::from(before - after),
+            _ => 0,
+        };
+        Self {
+            quantity: decrease(event.quantity_before, event.quantity_after),
+            weight_grams: decrease(event.weight_grams_before, event.weight_grams_after),
+            volume_milli_litres: decrease(
+                event.volume_milli_litres_before,
+                event.volume_milli_litres_after,
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConsumptionPeriodDto {
+    pub start: NaiveDate,
+    pub consumed: ConsumedDto,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConsumptionDto {
+    pub ingredient_id: Uuid,
+    pub ingredient_name: String,
+    pub total: ConsumedDto,
+    pub periods: Vec<ConsumptionPeriodDto>,
+}
+
+#[derive(Debug)]
+pub struct ConsumptionStatsDto {
+    pub items: Vec<ConsumptionDto>,
+}
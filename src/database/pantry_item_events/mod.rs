@@ -0,0 +1,232 @@
+pub mod dto;
+
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use chrono::{Days, NaiveDate, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, JoinType, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, RelationTrait, Select, Set,
+};
+use uuid::Uuid;
+
+use self::dto::{
+    ConsumedDto, ConsumptionDto, ConsumptionParamsDto, ConsumptionPeriodDto, ConsumptionStatsDto,
+    EventKind, ListParamsDto, PantryItemEventJoinDto, PantryItemEventsListDto,
+};
+use crate::database::dto::MetadataDto;
+use crate::database::households::visible_to;
+use crate::database::{errors::ListError, DBClient};
+use db_entities::pantry_item_events::{ActiveModel, Column, Entity};
+use migrations::Expr;
+
+#[async_trait]
+pub trait DatabaseCRUD {
+    async fn list_pantry_item_events(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<PantryItemEventsListDto, ListError>;
+    async fn get_pantry_item_events_metadata(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<MetadataDto, ListError>;
+    /// Sums what was consumed per ingredient and period. Consumption is every decrease
+    /// of an amount made by cooking or by updating a pantry item.
+    async fn get_consumption_stats(
+        &self,
+        params: &ConsumptionParamsDto,
+    ) -> Result<ConsumptionStatsDto, ListError>;
+}
+
+#[async_trait]
+impl DatabaseCRUD for DBClient {
+    async fn list_pantry_item_events(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<PantryItemEventsListDto, ListError> {
+        Ok(PantryItemEventsListDto {
+            items: list_entity(list_params)
+                .limit(list_params.limit)
+                .offset(list_params.offset)
+                .order_by_desc(Column::CreatedAt)
+                .into_model::<PantryItemEventJoinDto>()
+                .all(&self.database_connection)
+                .await
+                .map_err(|err| ListError::Unexpected { error: err.into() })?,
+        })
+    }
+    async fn get_pantry_item_events_metadata(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<MetadataDto, ListError> {
+        let total_count = list_entity(list_params)
+            .count(&self.database_connection)
+            .await
+            .map_err(|err| ListError::Unexpected { error: err.into() })?;
+        Ok(MetadataDto {
+            page: list_params.offset / list_params.limit + 1,
+            per_page: list_params.limit,
+            page_count: total_count / list_params.limit + 1,
+            total_count,
+        })
+    }
+    async fn get_consumption_stats(
+        &self,
+        params: &ConsumptionParamsDto,
+    ) -> Result<ConsumptionStatsDto, ListError> {
+        let from = params.from_date.and_time(chrono::NaiveTime::MIN);
+        let to = params
+            .to_date
+            .checked_add_days(Days::new(1))
+            .unwrap_or(NaiveDate::MAX)
+            .and_time(chrono::NaiveTime::MIN);
+        let mut entity = join_ingredients(Entity::find())
+            .filter(visible_to(
+                Column::UserId,
+                Column::HouseholdId,
+                params.user_id,
+            ))
+            .filter(Column::Kind.is_in([EventKind::Consumed, EventKind::Updated]))
+            .filter(Column::CreatedAt.gte(from))
+            .filter(Column::CreatedAt.lt(to));
+        if let Some(ingredient_id) = params.ingredient_id {
+            entity = entity.filter(Column::IngredientId.eq(ingredient_id));
+        }
+        let events = entity
+            .order_by_asc(Column::CreatedAt)
+            .into_model::<PantryItemEventJoinDto>()
+            .all(&self.database_connection)
+            .await
+            .map_err(|err| ListError::Unexpected { error: err.into() })?;
+
+        let mut by_ingredient: BTreeMap<(String, Uuid), BTreeMap<NaiveDate, ConsumedDto>> =
+            BTreeMap::new();
+        for event in &events {
+            let consumed = ConsumedDto::from(event);
+            if consumed == ConsumedDto::default() {
+                continue;
+            }
+            by_ingredient
+                .entry((event.ingredient_name.clone(), event.ingredient_id))
+                .or_default()
+                .entry(params.period.start(event.created_at.date()))
+                .or_default()
+                .add(&consumed);
+        }
+        Ok(ConsumptionStatsDto {
+            items: by_ingredient
+                .into_iter()
+                .map(|((ingredient_name, ingredient_id), periods)| {
+                    let mut total = ConsumedDto::default();
+                    for consumed in periods.values() {
+                        total.add(consumed);
+                    }
+                    ConsumptionDto {
+                        ingredient_id,
+                        ingredient_name,
+                        total,
+                        periods: periods
+                            .into_iter()
+                            .map(|(start, consumed)| ConsumptionPeriodDto { start, consumed })
+                            .collect(),
+                    }
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Records a change of a pantry item made by `user_id`.
+/// `before` is `None` when the item is created and `after` when it is deleted.
+pub(crate) async fn record<C: ConnectionTrait>(
+    db: &C,
+    kind: EventKind,
+    user_id: Uuid,
+    before: Option<&db_entities::pantry_items::Model>,
+    after: Option<&db_entities::pantry_items::Model>,
+) -> Result<(), DbErr> {
+    let Some(item) = after.or(before) else {
+        return Ok(());
+    };
+    ActiveModel {
+        id: Set(Uuid::new_v4()),
+        pantry_item_id: Set(item.id),
+        ingredient_id: Set(item.ingredient_id),
+        user_id: Set(user_id),
+        household_id: Set(item.household_id),
+        kind: Set(kind),
+        quantity_before: Set(before.and_then(|item| item.quantity)),
+        quantity_after: Set(after.and_then(|item| item.quantity)),
+        weight_grams_before: Set(before.and_then(|item| item.weight_grams)),
+        weight_grams_after: Set(after.and_then(|item| item.weight_grams)),
+        volume_milli_litres_before: Set(before.and_then(|item| item.volume_milli_litres)),
+        volume_milli_litres_after: Set(after.and_then(|item| item.volume_milli_litres)),
+        created_at: Set(Utc::now().naive_utc()),
+    }
+    .insert(db)
+    .await?;
+    Ok(())
+}
+
+/// Moves the history of merged ingredients onto the surviving ingredient
+pub(crate) async fn repoint_ingredient<C: ConnectionTrait>(
+    db: &C,
+    from: &[Uuid],
+    to: Uuid,
+) -> Result<(), DbErr> {
+    Entity::update_many()
+        .col_expr(Column::IngredientId, Expr::value(to))
+        .filter(Column::IngredientId.is_in(from.to_vec()))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+fn join_ingredients(entity: Select<Entity>) -> Select<Entity> {
+    entity
+        .join(
+            JoinType::InnerJoin,
+            db_entities::pantry_item_events::Relation::Ingredients.def(),
+        )
+        .column_as(db_entities::ingredients::Column::Name, "ingredient_name")
+}
+
+fn list_entity(list_params: &ListParamsDto) -> Select<Entity> {
+    let mut entity = join_ingredients(Entity::find())
+        .filter(Column::PantryItemId.eq(list_params.pantry_item_id));
+    if let Some(value) = list_params.visible_to {
+        entity = entity.filter(visible_to(Column::UserId, Column::HouseholdId, value));
+    }
+    entity
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::{DbBackend, QueryTrait};
+
+    use super::*;
+
+    #[test]
+    fn deleted_items_show_the_events_the_user_could_see() {
+        let user_id = Uuid::new_v4();
+        let list_params = ListParamsDto {
+            pantry_item_id: Uuid::new_v4(),
+            visible_to: Some(user_id),
+            ..Default::default()
+        };
+        let sql = list_entity(&list_params)
+            .build(DbBackend::Postgres)
+            .to_string();
+        assert!(sql.contains(&format!(
+            r#""pantry_item_events"."user_id" = '{user_id}' OR "pantry_item_events"."household_id" IN"#
+        )), "{sql}");
+        let list_params = ListParamsDto {
+            visible_to: None,
+            ..list_params
+        };
+        let sql = list_entity(&list_params)
+            .build(DbBackend::Postgres)
+            .to_string();
+        assert!(!sql.contains(&user_id.to_string()), "{sql}");
+    }
+}
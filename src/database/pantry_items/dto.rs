@@ -3,12 +3,16 @@ use sea_orm::FromQueryResult;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::units::Measure;
 use db_entities::pantry_items::Model;
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct CreateDto {
     pub ingredient_id: Uuid,
     pub user_id: Uuid,
+    pub household_id: Option<Uuid>,
+    pub location_id: Option<Uuid>,
+    pub purchase_date: Option<NaiveDate>,
     pub expiration_date: Option<NaiveDate>,
     pub quantity: Option<i32>,
     pub weight_grams: Option<i32>,
@@ -19,8 +23,12 @@ pub struct CreateDto {
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct UpdateDto {
-    pub ingredient_id: Uuid,
+    /// User making the change, recorded in the item history. The owner is not changed.
     pub user_id: Uuid,
+    pub ingredient_id: Uuid,
+    pub household_id: Option<Uuid>,
+    pub location_id: Option<Uuid>,
+    pub purchase_date: Option<NaiveDate>,
     pub expiration_date: Option<NaiveDate>,
     pub quantity: Option<i32>,
     pub weight_grams: Option<i32>,
@@ -33,6 +41,7 @@ pub struct UpdateDto {
 pub struct ListParamsDto {
     pub user_id: Option<Uuid>,
     pub ingredient_id: Option<Uuid>,
+    pub location_id: Option<Uuid>,
     pub name_contains: Option<String>,
     pub max_expiration_date: Option<NaiveDate>,
     pub limit: u64,
@@ -43,6 +52,7 @@ pub struct ListParamsDto {
 pub struct PantryItemDto {
     pub id: Uuid,
     pub ingredient_id: Uuid,
+    pub purchase_date: Option<NaiveDate>,
     pub expiration_date: Option<NaiveDate>,
     pub quantity: Option<i32>,
     pub weight_grams: Option<i32>,
@@ -50,10 +60,21 @@ pub struct PantryItemDto {
     pub essential: bool,
     pub running_low: Option<i32>,
     pub user_id: Uuid,
+    pub household_id: Option<Uuid>,
+    pub location_id: Option<Uuid>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
 
+/// Moves a pantry item to another location, `None` takes it out of every location
+#[derive(Deserialize, Debug, Clone)]
+pub struct MoveDto {
+    /// User making the change, recorded in the item history
+    pub user_id: Uuid,
+    pub location_id: Option<Uuid>,
+    pub expiration_date: Option<NaiveDate>,
+}
+
 impl From<CreateDto> for Model {
     fn from(value: CreateDto) -> Self {
         let now = Utc::now().naive_utc();
@@ -61,6 +82,7 @@ impl From<CreateDto> for Model {
         Self {
             id: Uuid::new_v4(),
             ingredient_id: value.ingredient_id,
+            purchase_date: value.purchase_date,
             expiration_date: value.expiration_date,
             quantity: value.quantity,
             weight_grams: value.weight_grams,
@@ -68,6 +90,8 @@ impl From<CreateDto> for Model {
             essential: value.essential,
             running_low: value.running_low,
             user_id: value.user_id,
+            household_id: value.household_id,
+            location_id: value.location_id,
             created_at: now,
             updated_at: now,
         }
@@ -79,6 +103,7 @@ impl From<Model> for PantryItemDto {
         Self {
             id: value.id,
             ingredient_id: value.ingredient_id,
+            purchase_date: value.purchase_date,
             expiration_date: value.expiration_date,
             quantity: value.quantity,
             weight_grams: value.weight_grams,
@@ -86,6 +111,8 @@ impl From<Model> for PantryItemDto {
             essential: value.essential,
             running_low: value.running_low,
             user_id: value.user_id,
+            household_id: value.household_id,
+            location_id: value.location_id,
             created_at: value.created_at,
             updated_at: value.updated_at,
         }
@@ -105,6 +132,9 @@ pub struct PantryItemJoinDto {
     pub essential: bool,
     pub running_low: Option<i32>,
     pub user_id: Uuid,
+    pub household_id: Option<Uuid>,
+    pub location_id: Option<Uuid>,
+    pub location_name: Option<String>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
@@ -113,3 +143,45 @@ pub struct PantryItemJoinDto {
 pub struct PantryItemsListDto {
     pub items: Vec<PantryItemJoinDto>,
 }
+
+/// Lots of one ingredient, in the order they are consumed
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct PantryItemGroupDto {
+    pub ingredient_id: Uuid,
+    pub ingredient_name: String,
+    pub lots: Vec<PantryItemJoinDto>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct PantryItemGroupsListDto {
+    pub items: Vec<PantryItemGroupDto>,
+}
+
+/// Lots kept in one location grouped by ingredient, `None` for lots without a location
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct PantryItemLocationGroupDto {
+    pub location_id: Option<Uuid>,
+    pub location_name: Option<String>,
+    pub items: Vec<PantryItemGroupDto>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct PantryItemLocationGroupsListDto {
+    pub items: Vec<PantryItemLocationGroupDto>,
+}
+
+#[derive(Debug, Clone, FromQueryResult)]
+pub(crate) struct GroupKeyDto {
+    pub ingredient_id: Uuid,
+    pub ingredient_name: String,
+}
+
+/// Lots an amount was deducted from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsumedLotsDto {
+    /// Measure of the lots the amount was deducted from
+    pub measure: Measure,
+    /// Amount the lots were short of, in `measure`
+    pub missing: i32,
+    pub lots: Vec<PantryItemDto>,
+}
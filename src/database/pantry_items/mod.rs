@@ -1,22 +1,30 @@
 pub mod dto;
 
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{NaiveDate, Utc};
 use migrations::{Expr, Func};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DbErr, EntityTrait, JoinType, PaginatorTrait, QueryFilter,
-    QueryOrder, QuerySelect, RelationTrait, Select, Set,
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, JoinType, Order,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait, Select, Set,
+    TransactionTrait,
 };
 use uuid::Uuid;
 
-use self::dto::{CreateDto, ListParamsDto, PantryItemDto, PantryItemsListDto, UpdateDto};
+use self::dto::{
+    ConsumedLotsDto, CreateDto, GroupKeyDto, ListParamsDto, MoveDto, PantryItemDto,
+    PantryItemGroupDto, PantryItemGroupsListDto, PantryItemLocationGroupDto,
+    PantryItemLocationGroupsListDto, PantryItemsListDto, UpdateDto,
+};
 use crate::database::dto::MetadataDto;
 use crate::database::errors::{error_code, UNIQUE_VIOLATION_CODE};
+use crate::database::households::{visible_to, writable_by};
+use crate::database::pantry_item_events::{self, dto::EventKind};
 use crate::database::pantry_items::dto::PantryItemJoinDto;
 use crate::database::{
     errors::{CreateError, DeleteError, GetError, ListError, UpdateError},
     DBClient,
 };
+use crate::units::{Measure, Measurement};
 use db_entities::pantry_items::{ActiveModel, Column, Entity, Model};
 
 #[async_trait]
@@ -24,20 +32,67 @@ pub trait DatabaseCRUD {
     async fn create_pantry_item(&self, request: CreateDto) -> Result<PantryItemDto, CreateError>;
     async fn get_pantry_item(&self, id: Uuid) -> Result<PantryItemDto, GetError>;
     async fn get_pantry_item_join(&self, id: Uuid) -> Result<PantryItemJoinDto, GetError>;
-    async fn list_pantry_items_join(
+    /// Returns pantry items expiring on or before `until`, soonest first.
+    /// Items of every user are returned if `user_id` is `None`.
+    async fn list_expiring_pantry_items(
         &self,
-        list_params: &ListParamsDto,
+        user_id: Option<Uuid>,
+        until: NaiveDate,
     ) -> Result<PantryItemsListDto, ListError>;
-    async fn get_pantry_items_join_metadata(
+    /// Returns every pantry item the user can see without pagination
+    async fn list_all_pantry_items(&self, user_id: Uuid) -> Result<Vec<PantryItemDto>, ListError>;
+    /// Returns the first lot, in FIFO order, of the ingredient at the location expiring on the
+    /// date that the user may write to
+    async fn find_writable_lot(
         &self,
-        list_params: &ListParamsDto,
-    ) -> Result<MetadataDto, ListError>;
+        user_id: Uuid,
+        ingredient_id: Uuid,
+        location_id: Option<Uuid>,
+        expiration_date: Option<NaiveDate>,
+    ) -> Result<Option<PantryItemDto>, ListError>;
     async fn update_pantry_item(
         &self,
         id: Uuid,
         request: UpdateDto,
     ) -> Result<PantryItemDto, UpdateError>;
-    async fn delete_pantry_item(&self, id: Uuid) -> Result<(), DeleteError>;
+    /// Adds `amount` in `measure` to the pantry item, recording `user_id` as the user who added it
+    async fn restock_pantry_item(
+        &self,
+        id: Uuid,
+        measure: Measure,
+        amount: i32,
+        user_id: Uuid,
+    ) -> Result<PantryItemDto, UpdateError>;
+    /// Deletes the pantry item, recording `user_id` as the user who removed it
+    async fn delete_pantry_item(&self, id: Uuid, user_id: Uuid) -> Result<(), DeleteError>;
+    /// Returns the lots matching the parameters grouped by ingredient, paginated by ingredient
+    async fn list_pantry_item_groups(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<PantryItemGroupsListDto, ListError>;
+    async fn get_pantry_item_groups_metadata(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<MetadataDto, ListError>;
+    /// Returns every lot matching the parameters grouped by location, then by ingredient
+    async fn list_pantry_items_by_location(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<PantryItemLocationGroupsListDto, ListError>;
+    /// Moves the pantry item to the location and sets its expiration date
+    async fn move_pantry_item(
+        &self,
+        id: Uuid,
+        request: MoveDto,
+    ) -> Result<PantryItemDto, UpdateError>;
+    /// Deducts `needed` from the user's lots of the ingredient, earliest expiration first.
+    /// Returns `None` if no lot holds an amount `needed` can be measured in.
+    async fn consume_pantry_items(
+        &self,
+        user_id: Uuid,
+        ingredient_id: Uuid,
+        needed: Measurement,
+    ) -> Result<Option<ConsumedLotsDto>, UpdateError>;
 }
 
 #[async_trait]
@@ -45,18 +100,47 @@ impl DatabaseCRUD for DBClient {
     async fn create_pantry_item(&self, request: CreateDto) -> Result<PantryItemDto, CreateError> {
         let model: Model = request.into();
         let id = model.id;
+        let user_id = model.user_id;
+        let ingredient_id = model.ingredient_id;
+        let location_id = model.location_id;
+        let expiration_date = model.expiration_date;
         let active_model: ActiveModel = model.into();
-        Ok(active_model
-            .insert(&self.database_connection)
-            .await
-            .map_err(|err| {
-                if error_code(&err) == Some(UNIQUE_VIOLATION_CODE.to_owned()) {
-                    CreateError::AlreadyExist { id }
-                } else {
-                    CreateError::Unexpected { error: err.into() }
-                }
-            })?
-            .into())
+        let txn = self
+            .database_connection
+            .begin()
+            .await
+            .map_err(|err| CreateError::Unexpected { error: err.into() })?;
+        let pantry_item = match active_model.insert(&txn).await {
+            Ok(pantry_item) => pantry_item,
+            Err(err) if error_code(&err) == Some(UNIQUE_VIOLATION_CODE.to_owned()) => {
+                // The user already has a lot of the ingredient there expiring that day
+                let existing = Entity::find()
+                    .filter(Column::UserId.eq(user_id))
+                    .filter(Column::IngredientId.eq(ingredient_id))
+                    .filter(match location_id {
+                        Some(location_id) => Column::LocationId.eq(location_id),
+                        None => Column::LocationId.is_null(),
+                    })
+                    .filter(match expiration_date {
+                        Some(date) => Column::ExpirationDate.eq(date),
+                        None => Column::ExpirationDate.is_null(),
+                    })
+                    .one(&self.database_connection)
+                    .await
+                    .map_err(|err| CreateError::Unexpected { error: err.into() })?;
+                return Err(CreateError::AlreadyExist {
+                    id: existing.map_or(id, |existing| existing.id),
+                });
+            }
+            Err(err) => return Err(CreateError::Unexpected { error: err.into() }),
+        };
+        pantry_item_events::record(&txn, EventKind::Created, user_id, None, Some(&pantry_item))
+            .await
+            .map_err(|err| CreateError::Unexpected { error: err.into() })?;
+        txn.commit()
+            .await
+            .map_err(|err| CreateError::Unexpected { error: err.into() })?;
+        Ok(pantry_item.into())
     }
     async fn get_pantry_item(&self, id: Uuid) -> Result<PantryItemDto, GetError> {
         Ok(Entity::find_by_id(id)
@@ -70,12 +154,7 @@ impl DatabaseCRUD for DBClient {
             .into())
     }
     async fn get_pantry_item_join(&self, id: Uuid) -> Result<PantryItemJoinDto, GetError> {
-        Ok(Entity::find_by_id(id)
-            .join(
-                JoinType::InnerJoin,
-                db_entities::pantry_items::Relation::Ingredients.def(),
-            )
-            .column_as(db_entities::ingredients::Column::Name, "ingredient_name")
+        Ok(join_names(Entity::find_by_id(id))
             .into_model::<PantryItemJoinDto>()
             .one(&self.database_connection)
             .await
@@ -85,52 +164,76 @@ impl DatabaseCRUD for DBClient {
             })?
             .ok_or(GetError::NotFound { id })?)
     }
-    async fn list_pantry_items_join(
+    async fn list_expiring_pantry_items(
         &self,
-        list_params: &ListParamsDto,
+        user_id: Option<Uuid>,
+        until: NaiveDate,
     ) -> Result<PantryItemsListDto, ListError> {
+        let list_params = ListParamsDto {
+            user_id,
+            max_expiration_date: Some(until),
+            ..Default::default()
+        };
         Ok(PantryItemsListDto {
-            items: list_entity(list_params)
-                .limit(list_params.limit)
-                .offset(list_params.offset)
-                .order_by_desc(Column::UpdatedAt)
+            items: list_entity(&list_params)
+                .order_by_asc(Column::ExpirationDate)
                 .into_model::<PantryItemJoinDto>()
                 .all(&self.database_connection)
                 .await
                 .map_err(|err| ListError::Unexpected { error: err.into() })?,
         })
     }
-    async fn get_pantry_items_join_metadata(
-        &self,
-        list_params: &ListParamsDto,
-    ) -> Result<MetadataDto, ListError> {
-        let total_count = list_entity(list_params)
-            .count(&self.database_connection)
+    async fn list_all_pantry_items(&self, user_id: Uuid) -> Result<Vec<PantryItemDto>, ListError> {
+        Ok(Entity::find()
+            .filter(visible_to(Column::UserId, Column::HouseholdId, user_id))
+            .all(&self.database_connection)
             .await
-            .map_err(|err| ListError::Unexpected { error: err.into() })?;
-        Ok(MetadataDto {
-            page: list_params.offset / list_params.limit + 1,
-            per_page: list_params.limit,
-            page_count: total_count / list_params.limit + 1,
-            total_count,
-        })
+            .map_err(|err| ListError::Unexpected { error: err.into() })?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+    async fn find_writable_lot(
+        &self,
+        user_id: Uuid,
+        ingredient_id: Uuid,
+        location_id: Option<Uuid>,
+        expiration_date: Option<NaiveDate>,
+    ) -> Result<Option<PantryItemDto>, ListError> {
+        Ok(
+            writable_lot_query(user_id, ingredient_id, location_id, expiration_date)
+                .one(&self.database_connection)
+                .await
+                .map_err(|err| ListError::Unexpected { error: err.into() })?
+                .map(Into::into),
+        )
     }
     async fn update_pantry_item(
         &self,
         id: Uuid,
         request: UpdateDto,
     ) -> Result<PantryItemDto, UpdateError> {
-        let pantry_item: Model = Entity::find_by_id(id)
-            .one(&self.database_connection)
+        let txn =
+            self.database_connection
+                .begin()
+                .await
+                .map_err(|err| UpdateError::Unexpected {
+                    id,
+                    error: err.into(),
+                })?;
+        let before: Model = Entity::find_by_id(id)
+            .one(&txn)
             .await
             .map_err(|err| UpdateError::Unexpected {
                 id,
                 error: err.into(),
             })?
             .ok_or(UpdateError::NotFound { id })?;
-        let mut pantry_item: ActiveModel = pantry_item.into();
+        let mut pantry_item: ActiveModel = before.clone().into();
         pantry_item.ingredient_id = Set(request.ingredient_id);
-        pantry_item.user_id = Set(request.user_id);
+        pantry_item.household_id = Set(request.household_id);
+        pantry_item.location_id = Set(request.location_id);
+        pantry_item.purchase_date = Set(request.purchase_date);
         pantry_item.expiration_date = Set(request.expiration_date);
         pantry_item.quantity = Set(request.quantity);
         pantry_item.weight_grams = Set(request.weight_grams);
@@ -139,44 +242,452 @@ impl DatabaseCRUD for DBClient {
         pantry_item.running_low = Set(request.running_low);
         pantry_item.updated_at = Set(Utc::now().naive_utc());
 
-        Ok(Entity::update(pantry_item)
+        let after = Entity::update(pantry_item)
             .filter(Column::Id.eq(id))
-            .exec(&self.database_connection)
-            .await
-            .map_err(|err| {
-                if let DbErr::RecordNotUpdated = err {
-                    UpdateError::NotFound { id }
-                } else {
-                    UpdateError::Unexpected {
-                        id,
-                        error: err.into(),
-                    }
+            .exec(&txn)
+            .await
+            .map_err(|err| match err {
+                DbErr::RecordNotUpdated => UpdateError::NotFound { id },
+                err if error_code(&err) == Some(UNIQUE_VIOLATION_CODE.to_owned()) => {
+                    lot_conflict(id)
                 }
+                err => UpdateError::Unexpected {
+                    id,
+                    error: err.into(),
+                },
+            })?;
+        pantry_item_events::record(
+            &txn,
+            EventKind::Updated,
+            request.user_id,
+            Some(&before),
+            Some(&after),
+        )
+        .await
+        .map_err(|err| UpdateError::Unexpected {
+            id,
+            error: err.into(),
+        })?;
+        txn.commit().await.map_err(|err| UpdateError::Unexpected {
+            id,
+            error: err.into(),
+        })?;
+        Ok(after.into())
+    }
+    async fn restock_pantry_item(
+        &self,
+        id: Uuid,
+        measure: Measure,
+        amount: i32,
+        user_id: Uuid,
+    ) -> Result<PantryItemDto, UpdateError> {
+        let txn =
+            self.database_connection
+                .begin()
+                .await
+                .map_err(|err| UpdateError::Unexpected {
+                    id,
+                    error: err.into(),
+                })?;
+        // Concurrent scans of the same lot add up instead of overwriting each other
+        let before: Model = Entity::find_by_id(id)
+            .lock_exclusive()
+            .one(&txn)
+            .await
+            .map_err(|err| UpdateError::Unexpected {
+                id,
+                error: err.into(),
             })?
-            .into())
+            .ok_or(UpdateError::NotFound { id })?;
+        let mut pantry_item: ActiveModel = before.clone().into();
+        let held = match measure {
+            Measure::Quantity => before.quantity,
+            Measure::WeightGrams => before.weight_grams,
+            Measure::VolumeMilliLitres => before.volume_milli_litres,
+        };
+        // Repeated scans of a large package must not wrap around
+        let Some(restocked) = held.unwrap_or_default().checked_add(amount) else {
+            return Err(UpdateError::Invalid {
+                id,
+                reason: format!("Pantry item {id:?} cannot hold {amount} more"),
+            });
+        };
+        let restocked = Set(Some(restocked));
+        match measure {
+            Measure::Quantity => pantry_item.quantity = restocked,
+            Measure::WeightGrams => pantry_item.weight_grams = restocked,
+            Measure::VolumeMilliLitres => pantry_item.volume_milli_litres = restocked,
+        }
+        pantry_item.updated_at = Set(Utc::now().naive_utc());
+        let after = pantry_item
+            .update(&txn)
+            .await
+            .map_err(|err| UpdateError::Unexpected {
+                id,
+                error: err.into(),
+            })?;
+        pantry_item_events::record(
+            &txn,
+            EventKind::Updated,
+            user_id,
+            Some(&before),
+            Some(&after),
+        )
+        .await
+        .map_err(|err| UpdateError::Unexpected {
+            id,
+            error: err.into(),
+        })?;
+        txn.commit().await.map_err(|err| UpdateError::Unexpected {
+            id,
+            error: err.into(),
+        })?;
+        Ok(after.into())
     }
-    async fn delete_pantry_item(&self, id: Uuid) -> Result<(), DeleteError> {
-        if Entity::delete_by_id(id)
-            .exec(&self.database_connection)
+    async fn delete_pantry_item(&self, id: Uuid, user_id: Uuid) -> Result<(), DeleteError> {
+        let txn =
+            self.database_connection
+                .begin()
+                .await
+                .map_err(|err| DeleteError::Unexpected {
+                    id,
+                    error: err.into(),
+                })?;
+        let before: Model = Entity::find_by_id(id)
+            .one(&txn)
             .await
             .map_err(|err| DeleteError::Unexpected {
                 id,
                 error: err.into(),
             })?
-            .rows_affected
-            == 0
-        {
-            Err(DeleteError::NotFound { id })
-        } else {
-            Ok(())
+            .ok_or(DeleteError::NotFound { id })?;
+        Entity::delete_by_id(id)
+            .exec(&txn)
+            .await
+            .map_err(|err| DeleteError::Unexpected {
+                id,
+                error: err.into(),
+            })?;
+        pantry_item_events::record(&txn, EventKind::Deleted, user_id, Some(&before), None)
+            .await
+            .map_err(|err| DeleteError::Unexpected {
+                id,
+                error: err.into(),
+            })?;
+        txn.commit().await.map_err(|err| DeleteError::Unexpected {
+            id,
+            error: err.into(),
+        })
+    }
+    async fn list_pantry_item_groups(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<PantryItemGroupsListDto, ListError> {
+        let keys = list_groups(list_params)
+            .order_by_asc(db_entities::ingredients::Column::Name)
+            .limit(list_params.limit)
+            .offset(list_params.offset)
+            .into_model::<GroupKeyDto>()
+            .all(&self.database_connection)
+            .await
+            .map_err(|err| ListError::Unexpected { error: err.into() })?;
+        let mut lots = fifo_order(
+            list_entity(list_params)
+                .filter(Column::IngredientId.is_in(keys.iter().map(|key| key.ingredient_id))),
+        )
+        .into_model::<PantryItemJoinDto>()
+        .all(&self.database_connection)
+        .await
+        .map_err(|err| ListError::Unexpected { error: err.into() })?;
+        Ok(PantryItemGroupsListDto {
+            items: keys
+                .into_iter()
+                .map(|key| {
+                    let (group, rest) = lots
+                        .drain(..)
+                        .partition(|lot| lot.ingredient_id == key.ingredient_id);
+                    lots = rest;
+                    PantryItemGroupDto {
+                        ingredient_id: key.ingredient_id,
+                        ingredient_name: key.ingredient_name,
+                        lots: group,
+                    }
+                })
+                .collect(),
+        })
+    }
+    async fn get_pantry_item_groups_metadata(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<MetadataDto, ListError> {
+        let total_count = list_groups(list_params)
+            .count(&self.database_connection)
+            .await
+            .map_err(|err| ListError::Unexpected { error: err.into() })?;
+        Ok(MetadataDto {
+            page: list_params.offset / list_params.limit + 1,
+            per_page: list_params.limit,
+            page_count: total_count / list_params.limit + 1,
+            total_count,
+        })
+    }
+    async fn list_pantry_items_by_location(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<PantryItemLocationGroupsListDto, ListError> {
+        let lots = fifo_order(list_entity(list_params))
+            .into_model::<PantryItemJoinDto>()
+            .all(&self.database_connection)
+            .await
+            .map_err(|err| ListError::Unexpected { error: err.into() })?;
+        let mut locations: Vec<PantryItemLocationGroupDto> = Vec::new();
+        for lot in lots {
+            let index = if let Some(index) = locations
+                .iter()
+                .position(|location| location.location_id == lot.location_id)
+            {
+                index
+            } else {
+                locations.push(PantryItemLocationGroupDto {
+                    location_id: lot.location_id,
+                    location_name: lot.location_name.clone(),
+                    items: Vec::new(),
+                });
+                locations.len() - 1
+            };
+            let location = &mut locations[index];
+            match location
+                .items
+                .iter_mut()
+                .find(|group| group.ingredient_id == lot.ingredient_id)
+            {
+                Some(group) => group.lots.push(lot),
+                None => location.items.push(PantryItemGroupDto {
+                    ingredient_id: lot.ingredient_id,
+                    ingredient_name: lot.ingredient_name.clone(),
+                    lots: vec![lot],
+                }),
+            }
+        }
+        // Lots without a location come last
+        locations.sort_by(|a, b| {
+            a.location_id
+                .is_none()
+                .cmp(&b.location_id.is_none())
+                .then_with(|| a.location_name.cmp(&b.location_name))
+        });
+        for location in &mut locations {
+            location
+                .items
+                .sort_by(|a, b| a.ingredient_name.cmp(&b.ingredient_name));
         }
+        Ok(PantryItemLocationGroupsListDto { items: locations })
+    }
+    async fn move_pantry_item(
+        &self,
+        id: Uuid,
+        request: MoveDto,
+    ) -> Result<PantryItemDto, UpdateError> {
+        let unexpected = |err: DbErr| UpdateError::Unexpected {
+            id,
+            error: err.into(),
+        };
+        let txn = self.database_connection.begin().await.map_err(unexpected)?;
+        let before: Model = Entity::find_by_id(id)
+            .one(&txn)
+            .await
+            .map_err(unexpected)?
+            .ok_or(UpdateError::NotFound { id })?;
+        let mut pantry_item: ActiveModel = before.clone().into();
+        pantry_item.location_id = Set(request.location_id);
+        pantry_item.expiration_date = Set(request.expiration_date);
+        pantry_item.updated_at = Set(Utc::now().naive_utc());
+        let after = pantry_item.update(&txn).await.map_err(|err| {
+            if error_code(&err) == Some(UNIQUE_VIOLATION_CODE.to_owned()) {
+                lot_conflict(id)
+            } else {
+                unexpected(err)
+            }
+        })?;
+        pantry_item_events::record(
+            &txn,
+            EventKind::Updated,
+            request.user_id,
+            Some(&before),
+            Some(&after),
+        )
+        .await
+        .map_err(unexpected)?;
+        txn.commit().await.map_err(unexpected)?;
+        Ok(after.into())
+    }
+    async fn consume_pantry_items(
+        &self,
+        user_id: Uuid,
+        ingredient_id: Uuid,
+        needed: Measurement,
+    ) -> Result<Option<ConsumedLotsDto>, UpdateError> {
+        let unexpected = |err: DbErr| UpdateError::Unexpected {
+            id: ingredient_id,
+            error: err.into(),
+        };
+        let txn = self.database_connection.begin().await.map_err(unexpected)?;
+        let lots = fifo_lots(&txn, user_id, ingredient_id)
+            .await
+            .map_err(unexpected)?;
+        let consumed = consume_lots(&txn, user_id, lots, &needed)
+            .await
+            .map_err(unexpected)?;
+        txn.commit().await.map_err(unexpected)?;
+        Ok(consumed)
     }
 }
 
+/// Another lot of the ingredient at the location expires on the same day
+fn lot_conflict(id: Uuid) -> UpdateError {
+    UpdateError::Conflict {
+        id,
+        reason: "A lot of the ingredient at that location already expires on that day".to_owned(),
+    }
+}
+
+/// Lots of the ingredient the user may consume, in the order they are consumed
+pub(crate) async fn fifo_lots<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    ingredient_id: Uuid,
+) -> Result<Vec<Model>, DbErr> {
+    fifo_lots_query(user_id, ingredient_id).all(db).await
+}
+
+/// Locks the lots until the transaction ends, so concurrent consumers do not overwrite
+/// each other's deductions
+fn fifo_lots_query(user_id: Uuid, ingredient_id: Uuid) -> Select<Entity> {
+    fifo_order(
+        Entity::find()
+            .filter(writable_by(Column::UserId, Column::HouseholdId, user_id))
+            .filter(Column::IngredientId.eq(ingredient_id)),
+    )
+    .lock_exclusive()
+}
+
+fn writable_lot_query(
+    user_id: Uuid,
+    ingredient_id: Uuid,
+    location_id: Option<Uuid>,
+    expiration_date: Option<NaiveDate>,
+) -> Select<Entity> {
+    fifo_order(
+        Entity::find()
+            .filter(writable_by(Column::UserId, Column::HouseholdId, user_id))
+            .filter(Column::IngredientId.eq(ingredient_id))
+            .filter(match location_id {
+                Some(location_id) => Column::LocationId.eq(location_id),
+                None => Column::LocationId.is_null(),
+            })
+            .filter(match expiration_date {
+                Some(date) => Column::ExpirationDate.eq(date),
+                None => Column::ExpirationDate.is_null(),
+            }),
+    )
+}
+
+/// Deducts `needed` from the lots in order, recording `user_id` as the user who consumed them.
+/// Only lots in the measure of the first lot `needed` converts to are used, counts cannot
+/// be taken from weighed lots. Returns `None` if there is no such lot.
+pub(crate) async fn consume_lots<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    lots: Vec<Model>,
+    needed: &Measurement,
+) -> Result<Option<ConsumedLotsDto>, DbErr> {
+    let Some(plan) = plan_consumption(&lots, needed) else {
+        return Ok(None);
+    };
+    let mut consumed = Vec::new();
+    for (index, left) in plan.left {
+        let lot = &lots[index];
+        let left = Set(Some(left));
+        let mut active_model: ActiveModel = lot.clone().into();
+        match plan.measure {
+            Measure::Quantity => active_model.quantity = left,
+            Measure::WeightGrams => active_model.weight_grams = left,
+            Measure::VolumeMilliLitres => active_model.volume_milli_litres = left,
+        }
+        active_model.updated_at = Set(Utc::now().naive_utc());
+        let after = active_model.update(db).await?;
+        pantry_item_events::record(db, EventKind::Consumed, user_id, Some(lot), Some(&after))
+            .await?;
+        consumed.push(after.into());
+    }
+    Ok(Some(ConsumedLotsDto {
+        measure: plan.measure,
+        missing: plan.missing,
+        lots: consumed,
+    }))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct ConsumptionPlan {
+    measure: Measure,
+    /// Indexes of the lots deducted from, with the amount left in each
+    left: Vec<(usize, i32)>,
+    missing: i32,
+}
+
+/// Works out the deductions of [`consume_lots`] without touching the database
+fn plan_consumption(lots: &[Model], needed: &Measurement) -> Option<ConsumptionPlan> {
+    let (measure, mut remaining) = lots.iter().find_map(|lot| {
+        let (measure, _) =
+            Measure::of_pantry_item(lot.quantity, lot.weight_grams, lot.volume_milli_litres)?;
+        Some((measure, needed.in_measure(measure)?))
+    })?;
+    let mut left = Vec::new();
+    for (index, lot) in lots.iter().enumerate() {
+        if remaining <= 0 {
+            break;
+        }
+        let Some((lot_measure, held)) =
+            Measure::of_pantry_item(lot.quantity, lot.weight_grams, lot.volume_milli_litres)
+        else {
+            continue;
+        };
+        if lot_measure != measure || held <= 0 {
+            continue;
+        }
+        let taken = held.min(remaining);
+        remaining -= taken;
+        left.push((index, held - taken));
+    }
+    Some(ConsumptionPlan {
+        measure,
+        left,
+        missing: remaining,
+    })
+}
+
+/// Earliest expiration first, lots that do not expire last, then oldest first
+fn fifo_order(entity: Select<Entity>) -> Select<Entity> {
+    entity
+        .order_by(Expr::col(Column::ExpirationDate).is_null(), Order::Asc)
+        .order_by_asc(Column::ExpirationDate)
+        .order_by_asc(Column::CreatedAt)
+}
+
+/// Ingredients with at least one lot matching the parameters
+fn list_groups(list_params: &ListParamsDto) -> Select<Entity> {
+    list_entity(list_params)
+        .select_only()
+        .column(Column::IngredientId)
+        .column_as(db_entities::ingredients::Column::Name, "ingredient_name")
+        .group_by(Column::IngredientId)
+        .group_by(db_entities::ingredients::Column::Name)
+}
+
 fn list_entity(list_params: &ListParamsDto) -> Select<Entity> {
     let mut entity = Entity::find();
     if let Some(value) = list_params.user_id {
-        entity = entity.filter(Column::UserId.eq(value));
+        entity = entity.filter(visible_to(Column::UserId, Column::HouseholdId, value));
     }
     if let Some(value) = list_params.ingredient_id {
         entity = entity.filter(Column::IngredientId.eq(value));
@@ -189,13 +700,144 @@ fn list_entity(list_params: &ListParamsDto) -> Select<Entity> {
             .like(format!("%{}%", value.to_lowercase())),
         );
     }
+    if let Some(value) = list_params.location_id {
+        entity = entity.filter(Column::LocationId.eq(value));
+    }
     if let Some(value) = list_params.max_expiration_date {
         entity = entity.filter(Column::ExpirationDate.lte(value));
     }
+    join_names(entity)
+}
+
+/// Adds the names of the ingredient and of the location
+fn join_names(entity: Select<Entity>) -> Select<Entity> {
     entity
         .join(
             JoinType::InnerJoin,
             db_entities::pantry_items::Relation::Ingredients.def(),
         )
+        .join(
+            JoinType::LeftJoin,
+            db_entities::pantry_items::Relation::Locations.def(),
+        )
         .column_as(db_entities::ingredients::Column::Name, "ingredient_name")
+        .column_as(db_entities::locations::Column::Name, "location_name")
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::{DbBackend, QueryTrait};
+
+    use super::*;
+
+    #[test]
+    fn viewers_cannot_consume_household_lots() {
+        let viewer_id = Uuid::new_v4();
+        let sql = fifo_lots_query(viewer_id, Uuid::new_v4())
+            .build(DbBackend::Postgres)
+            .to_string();
+        // Their own lots, and those of households where they are an owner or member
+        assert!(sql.contains(&format!(r#""pantry_items"."user_id" = '{viewer_id}' OR"#)));
+        assert!(sql.contains(&format!(
+            r#""household_members"."user_id" = '{viewer_id}' AND "household_members"."role" IN ('owner', 'member')"#
+        )));
+        assert!(!sql.contains("'viewer'"));
+    }
+
+    #[test]
+    fn scans_restock_writable_lots_at_the_location() {
+        let (user_id, location_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let sql = writable_lot_query(
+            user_id,
+            Uuid::new_v4(),
+            Some(location_id),
+            NaiveDate::from_ymd_opt(2025, 1, 12),
+        )
+        .build(DbBackend::Postgres)
+        .to_string();
+        assert!(sql.contains(&format!(
+            r#""household_members"."user_id" = '{user_id}' AND "household_members"."role" IN ('owner', 'member')"#
+        )));
+        assert!(sql.contains(&format!(
+            r#""pantry_items"."location_id" = '{location_id}' AND "pantry_items"."expiration_date" = '2025-01-12'"#
+        )), "{sql}");
+
+        let sql = writable_lot_query(user_id, Uuid::new_v4(), None, None)
+            .build(DbBackend::Postgres)
+            .to_string();
+        assert!(sql.contains(
+            r#""pantry_items"."location_id" IS NULL AND "pantry_items"."expiration_date" IS NULL ORDER BY"#
+        ), "{sql}");
+    }
+
+    #[test]
+    fn lots_are_locked_in_fifo_order() {
+        let sql = fifo_lots_query(Uuid::new_v4(), Uuid::new_v4())
+            .build(DbBackend::Postgres)
+            .to_string();
+        assert!(sql.ends_with(
+            r#"ORDER BY "expiration_date" IS NULL ASC, "pantry_items"."expiration_date" ASC, "pantry_items"."created_at" ASC FOR UPDATE"#
+        ), "{sql}");
+    }
+
+    fn lot(quantity: Option<i32>, weight_grams: Option<i32>) -> Model {
+        let now = Utc::now().naive_utc();
+        Model {
+            id: Uuid::new_v4(),
+            ingredient_id: Uuid::new_v4(),
+            purchase_date: None,
+            expiration_date: None,
+            quantity,
+            weight_grams,
+            volume_milli_litres: None,
+            essential: false,
+            running_low: None,
+            user_id: Uuid::new_v4(),
+            household_id: None,
+            location_id: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn lots_are_consumed_in_order() {
+        let lots = [
+            lot(Some(2), None),
+            lot(None, Some(500)),
+            lot(Some(0), None),
+            lot(Some(3), None),
+            lot(Some(4), None),
+        ];
+        let needed = Measurement::parse("4", None).unwrap();
+        assert_eq!(
+            plan_consumption(&lots, &needed),
+            Some(ConsumptionPlan {
+                measure: Measure::Quantity,
+                left: vec![(0, 0), (3, 1)],
+                missing: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn consumption_uses_the_first_convertible_measure() {
+        let lots = [
+            lot(Some(2), None),
+            lot(None, Some(300)),
+            lot(Some(5), Some(200)),
+        ];
+        let needed = Measurement::parse("1", Some("kg")).unwrap();
+        assert_eq!(
+            plan_consumption(&lots, &needed),
+            Some(ConsumptionPlan {
+                measure: Measure::WeightGrams,
+                left: vec![(1, 0), (2, 0)],
+                missing: 500,
+            })
+        );
+        // Counts cannot be weighed
+        assert_eq!(plan_consumption(&lots[..1], &needed), None);
+        assert_eq!(plan_consumption(&[], &needed), None);
+    }
 }
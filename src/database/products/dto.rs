@@ -0,0 +1,89 @@
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::units::{Amount, Measurement, Unit};
+use db_entities::products::Model;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CreateDto {
+    pub gtin: String,
+    pub ingredient_id: Uuid,
+    pub name: Option<String>,
+    pub quantity: Option<i32>,
+    pub weight_grams: Option<i32>,
+    pub volume_milli_litres: Option<i32>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct ListParamsDto {
+    pub gtin: Option<String>,
+    pub ingredient_id: Option<Uuid>,
+    pub limit: u64,
+    pub offset: u64,
+}
+
+#[derive(Serialize, Debug, Clone, Eq, PartialEq)]
+pub struct ProductDto {
+    pub id: Uuid,
+    pub gtin: String,
+    pub ingredient_id: Uuid,
+    pub name: Option<String>,
+    pub quantity: Option<i32>,
+    pub weight_grams: Option<i32>,
+    pub volume_milli_litres: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+impl ProductDto {
+    /// Amount in one package. Products without a package size count as one item.
+    pub fn package(&self) -> Measurement {
+        let (amount, unit) = match (self.quantity, self.weight_grams, self.volume_milli_litres) {
+            (_, Some(grams), _) => (grams, Some(Unit::Gram)),
+            (_, _, Some(millilitres)) => (millilitres, Some(Unit::Millilitre)),
+            (Some(quantity), _, _) => (quantity, None),
+            _ => (1, None),
+        };
+        Measurement {
+            amount: Amount::from_integer(i64::from(amount)),
+            unit,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct ProductsListDto {
+    pub items: Vec<ProductDto>,
+}
+
+impl From<CreateDto> for Model {
+    fn from(value: CreateDto) -> Self {
+        let now = Utc::now().naive_utc();
+
+        Self {
+            id: Uuid::new_v4(),
+            gtin: value.gtin,
+            ingredient_id: value.ingredient_id,
+            name: value.name,
+            quantity: value.quantity,
+            weight_grams: value.weight_grams,
+            volume_milli_litres: value.volume_milli_litres,
+            created_at: now,
+        }
+    }
+}
+
+impl From<Model> for ProductDto {
+    fn from(value: Model) -> Self {
+        Self {
+            id: value.id,
+            gtin: value.gtin,
+            ingredient_id: value.ingredient_id,
+            name: value.name,
+            quantity: value.quantity,
+            weight_grams: value.weight_grams,
+            volume_milli_litres: value.volume_milli_litres,
+            created_at: value.created_at,
+        }
+    }
+}
@@ -0,0 +1,133 @@
+pub mod dto;
+
+use async_trait::async_trait;
+use migrations::OnConflict;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect, Select,
+};
+use uuid::Uuid;
+
+use self::dto::{CreateDto, ListParamsDto, ProductDto, ProductsListDto};
+use crate::database::dto::MetadataDto;
+use crate::database::errors::{error_code, UNIQUE_VIOLATION_CODE};
+use crate::database::{
+    errors::{CreateError, DeleteError, ListError},
+    DBClient,
+};
+use db_entities::products::{ActiveModel, Column, Entity, Model};
+
+#[async_trait]
+pub trait DatabaseCRUD {
+    async fn create_product(&self, request: CreateDto) -> Result<ProductDto, CreateError>;
+    /// Creates the product or replaces the product with the same GTIN
+    async fn upsert_product(&self, request: CreateDto) -> Result<ProductDto, CreateError>;
+    async fn list_products(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<ProductsListDto, ListError>;
+    async fn get_products_metadata(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<MetadataDto, ListError>;
+    async fn delete_product(&self, id: Uuid) -> Result<(), DeleteError>;
+}
+
+#[async_trait]
+impl DatabaseCRUD for DBClient {
+    async fn create_product(&self, request: CreateDto) -> Result<ProductDto, CreateError> {
+        let model: Model = request.into();
+        let id = model.id;
+        let active_model: ActiveModel = model.into();
+        Ok(active_model
+            .insert(&self.database_connection)
+            .await
+            .map_err(|err| {
+                if error_code(&err) == Some(UNIQUE_VIOLATION_CODE.to_owned()) {
+                    CreateError::AlreadyExist { id }
+                } else {
+                    CreateError::Unexpected { error: err.into() }
+                }
+            })?
+            .into())
+    }
+    async fn upsert_product(&self, request: CreateDto) -> Result<ProductDto, CreateError> {
+        let model: Model = request.into();
+        let active_model: ActiveModel = model.into();
+        Ok(Entity::insert(active_model)
+            .on_conflict(
+                OnConflict::column(Column::Gtin)
+                    .update_columns([
+                        Column::IngredientId,
+                        Column::Name,
+                        Column::Quantity,
+                        Column::WeightGrams,
+                        Column::VolumeMilliLitres,
+                    ])
+                    .to_owned(),
+            )
+            .exec_with_returning(&self.database_connection)
+            .await
+            .map_err(|err| CreateError::Unexpected { error: err.into() })?
+            .into())
+    }
+    async fn list_products(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<ProductsListDto, ListError> {
+        Ok(ProductsListDto {
+            items: list_entity(list_params)
+                .limit(list_params.limit)
+                .offset(list_params.offset)
+                .order_by_asc(Column::Gtin)
+                .all(&self.database_connection)
+                .await
+                .map_err(|err| ListError::Unexpected { error: err.into() })?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        })
+    }
+    async fn get_products_metadata(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<MetadataDto, ListError> {
+        let total_count = list_entity(list_params)
+            .count(&self.database_connection)
+            .await
+            .map_err(|err| ListError::Unexpected { error: err.into() })?;
+        Ok(MetadataDto {
+            page: list_params.offset / list_params.limit + 1,
+            per_page: list_params.limit,
+            page_count: total_count / list_params.limit + 1,
+            total_count,
+        })
+    }
+    async fn delete_product(&self, id: Uuid) -> Result<(), DeleteError> {
+        if Entity::delete_by_id(id)
+            .exec(&self.database_connection)
+            .await
+            .map_err(|err| DeleteError::Unexpected {
+                id,
+                error: err.into(),
+            })?
+            .rows_affected
+            == 0
+        {
+            Err(DeleteError::NotFound { id })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn list_entity(list_params: &ListParamsDto) -> Select<Entity> {
+    let mut entity = Entity::find();
+    if let Some(value) = &list_params.gtin {
+        entity = entity.filter(Column::Gtin.eq(value));
+    }
+    if let Some(value) = list_params.ingredient_id {
+        entity = entity.filter(Column::IngredientId.eq(value));
+    }
+    entity
+}
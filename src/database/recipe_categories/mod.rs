@@ -0,0 +1,98 @@
+pub mod dto;
+
+use async_trait::async_trait;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, EntityTrait, JoinType, QueryFilter, QueryOrder, QuerySelect,
+    RelationTrait,
+};
+use uuid::Uuid;
+
+use self::dto::{CreateDto, RecipeCategoriesListDto, RecipeCategoryDto, RecipeCategoryJoinDto};
+use crate::database::errors::{error_code, UNIQUE_VIOLATION_CODE};
+use crate::database::{
+    errors::{CreateError, DeleteError, ListError},
+    DBClient,
+};
+use db_entities::recipe_categories::{ActiveModel, Column, Entity, Model};
+
+#[async_trait]
+pub trait DatabaseCRUD {
+    async fn create_recipe_category(
+        &self,
+        request: CreateDto,
+    ) -> Result<RecipeCategoryDto, CreateError>;
+    async fn list_recipe_categories(
+        &self,
+        recipe_id: Uuid,
+    ) -> Result<RecipeCategoriesListDto, ListError>;
+    /// Detaches category from recipe. `DeleteError` holds the `category_id`
+    async fn delete_recipe_category(
+        &self,
+        recipe_id: Uuid,
+        category_id: Uuid,
+    ) -> Result<(), DeleteError>;
+}
+
+#[async_trait]
+impl DatabaseCRUD for DBClient {
+    async fn create_recipe_category(
+        &self,
+        request: CreateDto,
+    ) -> Result<RecipeCategoryDto, CreateError> {
+        let model: Model = request.into();
+        let id = model.category_id;
+        let active_model: ActiveModel = model.into();
+        Ok(active_model
+            .insert(&self.database_connection)
+            .await
+            .map_err(|err| {
+                if error_code(&err) == Some(UNIQUE_VIOLATION_CODE.to_owned()) {
+                    CreateError::AlreadyExist { id }
+                } else {
+                    CreateError::Unexpected { error: err.into() }
+                }
+            })?
+            .into())
+    }
+    async fn list_recipe_categories(
+        &self,
+        recipe_id: Uuid,
+    ) -> Result<RecipeCategoriesListDto, ListError> {
+        Ok(RecipeCategoriesListDto {
+            items: Entity::find()
+                .filter(Column::RecipeId.eq(recipe_id))
+                .join(
+                    JoinType::InnerJoin,
+                    db_entities::recipe_categories::Relation::Categories.def(),
+                )
+                .column_as(db_entities::categories::Column::Name, "category_name")
+                .order_by_asc(db_entities::categories::Column::Name)
+                .into_model::<RecipeCategoryJoinDto>()
+                .all(&self.database_connection)
+                .await
+                .map_err(|err| ListError::Unexpected { error: err.into() })?,
+        })
+    }
+    async fn delete_recipe_category(
+        &self,
+        recipe_id: Uuid,
+        category_id: Uuid,
+    ) -> Result<(), DeleteError> {
+        if Entity::delete_many()
+            .filter(Column::RecipeId.eq(recipe_id))
+            .filter(Column::CategoryId.eq(category_id))
+            .exec(&self.database_connection)
+            .await
+            .map_err(|err| DeleteError::Unexpected {
+                id: category_id,
+                error: err.into(),
+            })?
+            .rows_affected
+            == 0
+        {
+            Err(DeleteError::NotFound { id: category_id })
+        } else {
+            Ok(())
+        }
+    }
+}
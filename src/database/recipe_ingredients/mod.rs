@@ -14,6 +14,7 @@ use self::dto::{
 
 use crate::database::dto::MetadataDto;
 use crate::database::errors::{error_code, UNIQUE_VIOLATION_CODE};
+use crate::database::households::visible_to;
 use crate::database::recipe_ingredients::dto::RecipeIngredientJoinDto;
 use crate::database::{
     errors::{CreateError, DeleteError, GetError, ListError, UpdateError},
@@ -37,6 +38,16 @@ pub trait DatabaseCRUD {
         &self,
         list_params: &ListParamsDto,
     ) -> Result<MetadataDto, ListError>;
+    /// Returns every ingredient of the recipe without pagination
+    async fn list_all_recipe_ingredients(
+        &self,
+        recipe_id: Uuid,
+    ) -> Result<Vec<RecipeIngredientDto>, ListError>;
+    /// Returns every ingredient of the recipe with ingredient names, in the order they were added
+    async fn list_all_recipe_ingredients_join(
+        &self,
+        recipe_id: Uuid,
+    ) -> Result<Vec<RecipeIngredientJoinDto>, ListError>;
     async fn update_recipe_ingredient(
         &self,
         id: Uuid,
@@ -107,6 +118,33 @@ impl DatabaseCRUD for DBClient {
             total_count,
         })
     }
+    async fn list_all_recipe_ingredients(
+        &self,
+        recipe_id: Uuid,
+    ) -> Result<Vec<RecipeIngredientDto>, ListError> {
+        Ok(Entity::find()
+            .filter(Column::RecipeId.eq(recipe_id))
+            .all(&self.database_connection)
+            .await
+            .map_err(|err| ListError::Unexpected { error: err.into() })?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+    async fn list_all_recipe_ingredients_join(
+        &self,
+        recipe_id: Uuid,
+    ) -> Result<Vec<RecipeIngredientJoinDto>, ListError> {
+        list_entity(&ListParamsDto {
+            recipe_id: Some(recipe_id),
+            ..Default::default()
+        })
+        .order_by_asc(Column::CreatedAt)
+        .into_model::<RecipeIngredientJoinDto>()
+        .all(&self.database_connection)
+        .await
+        .map_err(|err| ListError::Unexpected { error: err.into() })
+    }
     async fn update_recipe_ingredient(
         &self,
         id: Uuid,
@@ -176,6 +214,9 @@ fn list_entity(list_params: &ListParamsDto) -> Select<Entity> {
     )
     .column_as(db_entities::ingredients::Column::Name, "ingredient_name")
     .column_as(db_entities::recipes::Column::Name, "recipe_name");
+    if let Some(value) = list_params.ingredient_id {
+        entity = entity.filter(Column::IngredientId.eq(value));
+    }
     if let Some(value) = &list_params.name_contains {
         entity = entity.filter(
             Expr::expr(Func::lower(
@@ -186,7 +227,11 @@ fn list_entity(list_params: &ListParamsDto) -> Select<Entity> {
     }
     if let Some(user_id) = list_params.user_id {
         entity = entity
-            .filter(db_entities::recipes::Column::UserId.eq(user_id))
+            .filter(visible_to(
+                db_entities::recipes::Column::UserId,
+                db_entities::recipes::Column::HouseholdId,
+                user_id,
+            ))
             .distinct_on([(
                 db_entities::ingredients::Entity,
                 db_entities::ingredients::Column::Name,
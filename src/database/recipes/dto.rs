@@ -3,11 +3,13 @@ use serde::{Deserialize, Serialize};
 use std::hash::Hash;
 use uuid::Uuid;
 
+use crate::database::recipe_ingredients::dto::RecipeIngredientJoinDto;
 use db_entities::recipes::Model;
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct CreateDto {
     pub user_id: Uuid,
+    pub household_id: Option<Uuid>,
     pub name: String,
     pub prep_time_mins: Option<i32>,
     pub total_time_mins: Option<i32>,
@@ -19,11 +21,29 @@ pub struct CreateDto {
     pub notes: Option<String>,
 }
 
+/// Ingredient of a recipe created together with its ingredients,
+/// looked up by name unless it was matched to an existing ingredient
+#[derive(Debug, Clone)]
+pub struct NewRecipeIngredientDto {
+    pub name: String,
+    pub ingredient_id: Option<Uuid>,
+    pub amount: Option<String>,
+    pub unit: Option<String>,
+    pub optional: bool,
+}
+
+#[derive(Debug)]
+pub struct RecipeWithIngredientsDto {
+    pub recipe: RecipeDto,
+    pub ingredients: Vec<RecipeIngredientJoinDto>,
+}
+
 #[derive(Deserialize, Debug, Default)]
 pub struct ListParamsDto {
     pub name_contains: Option<String>,
     pub total_time_mins: Option<i32>,
     pub user_id: Option<Uuid>,
+    pub category_ids: Option<Vec<Uuid>>,
     pub limit: u64,
     pub offset: u64,
 }
@@ -32,13 +52,14 @@ pub struct ListParamsDto {
 pub struct ListRecipeJoinParamsDto {
     pub user_id: Uuid,
     pub ingredient_ids: Vec<Uuid>,
+    pub category_ids: Option<Vec<Uuid>>,
     pub limit: u64,
     pub offset: u64,
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct UpdateDto {
-    pub user_id: Uuid,
+    pub household_id: Option<Uuid>,
     pub name: String,
     pub prep_time_mins: Option<i32>,
     pub total_time_mins: Option<i32>,
@@ -50,10 +71,62 @@ pub struct UpdateDto {
     pub notes: Option<String>,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+pub struct CookDto {
+    pub recipe_id: Uuid,
+    pub user_id: Uuid,
+    pub cooked_on: NaiveDate,
+    pub deduct_ingredients: bool,
+}
+
+/// A recipe ingredient the pantry did not hold enough of
+#[derive(Serialize, Debug, Clone, Eq, PartialEq)]
+pub struct ShortageDto {
+    pub ingredient_id: Uuid,
+    pub amount: Option<String>,
+    pub unit: Option<String>,
+    /// Missing amount in the pantry item's measure, if it could be computed
+    pub missing: Option<i32>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CookedRecipeDto {
+    pub recipe: RecipeDto,
+    pub shortages: Vec<ShortageDto>,
+}
+
+/// How much of a recipe the user's pantry covers
+#[derive(Serialize, Debug, Clone)]
+pub struct RecipeSuggestionDto {
+    pub recipe: RecipeDto,
+    /// Number of non-optional ingredients
+    pub ingredient_count: u64,
+    pub covered_count: u64,
+    pub missing_ingredient_ids: Vec<Uuid>,
+    /// Earliest expiration date among the pantry items covering the recipe
+    pub soonest_expiration: Option<NaiveDate>,
+}
+
+impl RecipeSuggestionDto {
+    #[allow(clippy::cast_precision_loss)]
+    pub fn coverage(&self) -> f64 {
+        if self.ingredient_count == 0 {
+            return 1.0;
+        }
+        self.covered_count as f64 / self.ingredient_count as f64
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct RecipeSuggestionsListDto {
+    pub items: Vec<RecipeSuggestionDto>,
+}
+
 #[derive(Serialize, Debug, Clone, Eq)]
 pub struct RecipeDto {
     pub id: Uuid,
     pub user_id: Uuid,
+    pub household_id: Option<Uuid>,
     pub name: String,
     pub prep_time_mins: Option<i32>,
     pub total_time_mins: Option<i32>,
@@ -90,6 +163,7 @@ impl From<CreateDto> for Model {
         Self {
             id: Uuid::new_v4(),
             user_id: value.user_id,
+            household_id: value.household_id,
             name: value.name,
             prep_time_mins: value.prep_time_mins,
             total_time_mins: value.total_time_mins,
@@ -110,6 +184,7 @@ impl From<Model> for RecipeDto {
         Self {
             id: value.id,
             user_id: value.user_id,
+            household_id: value.household_id,
             name: value.name,
             prep_time_mins: value.prep_time_mins,
             total_time_mins: value.total_time_mins,
@@ -1,29 +1,48 @@
 pub mod dto;
 
+use std::cmp::Ordering;
 use std::collections::HashSet;
 
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{NaiveDate, Utc};
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DbErr, EntityTrait, JoinType, PaginatorTrait, QueryFilter,
-    QueryOrder, QuerySelect, RelationTrait, Select, Set,
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, JoinType, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, RelationTrait, Select, Set, TransactionTrait,
 };
 use uuid::Uuid;
 
-use self::dto::{CreateDto, ListParamsDto, RecipeDto, RecipesListDto, UpdateDto};
+use self::dto::{
+    CookDto, CookedRecipeDto, CreateDto, ListParamsDto, NewRecipeIngredientDto, RecipeDto,
+    RecipeSuggestionDto, RecipeSuggestionsListDto, RecipeWithIngredientsDto, RecipesListDto,
+    ShortageDto, UpdateDto,
+};
 use crate::database::dto::MetadataDto;
 use crate::database::errors::{error_code, UNIQUE_VIOLATION_CODE};
+use crate::database::households::visible_to;
+use crate::database::ingredients::dto::CreateDto as IngredientCreateDto;
+use crate::database::pantry_items;
+use crate::database::recipe_ingredients::dto::{
+    CreateDto as RecipeIngredientCreateDto, RecipeIngredientJoinDto,
+};
 use crate::database::recipes::dto::ListRecipeJoinParamsDto;
 use crate::database::{
     errors::{CreateError, DeleteError, GetError, ListError, UpdateError},
     DBClient,
 };
+use crate::units::{Measure, Measurement};
 use db_entities::recipes::{ActiveModel, Column, Entity, Model};
-use migrations::{Expr, Func};
+use migrations::{Expr, Func, OnConflict, Query, SimpleExpr};
 
 #[async_trait]
 pub trait DatabaseCRUD {
     async fn create_recipe(&self, request: CreateDto) -> Result<RecipeDto, CreateError>;
+    /// Creates the recipe and its ingredients in one transaction. Ingredients are looked up
+    /// by case-insensitive name and created if missing, repeated ones are added once.
+    async fn create_recipe_with_ingredients(
+        &self,
+        request: CreateDto,
+        ingredients: Vec<NewRecipeIngredientDto>,
+    ) -> Result<RecipeWithIngredientsDto, CreateError>;
     async fn get_recipe(&self, id: Uuid) -> Result<RecipeDto, GetError>;
     async fn list_recipes(&self, list_params: &ListParamsDto) -> Result<RecipesListDto, ListError>;
     async fn get_recipes_metadata(
@@ -38,7 +57,17 @@ pub trait DatabaseCRUD {
         &self,
         list_params: &ListRecipeJoinParamsDto,
     ) -> Result<MetadataDto, ListError>;
+    /// Ranks recipes by how much of their non-optional ingredients the user's pantry covers,
+    /// ignoring pantry items expired before `today`
+    async fn list_recipe_suggestions(
+        &self,
+        list_params: &ListParamsDto,
+        today: NaiveDate,
+    ) -> Result<RecipeSuggestionsListDto, ListError>;
     async fn update_recipe(&self, id: Uuid, request: UpdateDto) -> Result<RecipeDto, UpdateError>;
+    /// Sets `last_cooked` and deducts the ingredients from the pantry items the user can see
+    /// in one transaction
+    async fn cook_recipe(&self, request: CookDto) -> Result<CookedRecipeDto, UpdateError>;
     async fn delete_recipe(&self, id: Uuid) -> Result<(), DeleteError>;
 }
 
@@ -60,6 +89,31 @@ impl DatabaseCRUD for DBClient {
             })?
             .into())
     }
+    async fn create_recipe_with_ingredients(
+        &self,
+        request: CreateDto,
+        ingredients: Vec<NewRecipeIngredientDto>,
+    ) -> Result<RecipeWithIngredientsDto, CreateError> {
+        let model: Model = request.into();
+        let id = model.id;
+        let create_error = |err: DbErr| {
+            if error_code(&err) == Some(UNIQUE_VIOLATION_CODE.to_owned()) {
+                CreateError::AlreadyExist { id }
+            } else {
+                CreateError::Unexpected { error: err.into() }
+            }
+        };
+        let txn = self
+            .database_connection
+            .begin()
+            .await
+            .map_err(create_error)?;
+        let created = create_with_ingredients(&txn, model, ingredients)
+            .await
+            .map_err(create_error)?;
+        txn.commit().await.map_err(create_error)?;
+        Ok(created)
+    }
     async fn get_recipe(&self, id: Uuid) -> Result<RecipeDto, GetError> {
         Ok(Entity::find_by_id(id)
             .one(&self.database_connection)
@@ -131,6 +185,54 @@ impl DatabaseCRUD for DBClient {
             total_count,
         })
     }
+    async fn list_recipe_suggestions(
+        &self,
+        list_params: &ListParamsDto,
+        today: NaiveDate,
+    ) -> Result<RecipeSuggestionsListDto, ListError> {
+        let recipes = list_entity(list_params)
+            .all(&self.database_connection)
+            .await
+            .map_err(|err| ListError::Unexpected { error: err.into() })?;
+        let recipe_ingredients = db_entities::recipe_ingredients::Entity::find()
+            .filter(
+                db_entities::recipe_ingredients::Column::RecipeId
+                    .is_in(recipes.iter().map(|recipe| recipe.id)),
+            )
+            .filter(db_entities::recipe_ingredients::Column::Optional.eq(false))
+            .all(&self.database_connection)
+            .await
+            .map_err(|err| ListError::Unexpected { error: err.into() })?;
+        let mut pantry_items = db_entities::pantry_items::Entity::find().filter(
+            db_entities::pantry_items::Column::ExpirationDate
+                .is_null()
+                .or(db_entities::pantry_items::Column::ExpirationDate.gte(today)),
+        );
+        if let Some(user_id) = list_params.user_id {
+            pantry_items = pantry_items.filter(visible_to(
+                db_entities::pantry_items::Column::UserId,
+                db_entities::pantry_items::Column::HouseholdId,
+                user_id,
+            ));
+        }
+        let pantry_items = pantry_items
+            .all(&self.database_connection)
+            .await
+            .map_err(|err| ListError::Unexpected { error: err.into() })?;
+
+        let mut suggestions: Vec<RecipeSuggestionDto> = recipes
+            .into_iter()
+            .map(|recipe| suggest(recipe, &recipe_ingredients, &pantry_items))
+            .collect();
+        suggestions.sort_by(rank);
+        Ok(RecipeSuggestionsListDto {
+            items: suggestions
+                .into_iter()
+                .skip(usize::try_from(list_params.offset).unwrap_or(usize::MAX))
+                .take(usize::try_from(list_params.limit).unwrap_or(usize::MAX))
+                .collect(),
+        })
+    }
     async fn update_recipe(&self, id: Uuid, request: UpdateDto) -> Result<RecipeDto, UpdateError> {
         let recipe: Model = Entity::find_by_id(id)
             .one(&self.database_connection)
@@ -141,12 +243,13 @@ impl DatabaseCRUD for DBClient {
             })?
             .ok_or(UpdateError::NotFound { id })?;
         let mut recipe: ActiveModel = recipe.into();
-        recipe.user_id = Set(request.user_id);
+        recipe.household_id = Set(request.household_id);
         recipe.name = Set(request.name);
-        recipe.prep_time_mins = Set(request.total_time_mins);
+        recipe.prep_time_mins = Set(request.prep_time_mins);
         recipe.total_time_mins = Set(request.total_time_mins);
         recipe.link = Set(request.link);
         recipe.instructions = Set(request.instructions);
+        recipe.image = Set(request.image);
         recipe.last_cooked = Set(request.last_cooked);
         recipe.rating = Set(request.rating);
         recipe.notes = Set(request.notes);
@@ -168,6 +271,25 @@ impl DatabaseCRUD for DBClient {
             })?
             .into())
     }
+    async fn cook_recipe(&self, request: CookDto) -> Result<CookedRecipeDto, UpdateError> {
+        let id = request.recipe_id;
+        let txn =
+            self.database_connection
+                .begin()
+                .await
+                .map_err(|err| UpdateError::Unexpected {
+                    id,
+                    error: err.into(),
+                })?;
+        let cooked = cook(&txn, &request)
+            .await
+            .map_err(|err| cook_error(id, err))?;
+        txn.commit().await.map_err(|err| UpdateError::Unexpected {
+            id,
+            error: err.into(),
+        })?;
+        Ok(cooked)
+    }
     async fn delete_recipe(&self, id: Uuid) -> Result<(), DeleteError> {
         if Entity::delete_by_id(id)
             .exec(&self.database_connection)
@@ -198,20 +320,313 @@ fn list_entity(list_params: &ListParamsDto) -> Select<Entity> {
         entity = entity.filter(Column::TotalTimeMins.lte(value));
     }
     if let Some(value) = list_params.user_id {
-        entity = entity.filter(Column::UserId.eq(value));
+        entity = entity.filter(visible_to(Column::UserId, Column::HouseholdId, value));
+    }
+    if let Some(value) = &list_params.category_ids {
+        entity = entity.filter(in_categories(value));
     }
     entity
 }
 
 fn list_join_entity(list_params: &ListRecipeJoinParamsDto) -> Select<Entity> {
-    Entity::find()
+    let mut entity = Entity::find();
+    if let Some(value) = &list_params.category_ids {
+        entity = entity.filter(in_categories(value));
+    }
+    entity
         .join(
             JoinType::InnerJoin,
             db_entities::recipes::Relation::RecipeIngredients.def(),
         )
-        .filter(Column::UserId.eq(list_params.user_id))
+        .filter(visible_to(
+            Column::UserId,
+            Column::HouseholdId,
+            list_params.user_id,
+        ))
         .filter(
             db_entities::recipe_ingredients::Column::IngredientId
                 .is_in(list_params.ingredient_ids.clone()),
         )
 }
+
+/// Works out which of the recipe's non-optional ingredients the pantry items cover
+fn suggest(
+    recipe: Model,
+    recipe_ingredients: &[db_entities::recipe_ingredients::Model],
+    pantry_items: &[db_entities::pantry_items::Model],
+) -> RecipeSuggestionDto {
+    let mut ingredient_count = 0;
+    let mut covered_count = 0;
+    let mut missing_ingredient_ids = Vec::new();
+    let mut soonest_expiration: Option<NaiveDate> = None;
+    for recipe_ingredient in recipe_ingredients
+        .iter()
+        .filter(|ri| ri.recipe_id == recipe.id)
+    {
+        ingredient_count += 1;
+        let held: Vec<&db_entities::pantry_items::Model> = pantry_items
+            .iter()
+            .filter(|item| item.ingredient_id == recipe_ingredient.ingredient_id)
+            .collect();
+        if covers(recipe_ingredient, &held) {
+            covered_count += 1;
+            for expiration_date in held.iter().filter_map(|item| item.expiration_date) {
+                soonest_expiration = Some(
+                    soonest_expiration
+                        .map_or(expiration_date, |soonest| soonest.min(expiration_date)),
+                );
+            }
+        } else {
+            missing_ingredient_ids.push(recipe_ingredient.ingredient_id);
+        }
+    }
+    RecipeSuggestionDto {
+        recipe: recipe.into(),
+        ingredient_count,
+        covered_count,
+        missing_ingredient_ids,
+        soonest_expiration,
+    }
+}
+
+/// Whether the pantry items hold enough of the recipe ingredient.
+/// Amounts that cannot be compared with the pantry item count any stock as enough.
+fn covers(
+    recipe_ingredient: &db_entities::recipe_ingredients::Model,
+    pantry_items: &[&db_entities::pantry_items::Model],
+) -> bool {
+    let held: Vec<(Measure, i32)> = pantry_items
+        .iter()
+        .filter_map(|item| {
+            Measure::of_pantry_item(item.quantity, item.weight_grams, item.volume_milli_litres)
+        })
+        .collect();
+    let Some((measure, _)) = held.first() else {
+        return false;
+    };
+    let held: i32 = held
+        .iter()
+        .filter(|(m, _)| m == measure)
+        .map(|(_, amount)| amount)
+        .sum();
+    let needed = recipe_ingredient.amount.as_deref().and_then(|amount| {
+        Measurement::parse(amount, recipe_ingredient.unit.as_deref())?.in_measure(*measure)
+    });
+    match needed {
+        Some(needed) => held >= needed,
+        None => held > 0,
+    }
+}
+
+/// Orders by coverage, then by the soonest expiring pantry item, then by name
+fn rank(a: &RecipeSuggestionDto, b: &RecipeSuggestionDto) -> Ordering {
+    // Cross-multiplied to compare the coverage fractions exactly
+    let fraction = |suggestion: &RecipeSuggestionDto| {
+        if suggestion.ingredient_count == 0 {
+            (1, 1)
+        } else {
+            (suggestion.covered_count, suggestion.ingredient_count)
+        }
+    };
+    let (a_covered, a_count) = fraction(a);
+    let (b_covered, b_count) = fraction(b);
+    (b_covered * a_count)
+        .cmp(&(a_covered * b_count))
+        .then(match (a.soonest_expiration, b.soonest_expiration) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        })
+        .then(a.recipe.name.cmp(&b.recipe.name))
+}
+
+/// Recipes tagged with at least one of `category_ids`
+fn in_categories(category_ids: &[Uuid]) -> SimpleExpr {
+    Column::Id.in_subquery(
+        Query::select()
+            .column(db_entities::recipe_categories::Column::RecipeId)
+            .from(db_entities::recipe_categories::Entity)
+            .and_where(
+                db_entities::recipe_categories::Column::CategoryId.is_in(category_ids.to_vec()),
+            )
+            .to_owned(),
+    )
+}
+
+/// Marks the recipe as cooked on `cooked_on` and, if requested, deducts its
+/// non-optional ingredients from the pantry lots the user can see, earliest expiration first.
+/// Returns the ingredients the pantry was short of.
+pub(crate) async fn cook<C: ConnectionTrait>(
+    db: &C,
+    request: &CookDto,
+) -> Result<CookedRecipeDto, DbErr> {
+    let recipe = Entity::find_by_id(request.recipe_id)
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(request.recipe_id.to_string()))?;
+    let recipe = if recipe
+        .last_cooked
+        .is_none_or(|last_cooked| last_cooked < request.cooked_on)
+    {
+        let mut active_model: ActiveModel = recipe.into();
+        active_model.last_cooked = Set(Some(request.cooked_on));
+        active_model.updated_at = Set(Utc::now().naive_utc());
+        active_model.update(db).await?
+    } else {
+        recipe
+    };
+    let mut shortages = Vec::new();
+    if !request.deduct_ingredients {
+        return Ok(CookedRecipeDto {
+            recipe: recipe.into(),
+            shortages,
+        });
+    }
+
+    let recipe_ingredients = db_entities::recipe_ingredients::Entity::find()
+        .filter(db_entities::recipe_ingredients::Column::RecipeId.eq(recipe.id))
+        .filter(db_entities::recipe_ingredients::Column::Optional.eq(false))
+        .all(db)
+        .await?;
+    for recipe_ingredient in recipe_ingredients {
+        let needed = recipe_ingredient
+            .amount
+            .as_deref()
+            .and_then(|amount| Measurement::parse(amount, recipe_ingredient.unit.as_deref()));
+        let lots =
+            pantry_items::fifo_lots(db, request.user_id, recipe_ingredient.ingredient_id).await?;
+        if lots.is_empty() {
+            shortages.push(ShortageDto {
+                ingredient_id: recipe_ingredient.ingredient_id,
+                amount: recipe_ingredient.amount,
+                unit: recipe_ingredient.unit,
+                missing: needed.map(|needed| needed.in_base_measure().1),
+            });
+            continue;
+        }
+        // No lot has an amount the recipe amount can be compared with
+        let Some(needed) = needed else {
+            continue;
+        };
+        let Some(consumed) = pantry_items::consume_lots(db, request.user_id, lots, &needed).await?
+        else {
+            continue;
+        };
+        if consumed.missing > 0 {
+            shortages.push(ShortageDto {
+                ingredient_id: recipe_ingredient.ingredient_id,
+                amount: recipe_ingredient.amount,
+                unit: recipe_ingredient.unit,
+                missing: Some(consumed.missing),
+            });
+        }
+    }
+    Ok(CookedRecipeDto {
+        recipe: recipe.into(),
+        shortages,
+    })
+}
+
+async fn create_with_ingredients<C: ConnectionTrait>(
+    db: &C,
+    recipe: Model,
+    ingredients: Vec<NewRecipeIngredientDto>,
+) -> Result<RecipeWithIngredientsDto, DbErr> {
+    let active_model: ActiveModel = recipe.into();
+    let recipe = active_model.insert(db).await?;
+    let mut added: Vec<RecipeIngredientJoinDto> = Vec::new();
+    for new_ingredient in ingredients {
+        let matched = match new_ingredient.ingredient_id {
+            Some(id) => {
+                db_entities::ingredients::Entity::find_by_id(id)
+                    .one(db)
+                    .await?
+            }
+            None => None,
+        };
+        let ingredient = match matched {
+            Some(ingredient) => ingredient,
+            None => ingredient_by_name(db, &new_ingredient.name).await?,
+        };
+        if added
+            .iter()
+            .any(|ingredient_added| ingredient_added.ingredient_id == ingredient.id)
+        {
+            continue;
+        }
+        let recipe_ingredient: db_entities::recipe_ingredients::Model = RecipeIngredientCreateDto {
+            recipe_id: recipe.id,
+            ingredient_id: ingredient.id,
+            amount: new_ingredient.amount,
+            unit: new_ingredient.unit,
+            optional: new_ingredient.optional,
+        }
+        .into();
+        let active_model: db_entities::recipe_ingredients::ActiveModel = recipe_ingredient.into();
+        let recipe_ingredient = active_model.insert(db).await?;
+        added.push(RecipeIngredientJoinDto {
+            id: recipe_ingredient.id,
+            ingredient_id: ingredient.id,
+            ingredient_name: ingredient.name,
+            recipe_id: recipe.id,
+            recipe_name: recipe.name.clone(),
+            amount: recipe_ingredient.amount,
+            unit: recipe_ingredient.unit,
+            optional: recipe_ingredient.optional,
+            created_at: recipe_ingredient.created_at,
+            updated_at: recipe_ingredient.updated_at,
+        });
+    }
+    Ok(RecipeWithIngredientsDto {
+        recipe: recipe.into(),
+        ingredients: added,
+    })
+}
+
+/// Returns the ingredient with the name, ignoring case, creating it if there is none
+async fn ingredient_by_name<C: ConnectionTrait>(
+    db: &C,
+    name: &str,
+) -> Result<db_entities::ingredients::Model, DbErr> {
+    let find = || {
+        db_entities::ingredients::Entity::find().filter(
+            Expr::expr(Func::lower(Expr::col(
+                db_entities::ingredients::Column::Name,
+            )))
+            .eq(name.to_lowercase()),
+        )
+    };
+    if let Some(ingredient) = find().one(db).await? {
+        return Ok(ingredient);
+    }
+    let ingredient: db_entities::ingredients::Model = IngredientCreateDto {
+        name: name.to_owned(),
+    }
+    .into();
+    let active_model: db_entities::ingredients::ActiveModel = ingredient.into();
+    // A failed insert would abort the transaction, so conflicts are skipped instead
+    db_entities::ingredients::Entity::insert(active_model)
+        .on_conflict(
+            OnConflict::column(db_entities::ingredients::Column::Name)
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+    find()
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(name.to_owned()))
+}
+
+pub(crate) fn cook_error(id: Uuid, err: DbErr) -> UpdateError {
+    if let DbErr::RecordNotFound(_) = err {
+        UpdateError::NotFound { id }
+    } else {
+        UpdateError::Unexpected {
+            id,
+            error: err.into(),
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use chrono::{NaiveDateTime, Utc};
+use sea_orm::FromQueryResult;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use db_entities::shopping_list_items::Model as ItemModel;
+use db_entities::shopping_lists::Model;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CreateDto {
+    pub user_id: Uuid,
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct UpdateDto {
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct ListParamsDto {
+    pub user_id: Option<Uuid>,
+    pub limit: u64,
+    pub offset: u64,
+}
+
+#[derive(Serialize, Debug, Clone, Eq, PartialEq)]
+pub struct ShoppingListDto {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct ShoppingListsListDto {
+    pub items: Vec<ShoppingListDto>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CreateItemDto {
+    pub shopping_list_id: Uuid,
+    pub ingredient_id: Uuid,
+    pub recipe_id: Option<Uuid>,
+    pub amount: Option<String>,
+    pub unit: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct UpdateItemDto {
+    pub amount: Option<String>,
+    pub unit: Option<String>,
+    pub checked: bool,
+}
+
+#[derive(Serialize, Debug, Clone, Eq, PartialEq)]
+pub struct ShoppingListItemDto {
+    pub id: Uuid,
+    pub shopping_list_id: Uuid,
+    pub ingredient_id: Uuid,
+    pub recipe_id: Option<Uuid>,
+    pub amount: Option<String>,
+    pub unit: Option<String>,
+    pub checked: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Debug, Clone, Eq, PartialEq, FromQueryResult)]
+pub struct ShoppingListItemJoinDto {
+    pub id: Uuid,
+    pub shopping_list_id: Uuid,
+    pub ingredient_id: Uuid,
+    pub ingredient_name: String,
+    pub recipe_id: Option<Uuid>,
+    pub amount: Option<String>,
+    pub unit: Option<String>,
+    pub checked: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct ShoppingListItemsListDto {
+    pub items: Vec<ShoppingListItemJoinDto>,
+}
+
+impl From<CreateDto> for Model {
+    fn from(value: CreateDto) -> Self {
+        let now = Utc::now().naive_utc();
+
+        Self {
+            id: Uuid::new_v4(),
+            user_id: value.user_id,
+            name: value.name,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+impl From<Model> for ShoppingListDto {
+    fn from(value: Model) -> Self {
+        Self {
+            id: value.id,
+            user_id: value.user_id,
+            name: value.name,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+impl From<CreateItemDto> for ItemModel {
+    fn from(value: CreateItemDto) -> Self {
+        let now = Utc::now().naive_utc();
+
+        Self {
+            id: Uuid::new_v4(),
+            shopping_list_id: value.shopping_list_id,
+            ingredient_id: value.ingredient_id,
+            recipe_id: value.recipe_id,
+            amount: value.amount,
+            unit: value.unit,
+            checked: false,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+impl From<ItemModel> for ShoppingListItemDto {
+    fn from(value: ItemModel) -> Self {
+        Self {
+            id: value.id,
+            shopping_list_id: value.shopping_list_id,
+            ingredient_id: value.ingredient_id,
+            recipe_id: value.recipe_id,
+            amount: value.amount,
+            unit: value.unit,
+            checked: value.checked,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
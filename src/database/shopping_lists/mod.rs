@@ -0,0 +1,360 @@
+pub mod dto;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, JoinType, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, RelationTrait, Select, Set, TransactionTrait,
+};
+use uuid::Uuid;
+
+use self::dto::{
+    CreateDto, CreateItemDto, ListParamsDto, ShoppingListDto, ShoppingListItemDto,
+    ShoppingListItemJoinDto, ShoppingListItemsListDto, ShoppingListsListDto, UpdateDto,
+    UpdateItemDto,
+};
+use crate::database::dto::MetadataDto;
+use crate::database::errors::{error_code, UNIQUE_VIOLATION_CODE};
+use crate::database::{
+    errors::{CreateError, DeleteError, GetError, ListError, UpdateError},
+    DBClient,
+};
+use db_entities::shopping_list_items::{
+    ActiveModel as ItemActiveModel, Column as ItemColumn, Entity as ItemEntity, Model as ItemModel,
+};
+use db_entities::shopping_lists::{ActiveModel, Column, Entity, Model};
+
+#[async_trait]
+pub trait DatabaseCRUD {
+    async fn create_shopping_list(
+        &self,
+        request: CreateDto,
+    ) -> Result<ShoppingListDto, CreateError>;
+    async fn get_shopping_list(&self, id: Uuid) -> Result<ShoppingListDto, GetError>;
+    async fn list_shopping_lists(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<ShoppingListsListDto, ListError>;
+    async fn get_shopping_lists_metadata(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<MetadataDto, ListError>;
+    async fn update_shopping_list(
+        &self,
+        id: Uuid,
+        request: UpdateDto,
+    ) -> Result<ShoppingListDto, UpdateError>;
+    async fn delete_shopping_list(&self, id: Uuid) -> Result<(), DeleteError>;
+    /// Inserts all items in one transaction
+    async fn create_shopping_list_items(
+        &self,
+        request: Vec<CreateItemDto>,
+    ) -> Result<Vec<ShoppingListItemDto>, CreateError>;
+    /// Inserts the new items and updates the existing ones of the shopping list in one
+    /// transaction, returning the inserted items followed by the updated ones
+    async fn add_shopping_list_items(
+        &self,
+        shopping_list_id: Uuid,
+        new_items: Vec<CreateItemDto>,
+        updated_items: Vec<(Uuid, UpdateItemDto)>,
+    ) -> Result<Vec<ShoppingListItemDto>, UpdateError>;
+    async fn get_shopping_list_item(&self, id: Uuid) -> Result<ShoppingListItemDto, GetError>;
+    async fn list_shopping_list_items(
+        &self,
+        shopping_list_id: Uuid,
+    ) -> Result<ShoppingListItemsListDto, ListError>;
+    async fn update_shopping_list_item(
+        &self,
+        id: Uuid,
+        request: UpdateItemDto,
+    ) -> Result<ShoppingListItemDto, UpdateError>;
+    async fn delete_shopping_list_item(&self, id: Uuid) -> Result<(), DeleteError>;
+}
+
+#[async_trait]
+impl DatabaseCRUD for DBClient {
+    async fn create_shopping_list(
+        &self,
+        request: CreateDto,
+    ) -> Result<ShoppingListDto, CreateError> {
+        let model: Model = request.into();
+        let id = model.id;
+        let active_model: ActiveModel = model.into();
+        Ok(active_model
+            .insert(&self.database_connection)
+            .await
+            .map_err(|err| {
+                if error_code(&err) == Some(UNIQUE_VIOLATION_CODE.to_owned()) {
+                    CreateError::AlreadyExist { id }
+                } else {
+                    CreateError::Unexpected { error: err.into() }
+                }
+            })?
+            .into())
+    }
+    async fn get_shopping_list(&self, id: Uuid) -> Result<ShoppingListDto, GetError> {
+        Ok(Entity::find_by_id(id)
+            .one(&self.database_connection)
+            .await
+            .map_err(|err| GetError::Unexpected {
+                id,
+                error: err.into(),
+            })?
+            .ok_or(GetError::NotFound { id })?
+            .into())
+    }
+    async fn list_shopping_lists(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<ShoppingListsListDto, ListError> {
+        Ok(ShoppingListsListDto {
+            items: list_entity(list_params)
+                .limit(list_params.limit)
+                .offset(list_params.offset)
+                .order_by_desc(Column::UpdatedAt)
+                .all(&self.database_connection)
+                .await
+                .map_err(|err| ListError::Unexpected { error: err.into() })?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        })
+    }
+    async fn get_shopping_lists_metadata(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<MetadataDto, ListError> {
+        let total_count = list_entity(list_params)
+            .count(&self.database_connection)
+            .await
+            .map_err(|err| ListError::Unexpected { error: err.into() })?;
+        Ok(MetadataDto {
+            page: list_params.offset / list_params.limit + 1,
+            per_page: list_params.limit,
+            page_count: total_count / list_params.limit + 1,
+            total_count,
+        })
+    }
+    async fn update_shopping_list(
+        &self,
+        id: Uuid,
+        request: UpdateDto,
+    ) -> Result<ShoppingListDto, UpdateError> {
+        let shopping_list: Model = Entity::find_by_id(id)
+            .one(&self.database_connection)
+            .await
+            .map_err(|err| UpdateError::Unexpected {
+                id,
+                error: err.into(),
+            })?
+            .ok_or(UpdateError::NotFound { id })?;
+        let mut shopping_list: ActiveModel = shopping_list.into();
+        shopping_list.name = Set(request.name);
+        shopping_list.updated_at = Set(Utc::now().naive_utc());
+
+        Ok(Entity::update(shopping_list)
+            .filter(Column::Id.eq(id))
+            .exec(&self.database_connection)
+            .await
+            .map_err(|err| {
+                if let DbErr::RecordNotUpdated = err {
+                    UpdateError::NotFound { id }
+                } else {
+                    UpdateError::Unexpected {
+                        id,
+                        error: err.into(),
+                    }
+                }
+            })?
+            .into())
+    }
+    async fn delete_shopping_list(&self, id: Uuid) -> Result<(), DeleteError> {
+        if Entity::delete_by_id(id)
+            .exec(&self.database_connection)
+            .await
+            .map_err(|err| DeleteError::Unexpected {
+                id,
+                error: err.into(),
+            })?
+            .rows_affected
+            == 0
+        {
+            Err(DeleteError::NotFound { id })
+        } else {
+            Ok(())
+        }
+    }
+    async fn create_shopping_list_items(
+        &self,
+        request: Vec<CreateItemDto>,
+    ) -> Result<Vec<ShoppingListItemDto>, CreateError> {
+        let txn = self
+            .database_connection
+            .begin()
+            .await
+            .map_err(|err| CreateError::Unexpected { error: err.into() })?;
+        let mut items = Vec::new();
+        for item in request {
+            let model: ItemModel = item.into();
+            let id = model.id;
+            let active_model: ItemActiveModel = model.into();
+            items.push(
+                active_model
+                    .insert(&txn)
+                    .await
+                    .map_err(|err| {
+                        if error_code(&err) == Some(UNIQUE_VIOLATION_CODE.to_owned()) {
+                            CreateError::AlreadyExist { id }
+                        } else {
+                            CreateError::Unexpected { error: err.into() }
+                        }
+                    })?
+                    .into(),
+            );
+        }
+        txn.commit()
+            .await
+            .map_err(|err| CreateError::Unexpected { error: err.into() })?;
+        Ok(items)
+    }
+    async fn add_shopping_list_items(
+        &self,
+        shopping_list_id: Uuid,
+        new_items: Vec<CreateItemDto>,
+        updated_items: Vec<(Uuid, UpdateItemDto)>,
+    ) -> Result<Vec<ShoppingListItemDto>, UpdateError> {
+        let unexpected = |err: DbErr| UpdateError::Unexpected {
+            id: shopping_list_id,
+            error: err.into(),
+        };
+        let txn = self.database_connection.begin().await.map_err(unexpected)?;
+        let mut items = Vec::new();
+        for item in new_items {
+            let model: ItemModel = item.into();
+            let id = model.id;
+            let active_model: ItemActiveModel = model.into();
+            let item = active_model.insert(&txn).await.map_err(|err| {
+                if error_code(&err) == Some(UNIQUE_VIOLATION_CODE.to_owned()) {
+                    UpdateError::Conflict {
+                        id: shopping_list_id,
+                        reason: format!("item {id} already exists"),
+                    }
+                } else {
+                    unexpected(err)
+                }
+            })?;
+            items.push(item.into());
+        }
+        for (id, request) in updated_items {
+            let item = update_item(&txn, id, Some(shopping_list_id), request).await?;
+            items.push(item.into());
+        }
+        txn.commit().await.map_err(unexpected)?;
+        Ok(items)
+    }
+    async fn get_shopping_list_item(&self, id: Uuid) -> Result<ShoppingListItemDto, GetError> {
+        Ok(ItemEntity::find_by_id(id)
+            .one(&self.database_connection)
+            .await
+            .map_err(|err| GetError::Unexpected {
+                id,
+                error: err.into(),
+            })?
+            .ok_or(GetError::NotFound { id })?
+            .into())
+    }
+    async fn list_shopping_list_items(
+        &self,
+        shopping_list_id: Uuid,
+    ) -> Result<ShoppingListItemsListDto, ListError> {
+        Ok(ShoppingListItemsListDto {
+            items: ItemEntity::find()
+                .filter(ItemColumn::ShoppingListId.eq(shopping_list_id))
+                .join(
+                    JoinType::InnerJoin,
+                    db_entities::shopping_list_items::Relation::Ingredients.def(),
+                )
+                .column_as(db_entities::ingredients::Column::Name, "ingredient_name")
+                .order_by_asc(ItemColumn::Checked)
+                .order_by_asc(db_entities::ingredients::Column::Name)
+                .into_model::<ShoppingListItemJoinDto>()
+                .all(&self.database_connection)
+                .await
+                .map_err(|err| ListError::Unexpected { error: err.into() })?,
+        })
+    }
+    async fn update_shopping_list_item(
+        &self,
+        id: Uuid,
+        request: UpdateItemDto,
+    ) -> Result<ShoppingListItemDto, UpdateError> {
+        Ok(update_item(&self.database_connection, id, None, request)
+            .await?
+            .into())
+    }
+    async fn delete_shopping_list_item(&self, id: Uuid) -> Result<(), DeleteError> {
+        if ItemEntity::delete_by_id(id)
+            .exec(&self.database_connection)
+            .await
+            .map_err(|err| DeleteError::Unexpected {
+                id,
+                error: err.into(),
+            })?
+            .rows_affected
+            == 0
+        {
+            Err(DeleteError::NotFound { id })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Items are only updated on the shopping list, if one is given
+async fn update_item<C: ConnectionTrait>(
+    db: &C,
+    id: Uuid,
+    shopping_list_id: Option<Uuid>,
+    request: UpdateItemDto,
+) -> Result<ItemModel, UpdateError> {
+    let mut item = ItemEntity::find_by_id(id);
+    if let Some(shopping_list_id) = shopping_list_id {
+        item = item.filter(ItemColumn::ShoppingListId.eq(shopping_list_id));
+    }
+    let item: ItemModel = item
+        .one(db)
+        .await
+        .map_err(|err| UpdateError::Unexpected {
+            id,
+            error: err.into(),
+        })?
+        .ok_or(UpdateError::NotFound { id })?;
+    let mut item: ItemActiveModel = item.into();
+    item.amount = Set(request.amount);
+    item.unit = Set(request.unit);
+    item.checked = Set(request.checked);
+    item.updated_at = Set(Utc::now().naive_utc());
+
+    ItemEntity::update(item)
+        .filter(ItemColumn::Id.eq(id))
+        .exec(db)
+        .await
+        .map_err(|err| {
+            if let DbErr::RecordNotUpdated = err {
+                UpdateError::NotFound { id }
+            } else {
+                UpdateError::Unexpected {
+                    id,
+                    error: err.into(),
+                }
+            }
+        })
+}
+
+fn list_entity(list_params: &ListParamsDto) -> Select<Entity> {
+    let mut entity = Entity::find();
+    if let Some(value) = list_params.user_id {
+        entity = entity.filter(Column::UserId.eq(value));
+    }
+    entity
+}
@@ -0,0 +1,59 @@
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use db_entities::user_identities::Model;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CreateDto {
+    pub user_id: Uuid,
+    pub issuer: String,
+    pub subject: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct ListParamsDto {
+    pub user_id: Option<Uuid>,
+    pub issuer: Option<String>,
+    pub subject: Option<String>,
+    pub limit: u64,
+    pub offset: u64,
+}
+
+#[derive(Serialize, Debug, Clone, Eq, PartialEq)]
+pub struct UserIdentityDto {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub issuer: String,
+    pub subject: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct UserIdentitiesListDto {
+    pub items: Vec<UserIdentityDto>,
+}
+
+impl From<CreateDto> for Model {
+    fn from(value: CreateDto) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id: value.user_id,
+            issuer: value.issuer,
+            subject: value.subject,
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+}
+
+impl From<Model> for UserIdentityDto {
+    fn from(value: Model) -> Self {
+        Self {
+            id: value.id,
+            user_id: value.user_id,
+            issuer: value.issuer,
+            subject: value.subject,
+            created_at: value.created_at,
+        }
+    }
+}
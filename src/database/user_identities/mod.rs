@@ -0,0 +1,81 @@
+pub mod dto;
+
+use async_trait::async_trait;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Select,
+};
+
+use self::dto::{CreateDto, ListParamsDto, UserIdentitiesListDto, UserIdentityDto};
+use crate::database::errors::{error_code, UNIQUE_VIOLATION_CODE};
+use crate::database::{
+    errors::{CreateError, ListError},
+    DBClient,
+};
+use db_entities::user_identities::{ActiveModel, Column, Entity, Model};
+
+#[async_trait]
+pub trait DatabaseCRUD {
+    /// Links an identity of an external provider to the user
+    async fn create_user_identity(
+        &self,
+        request: CreateDto,
+    ) -> Result<UserIdentityDto, CreateError>;
+    async fn list_user_identities(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<UserIdentitiesListDto, ListError>;
+}
+
+#[async_trait]
+impl DatabaseCRUD for DBClient {
+    async fn create_user_identity(
+        &self,
+        request: CreateDto,
+    ) -> Result<UserIdentityDto, CreateError> {
+        let model: Model = request.into();
+        let id = model.id;
+        let active_model: ActiveModel = model.into();
+        Ok(active_model
+            .insert(&self.database_connection)
+            .await
+            .map_err(|err| {
+                if error_code(&err) == Some(UNIQUE_VIOLATION_CODE.to_owned()) {
+                    CreateError::AlreadyExist { id }
+                } else {
+                    CreateError::Unexpected { error: err.into() }
+                }
+            })?
+            .into())
+    }
+    async fn list_user_identities(
+        &self,
+        list_params: &ListParamsDto,
+    ) -> Result<UserIdentitiesListDto, ListError> {
+        Ok(UserIdentitiesListDto {
+            items: list_entity(list_params)
+                .limit(list_params.limit)
+                .offset(list_params.offset)
+                .order_by_asc(Column::CreatedAt)
+                .all(&self.database_connection)
+                .await
+                .map_err(|err| ListError::Unexpected { error: err.into() })?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        })
+    }
+}
+
+fn list_entity(list_params: &ListParamsDto) -> Select<Entity> {
+    let mut entity = Entity::find();
+    if let Some(value) = list_params.user_id {
+        entity = entity.filter(Column::UserId.eq(value));
+    }
+    if let Some(value) = &list_params.issuer {
+        entity = entity.filter(Column::Issuer.eq(value));
+    }
+    if let Some(value) = &list_params.subject {
+        entity = entity.filter(Column::Subject.eq(value));
+    }
+    entity
+}
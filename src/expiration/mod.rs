@@ -0,0 +1,233 @@
+pub mod notifier;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use chrono::{Days, NaiveDate, NaiveTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use self::notifier::Notifier;
+use crate::database::errors::ListError;
+use crate::database::pantry_items::dto::PantryItemJoinDto;
+use crate::database::DBTrait;
+
+pub const DEFAULT_WINDOW_DAYS: u64 = 3;
+
+/// Pantry items of one user expiring on or before `until`, soonest first
+#[derive(Serialize, Debug)]
+pub struct ExpirationDigest {
+    pub user_id: Uuid,
+    pub until: NaiveDate,
+    pub items: Vec<PantryItemJoinDto>,
+}
+
+/// Last day included in a digest computed on `today`
+pub fn window_end(today: NaiveDate, window_days: u64) -> NaiveDate {
+    today
+        .checked_add_days(Days::new(window_days))
+        .unwrap_or(NaiveDate::MAX)
+}
+
+/// Expiration date of an item frozen on `today` that keeps `shelf_life_days` in the freezer.
+/// Freezing never makes an item expire sooner.
+pub fn frozen_expiration(
+    expiration_date: Option<NaiveDate>,
+    today: NaiveDate,
+    shelf_life_days: u64,
+) -> NaiveDate {
+    let frozen = window_end(today, shelf_life_days);
+    expiration_date.map_or(frozen, |date| date.max(frozen))
+}
+
+/// Computes the digest of a single user
+pub async fn user_digest(
+    db_client: &(dyn DBTrait + Send + Sync),
+    user_id: Uuid,
+    until: NaiveDate,
+) -> Result<ExpirationDigest, ListError> {
+    let items = db_client
+        .list_expiring_pantry_items(Some(user_id), until)
+        .await?
+        .items;
+    Ok(ExpirationDigest {
+        user_id,
+        until,
+        items,
+    })
+}
+
+/// Computes the digests of every user with expiring pantry items
+pub async fn digests(
+    db_client: &(dyn DBTrait + Send + Sync),
+    until: NaiveDate,
+) -> Result<Vec<ExpirationDigest>, ListError> {
+    let items = db_client
+        .list_expiring_pantry_items(None, until)
+        .await?
+        .items;
+    Ok(group_by_user(items, until))
+}
+
+/// One digest per user, keeping the order of the items
+fn group_by_user(items: Vec<PantryItemJoinDto>, until: NaiveDate) -> Vec<ExpirationDigest> {
+    let mut by_user: BTreeMap<Uuid, Vec<PantryItemJoinDto>> = BTreeMap::new();
+    for item in items {
+        by_user.entry(item.user_id).or_default().push(item);
+    }
+    by_user
+        .into_iter()
+        .map(|(user_id, items)| ExpirationDigest {
+            user_id,
+            until,
+            items,
+        })
+        .collect()
+}
+
+/// Sends the expiration digests every day at `at` (UTC)
+pub struct DigestScheduler {
+    db_client: Arc<dyn DBTrait + Send + Sync>,
+    notifier: Box<dyn Notifier + Send + Sync>,
+    window_days: u64,
+    at: NaiveTime,
+}
+
+impl DigestScheduler {
+    pub fn new(
+        db_client: Arc<dyn DBTrait + Send + Sync>,
+        notifier: Box<dyn Notifier + Send + Sync>,
+        window_days: u64,
+        at: NaiveTime,
+    ) -> Self {
+        Self {
+            db_client,
+            notifier,
+            window_days,
+            at,
+        }
+    }
+
+    pub async fn run(self) {
+        loop {
+            tokio::time::sleep(self.until_next_run()).await;
+            self.send_digests().await;
+        }
+    }
+
+    async fn send_digests(&self) {
+        let until = window_end(Utc::now().date_naive(), self.window_days);
+        let digests = match digests(self.db_client.as_ref(), until).await {
+            Ok(digests) => digests,
+            Err(err) => {
+                log::error!("Could not compute expiration digests: {err}");
+                return;
+            }
+        };
+        log::info!("Sending {} expiration digests", digests.len());
+        for digest in &digests {
+            if let Err(err) = self.notifier.notify(digest).await {
+                log::error!("{err}");
+            }
+        }
+    }
+
+    fn until_next_run(&self) -> std::time::Duration {
+        let now = Utc::now().naive_utc();
+        let mut next = now.date().and_time(self.at);
+        if next <= now {
+            next += chrono::Duration::days(1);
+        }
+        (next - now).to_std().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    fn item(user_id: Uuid, name: &str, expiration_date: NaiveDate) -> PantryItemJoinDto {
+        let now = Utc::now().naive_utc();
+        PantryItemJoinDto {
+            id: Uuid::new_v4(),
+            ingredient_id: Uuid::new_v4(),
+            ingredient_name: name.to_owned(),
+            purchase_date: None,
+            expiration_date: Some(expiration_date),
+            quantity: Some(1),
+            weight_grams: None,
+            volume_milli_litres: None,
+            essential: false,
+            running_low: None,
+            user_id,
+            household_id: None,
+            location_id: None,
+            location_name: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn windows_end_days_after_today() {
+        assert_eq!(window_end(date(12, 1), 0), date(12, 1));
+        assert_eq!(window_end(date(12, 1), DEFAULT_WINDOW_DAYS), date(12, 4));
+        assert_eq!(
+            window_end(date(12, 30), 3),
+            NaiveDate::from_ymd_opt(2025, 1, 2).unwrap()
+        );
+        assert_eq!(window_end(date(12, 1), u64::MAX), NaiveDate::MAX);
+    }
+
+    #[test]
+    fn freezing_only_extends_expiration() {
+        let today = date(12, 1);
+        assert_eq!(
+            frozen_expiration(None, today, 90),
+            date(12, 1) + Days::new(90)
+        );
+        assert_eq!(
+            frozen_expiration(Some(date(12, 3)), today, 30),
+            date(12, 31)
+        );
+        assert_eq!(
+            frozen_expiration(Some(date(12, 31)), today, 10),
+            date(12, 31)
+        );
+    }
+
+    #[test]
+    fn digests_are_grouped_by_user() {
+        let alice = Uuid::from_u128(1);
+        let bob = Uuid::from_u128(2);
+        let items = vec![
+            item(bob, "Milk", date(12, 1)),
+            item(alice, "Eggs", date(12, 2)),
+            item(bob, "Yogurt", date(12, 3)),
+        ];
+        let digests = group_by_user(items, date(12, 4));
+        let summary: Vec<(Uuid, Vec<&str>)> = digests
+            .iter()
+            .map(|digest| {
+                assert_eq!(digest.until, date(12, 4));
+                (
+                    digest.user_id,
+                    digest
+                        .items
+                        .iter()
+                        .map(|item| item.ingredient_name.as_str())
+                        .collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![(alice, vec!["Eggs"]), (bob, vec!["Milk", "Yogurt"])]
+        );
+        assert!(group_by_user(Vec::new(), date(12, 4)).is_empty());
+    }
+}
@@ -0,0 +1,168 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use color_eyre::Report as AnyError;
+use reqwest::header::CONTENT_TYPE;
+use thiserror::Error;
+use url::Url;
+
+use super::ExpirationDigest;
+
+/// A webhook that does not answer in time must not hold up the other digests
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Error, Debug)]
+pub enum NotifyError {
+    #[error("Could not serialize digest: {error}")]
+    Serialize { error: AnyError },
+    #[error("Could not deliver digest to {url}: {error}")]
+    Delivery { url: String, error: AnyError },
+}
+
+/// Delivers expiration digests to users
+#[async_trait]
+pub trait Notifier {
+    async fn notify(&self, digest: &ExpirationDigest) -> Result<(), NotifyError>;
+}
+
+/// Writes digests to the log, for running without outside services
+pub struct LogNotifier {}
+
+#[async_trait]
+impl Notifier for LogNotifier {
+    async fn notify(&self, digest: &ExpirationDigest) -> Result<(), NotifyError> {
+        log::info!(
+            "User {:?} has {} pantry items expiring by {}",
+            digest.user_id,
+            digest.items.len(),
+            digest.until
+        );
+        for item in &digest.items {
+            log::info!(
+                "  {} expires {}",
+                item.ingredient_name,
+                item.expiration_date
+                    .map_or_else(String::new, |date| date.to_string())
+            );
+        }
+        Ok(())
+    }
+}
+
+/// POSTs each digest as JSON to a webhook
+pub struct WebhookNotifier {
+    url: Url,
+    client: reqwest::Client,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: Url) -> Result<Self, reqwest::Error> {
+        Self::with_timeout(url, TIMEOUT)
+    }
+
+    fn with_timeout(url: Url, timeout: Duration) -> Result<Self, reqwest::Error> {
+        Ok(Self {
+            url,
+            client: reqwest::Client::builder()
+                .timeout(timeout)
+                .connect_timeout(timeout)
+                .build()?,
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    async fn notify(&self, digest: &ExpirationDigest) -> Result<(), NotifyError> {
+        let body = serde_json::to_string(digest)
+            .map_err(|err| NotifyError::Serialize { error: err.into() })?;
+        self.client
+            .post(self.url.clone())
+            .header(CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|err| NotifyError::Delivery {
+                url: self.url.to_string(),
+                error: err.into(),
+            })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use chrono::NaiveDate;
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn digest() -> ExpirationDigest {
+        ExpirationDigest {
+            user_id: Uuid::nil(),
+            until: NaiveDate::from_ymd_opt(2024, 12, 4).unwrap(),
+            items: Vec::new(),
+        }
+    }
+
+    /// Webhook that answers with `status` after `delay`, passing on the bodies it receives
+    async fn mock_webhook(status: StatusCode, delay: Duration) -> (Url, mpsc::Receiver<Value>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("http://{}/digest", listener.local_addr().unwrap())).unwrap();
+        let (sender, receiver) = mpsc::channel(1);
+        let router = Router::new().route(
+            "/digest",
+            post(move |Json(body): Json<Value>| async move {
+                sender.send(body).await.unwrap();
+                tokio::time::sleep(delay).await;
+                status
+            }),
+        );
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        (url, receiver)
+    }
+
+    #[tokio::test]
+    async fn logs_digests() {
+        assert!(LogNotifier {}.notify(&digest()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn posts_digests_as_json() {
+        let (url, mut received) = mock_webhook(StatusCode::NO_CONTENT, Duration::ZERO).await;
+        WebhookNotifier::new(url)
+            .unwrap()
+            .notify(&digest())
+            .await
+            .unwrap();
+        assert_eq!(
+            received.recv().await.unwrap(),
+            json!({ "user_id": Uuid::nil(), "until": "2024-12-04", "items": [] })
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_failed_deliveries() {
+        let (url, _received) =
+            mock_webhook(StatusCode::INTERNAL_SERVER_ERROR, Duration::ZERO).await;
+        let result = WebhookNotifier::new(url).unwrap().notify(&digest()).await;
+        assert!(matches!(result, Err(NotifyError::Delivery { .. })));
+    }
+
+    #[tokio::test]
+    async fn gives_up_on_hanging_webhooks() {
+        let (url, _received) = mock_webhook(StatusCode::OK, Duration::from_mins(1)).await;
+        let notifier = WebhookNotifier::with_timeout(url, Duration::from_millis(200)).unwrap();
+        let result = tokio::time::timeout(Duration::from_secs(5), notifier.notify(&digest()))
+            .await
+            .expect("notify should time out on its own");
+        assert!(matches!(result, Err(NotifyError::Delivery { .. })));
+    }
+}
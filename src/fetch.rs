@@ -0,0 +1,279 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::redirect::{Attempt, Policy};
+use reqwest::{Client, StatusCode};
+use thiserror::Error;
+use url::{Host, Url};
+
+const USER_AGENT: &str = concat!(
+    env!("CARGO_PKG_NAME"),
+    "/",
+    env!("CARGO_PKG_VERSION"),
+    " (recipe link parser)"
+);
+
+#[derive(Error, Debug)]
+pub enum FetchError {
+    #[error("Only http and https links can be fetched")]
+    Scheme,
+    #[error("{host} does not resolve to a public address")]
+    Blocked { host: String },
+    #[error("Response is larger than {limit} bytes")]
+    TooLarge { limit: usize },
+    #[error("Server responded with {status}")]
+    Status { status: StatusCode },
+    #[error("{error}")]
+    Request { error: reqwest::Error },
+}
+
+impl From<reqwest::Error> for FetchError {
+    fn from(error: reqwest::Error) -> Self {
+        FetchError::Request { error }
+    }
+}
+
+/// Limits applied to every outbound request made for user supplied links
+#[derive(Clone, Copy, Debug)]
+pub struct FetchLimits {
+    pub timeout: Duration,
+    pub max_redirects: usize,
+    pub max_body_bytes: usize,
+}
+
+/// HTTP client for user supplied links. Requests only reach public addresses:
+/// hosts are checked after DNS resolution and again on every redirect.
+#[derive(Clone)]
+pub struct Fetcher {
+    client: Client,
+    max_body_bytes: usize,
+}
+
+impl Fetcher {
+    pub fn new(limits: FetchLimits) -> Result<Self, FetchError> {
+        let max_redirects = limits.max_redirects;
+        let client = Client::builder()
+            .user_agent(USER_AGENT)
+            .timeout(limits.timeout)
+            .connect_timeout(limits.timeout)
+            .dns_resolver(Arc::new(PublicResolver))
+            // Through a proxy only the proxy's address would be resolved and checked
+            .no_proxy()
+            .redirect(Policy::custom(move |attempt| {
+                redirect(attempt, max_redirects)
+            }))
+            .build()?;
+        Ok(Fetcher {
+            client,
+            max_body_bytes: limits.max_body_bytes,
+        })
+    }
+
+    /// GETs the link and returns the body as text
+    pub async fn get_text(&self, url: &Url) -> Result<String, FetchError> {
+        check_url(url)?;
+        let mut response = self.client.get(url.clone()).send().await?;
+        if !response.status().is_success() {
+            return Err(FetchError::Status {
+                status: response.status(),
+            });
+        }
+        let limit = self.max_body_bytes;
+        if response
+            .content_length()
+            .is_some_and(|length| length > limit as u64)
+        {
+            return Err(FetchError::TooLarge { limit });
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if body.len() + chunk.len() > limit {
+                return Err(FetchError::TooLarge { limit });
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+}
+
+fn redirect(attempt: Attempt, max_redirects: usize) -> reqwest::redirect::Action {
+    if attempt.previous().len() > max_redirects {
+        return attempt.error(format!("More than {max_redirects} redirects"));
+    }
+    match check_url(attempt.url()) {
+        Ok(()) => attempt.follow(),
+        Err(err) => attempt.error(err),
+    }
+}
+
+/// Rejects other schemes and IP literals outside the public ranges.
+/// Host names are checked by the resolver.
+fn check_url(url: &Url) -> Result<(), FetchError> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(FetchError::Scheme);
+    }
+    let ip = match url.host() {
+        Some(Host::Ipv4(ip)) => IpAddr::V4(ip),
+        Some(Host::Ipv6(ip)) => IpAddr::V6(ip),
+        Some(Host::Domain(_)) => return Ok(()),
+        None => {
+            return Err(FetchError::Blocked {
+                host: String::new(),
+            })
+        }
+    };
+    if is_public(ip) {
+        Ok(())
+    } else {
+        Err(FetchError::Blocked {
+            host: ip.to_string(),
+        })
+    }
+}
+
+/// Resolves host names and drops every address that is not public,
+/// so the connection is made to the address that was checked
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_owned();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| is_public(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(FetchError::Blocked { host }.into());
+            }
+            let addrs: Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match embedded_v4(ip) {
+            Some(ip) => is_public_v4(ip),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+/// IPv4 address reached through an IPv4-mapped or NAT64 address
+fn embedded_v4(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    if let Some(ip) = ip.to_ipv4_mapped() {
+        return Some(ip);
+    }
+    match ip.segments() {
+        [0x64, 0xff9b, 0, 0, 0, 0, high, low] => {
+            let [a, b] = high.to_be_bytes();
+            let [c, d] = low.to_be_bytes();
+            Some(Ipv4Addr::new(a, b, c, d))
+        }
+        _ => None,
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        // "This network", carrier-grade NAT, IETF protocol assignments,
+        // benchmarking and reserved ranges
+        || a == 0
+        || (a == 100 && (64..128).contains(&b))
+        || (a == 192 && b == 0 && c == 0)
+        || (a == 198 && (18..20).contains(&b))
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // Unique local, link-local and documentation ranges
+        || (first & 0xfe00) == 0xfc00
+        || (first & 0xffc0) == 0xfe80
+        || (first == 0x2001 && ip.segments()[1] == 0x0db8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_public_addresses_are_allowed() {
+        let cases = [
+            ("93.184.216.34", true),
+            ("2606:2800:220:1:248:1893:25c8:1946", true),
+            ("127.0.0.1", false),
+            ("127.255.255.254", false),
+            ("10.0.0.1", false),
+            ("10.255.255.255", false),
+            ("169.254.169.254", false),
+            ("100.64.0.1", false),
+            ("100.127.255.255", false),
+            ("100.128.0.1", true),
+            ("::1", false),
+            ("::ffff:127.0.0.1", false),
+            ("::ffff:10.0.0.1", false),
+            ("::ffff:93.184.216.34", true),
+            ("64:ff9b::a00:1", false),
+            ("64:ff9b::5db8:d822", true),
+            ("fc00::1", false),
+            ("fd12:3456:789a::1", false),
+            ("fe80::1", false),
+            ("febf::1", false),
+        ];
+        for (ip, public) in cases {
+            assert_eq!(is_public(ip.parse().unwrap()), public, "{ip}");
+        }
+    }
+
+    #[test]
+    fn embedded_v4_addresses_are_extracted() {
+        let cases = [
+            ("::ffff:127.0.0.1", Some(Ipv4Addr::LOCALHOST)),
+            ("64:ff9b::a00:1", Some(Ipv4Addr::new(10, 0, 0, 1))),
+            ("64:ff9b::7f00:1", Some(Ipv4Addr::LOCALHOST)),
+            ("64:ff9b:1::a00:1", None),
+            ("fe80::1", None),
+            ("2606:2800:220:1:248:1893:25c8:1946", None),
+        ];
+        for (ip, embedded) in cases {
+            assert_eq!(embedded_v4(ip.parse().unwrap()), embedded, "{ip}");
+        }
+    }
+
+    #[test]
+    fn urls_are_checked() {
+        let cases = [
+            ("https://example.com/recipe", true),
+            ("http://93.184.216.34/recipe", true),
+            ("ftp://example.com/recipe", false),
+            ("file:///etc/passwd", false),
+            ("http://127.0.0.1:8080/health", false),
+            ("http://10.0.0.1/", false),
+            ("http://169.254.169.254/latest/meta-data/", false),
+            ("http://100.64.0.1/", false),
+            ("http://[::ffff:127.0.0.1]/", false),
+            ("http://[64:ff9b::a00:1]/", false),
+            ("http://[fc00::1]/", false),
+            ("http://[fe80::1]/", false),
+        ];
+        for (url, allowed) in cases {
+            assert_eq!(check_url(&url.parse().unwrap()).is_ok(), allowed, "{url}");
+        }
+    }
+}
@@ -0,0 +1,560 @@
+mod paprika;
+mod schema;
+mod tandoor;
+
+use std::io::{self, Cursor, Read};
+
+use color_eyre::eyre::{eyre, WrapErr};
+use color_eyre::Result as AnyResult;
+use flate2::read::GzDecoder;
+use serde_json::Value;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::database::recipes::dto::CreateDto as RecipeCreateDto;
+use crate::database::DBTrait;
+use crate::matching::IngredientMatcher;
+use crate::server::routes::parse_ingredients::parse_ingredients;
+use crate::settings::ImportArgs;
+
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+/// Bytes the entries of an archive may decompress to in total, nested archives and
+/// gzipped recipes included
+const MAX_DECOMPRESSED_BYTES: u64 = 512 * 1024 * 1024;
+
+#[derive(Error, Debug)]
+pub enum ImportError {
+    #[error("Could not read archive: {error}")]
+    Archive { error: String },
+    #[error("Archive decompresses to more than {limit} bytes")]
+    TooLarge { limit: u64 },
+    #[error("Could not parse JSON: {error}")]
+    Json { error: String },
+    #[error("No recipes found in file")]
+    NoRecipes,
+}
+
+/// Recipe read from an export file, before it is stored
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ImportedRecipe {
+    pub name: String,
+    pub prep_time_mins: Option<i32>,
+    pub total_time_mins: Option<i32>,
+    pub link: Option<String>,
+    pub instructions: Option<String>,
+    pub image: Option<String>,
+    pub rating: Option<i32>,
+    pub notes: Option<String>,
+    /// Ingredient lines such as "2 cups rice", parsed on import
+    pub ingredients: Vec<String>,
+}
+
+/// Recipe of an export file, or why it could not be read.
+/// `source` tells which entry of the file it came from.
+#[derive(Debug)]
+pub struct ImportEntry {
+    pub source: String,
+    pub recipe: Result<ImportedRecipe, String>,
+}
+
+#[derive(Debug)]
+pub struct ImportResult {
+    pub source: String,
+    pub name: Option<String>,
+    pub recipe_id: Option<Uuid>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct ImportReport {
+    pub imported: usize,
+    pub failed: usize,
+    pub items: Vec<ImportResult>,
+}
+
+/// Reads recipes from a file of JSON-LD recipes, a Mealie or Tandoor JSON export,
+/// or a Paprika or Tandoor zip archive. Blocks while archives are decompressed.
+pub fn read_recipes(file: &[u8]) -> Result<Vec<ImportEntry>, ImportError> {
+    let entries = if file.starts_with(ZIP_MAGIC) {
+        let mut remaining = MAX_DECOMPRESSED_BYTES;
+        read_archive(file, "", &mut remaining, false)?
+    } else {
+        let json: Value = serde_json::from_slice(file).map_err(|err| ImportError::Json {
+            error: err.to_string(),
+        })?;
+        read_json(&json, "")
+    };
+    if entries.is_empty() {
+        return Err(ImportError::NoRecipes);
+    }
+    Ok(entries)
+}
+
+/// Reads the recipes of the archive, taking the size of every entry from `remaining`.
+/// Archives in a `nested` archive are not read.
+fn read_archive(
+    file: &[u8],
+    prefix: &str,
+    remaining: &mut u64,
+    nested: bool,
+) -> Result<Vec<ImportEntry>, ImportError> {
+    let mut archive =
+        zip::ZipArchive::new(Cursor::new(file)).map_err(|err| ImportError::Archive {
+            error: err.to_string(),
+        })?;
+    let mut entries = Vec::new();
+    for index in 0..archive.len() {
+        let mut entry = archive
+            .by_index(index)
+            .map_err(|err| ImportError::Archive {
+                error: err.to_string(),
+            })?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry
+            .name()
+            .map_or_else(|_| format!("#{}", index + 1), std::borrow::Cow::into_owned);
+        let source = format!("{prefix}{name}");
+        let content = match read_limited(&mut entry, remaining)? {
+            Ok(content) => content,
+            Err(err) => {
+                entries.push(ImportEntry {
+                    source,
+                    recipe: Err(err.to_string()),
+                });
+                continue;
+            }
+        };
+        let extension = source.rsplit('.').next().unwrap_or_default();
+        match extension {
+            "paprikarecipe" => entries.push(ImportEntry {
+                recipe: read_limited(GzDecoder::new(content.as_slice()), remaining)?
+                    .map_err(|err| err.to_string())
+                    .and_then(|json| paprika::read_recipe(&json)),
+                source,
+            }),
+            "zip" if nested => entries.push(ImportEntry {
+                source,
+                recipe: Err("Archives are only read one level deep".to_owned()),
+            }),
+            // Tandoor archives hold one archive per recipe
+            "zip" => entries.extend(read_archive(
+                &content,
+                &format!("{source}/"),
+                remaining,
+                true,
+            )?),
+            "json" => match serde_json::from_slice::<Value>(&content) {
+                Ok(json) => entries.extend(read_json(&json, &source)),
+                Err(err) => entries.push(ImportEntry {
+                    source,
+                    recipe: Err(err.to_string()),
+                }),
+            },
+            _ => log::debug!("Skipping {source} in import archive"),
+        }
+    }
+    Ok(entries)
+}
+
+/// Decompresses the reader, failing the whole import once the archive has expanded
+/// to more than its limit. The inner error is the entry's own.
+fn read_limited(
+    reader: impl Read,
+    remaining: &mut u64,
+) -> Result<io::Result<Vec<u8>>, ImportError> {
+    let mut content = Vec::new();
+    let read = reader
+        .take(remaining.saturating_add(1))
+        .read_to_end(&mut content);
+    *remaining = remaining
+        .checked_sub(content.len() as u64)
+        .ok_or(ImportError::TooLarge {
+            limit: MAX_DECOMPRESSED_BYTES,
+        })?;
+    Ok(read.map(|_| content))
+}
+
+/// Reads a recipe or a list of recipes, detecting the format from their fields
+fn read_json(json: &Value, source: &str) -> Vec<ImportEntry> {
+    let recipes = match json {
+        Value::Array(recipes) => recipes.iter().collect(),
+        Value::Object(object) => match object.get("@graph").or(object.get("recipes")) {
+            Some(Value::Array(recipes)) => recipes.iter().collect(),
+            _ => vec![json],
+        },
+        _ => Vec::new(),
+    };
+    let single = recipes.len() == 1;
+    recipes
+        .into_iter()
+        .filter(|recipe| !schema::is_other_type(recipe))
+        .enumerate()
+        .map(|(index, recipe)| {
+            let source = match (source, single) {
+                ("", _) => format!("#{}", index + 1),
+                (source, true) => source.to_owned(),
+                (source, false) => format!("{source}#{}", index + 1),
+            };
+            let recipe = if tandoor::is_tandoor(recipe) {
+                tandoor::read_recipe(recipe)
+            } else {
+                schema::read_recipe(recipe)
+            };
+            ImportEntry { source, recipe }
+        })
+        .collect()
+}
+
+/// Stores the recipes for the user, creating ingredients that do not exist yet.
+/// A recipe that cannot be stored does not stop the others from being imported.
+pub async fn import_recipes(
+    client: &(dyn DBTrait + Send + Sync),
+    user_id: Uuid,
+    household_id: Option<Uuid>,
+    entries: Vec<ImportEntry>,
+) -> ImportReport {
+    let matcher = IngredientMatcher::load(client).await.unwrap_or_else(|err| {
+        log::warn!("Importing without ingredient matching: {err}");
+        IngredientMatcher::new(Vec::new(), Vec::new())
+    });
+    let mut items = Vec::new();
+    for entry in entries {
+        let result = match entry.recipe {
+            Ok(recipe) => {
+                let name = recipe.name.clone();
+                match import_recipe(client, &matcher, user_id, household_id, recipe).await {
+                    Ok(recipe_id) => ImportResult {
+                        source: entry.source,
+                        name: Some(name),
+                        recipe_id: Some(recipe_id),
+                        error: None,
+                    },
+                    Err(error) => ImportResult {
+                        source: entry.source,
+                        name: Some(name),
+                        recipe_id: None,
+                        error: Some(error),
+                    },
+                }
+            }
+            Err(error) => ImportResult {
+                source: entry.source,
+                name: None,
+                recipe_id: None,
+                error: Some(error),
+            },
+        };
+        items.push(result);
+    }
+    let imported = items.iter().filter(|item| item.error.is_none()).count();
+    ImportReport {
+        imported,
+        failed: items.len() - imported,
+        items,
+    }
+}
+
+async fn import_recipe(
+    client: &(dyn DBTrait + Send + Sync),
+    matcher: &IngredientMatcher,
+    user_id: Uuid,
+    household_id: Option<Uuid>,
+    recipe: ImportedRecipe,
+) -> Result<Uuid, String> {
+    let mut ingredients =
+        parse_ingredients(recipe.ingredients.iter().map(String::as_str).collect());
+    ingredients.retain(|ingredient| !ingredient.name.trim().is_empty());
+    matcher.annotate(&mut ingredients);
+    let ingredients = ingredients.into_iter().map(Into::into).collect();
+    let created = client
+        .create_recipe_with_ingredients(
+            RecipeCreateDto {
+                user_id,
+                household_id,
+                name: recipe.name,
+                prep_time_mins: recipe.prep_time_mins,
+                total_time_mins: recipe.total_time_mins,
+                link: recipe.link,
+                instructions: recipe.instructions,
+                image: recipe.image,
+                last_cooked: None,
+                rating: recipe.rating,
+                notes: recipe.notes,
+            },
+            ingredients,
+        )
+        .await
+        .map_err(|err| err.to_string())?;
+    Ok(created.recipe.id)
+}
+
+/// Imports the file given on the command line and logs the report
+pub async fn run(client: &(dyn DBTrait + Send + Sync), args: &ImportArgs) -> AnyResult<()> {
+    let user = client
+        .list_users(&crate::database::users::dto::ListParamsDto {
+            name: Some(args.user.clone()),
+            limit: 1,
+            ..Default::default()
+        })
+        .await?
+        .items
+        .into_iter()
+        .next()
+        .ok_or_else(|| eyre!("User {:?} not found", args.user))?;
+    let file = std::fs::read(&args.file)
+        .wrap_err_with(|| format!("Could not read {}", args.file.display()))?;
+    let report = import_recipes(client, user.id, args.household_id, read_recipes(&file)?).await;
+    for item in &report.items {
+        match (&item.recipe_id, &item.error) {
+            (Some(recipe_id), _) => log::info!(
+                "{}: imported {:?} as {recipe_id}",
+                item.source,
+                item.name.as_deref().unwrap_or_default()
+            ),
+            (None, error) => log::error!(
+                "{}: could not import {:?}: {}",
+                item.source,
+                item.name.as_deref().unwrap_or_default(),
+                error.as_deref().unwrap_or_default()
+            ),
+        }
+    }
+    log::info!(
+        "Imported {} recipes, {} failed",
+        report.imported,
+        report.failed
+    );
+    Ok(())
+}
+
+/// Reads durations written as text, such as "1 hr 30 mins" or "45 minutes".
+/// Durations too long to count in minutes are `None`.
+pub(crate) fn text_duration_mins(text: &str) -> Option<i32> {
+    let mut total: i32 = 0;
+    let mut number: Option<i32> = None;
+    let mut found = false;
+    for word in text
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|word| !word.is_empty())
+    {
+        let digits: String = word.chars().take_while(char::is_ascii_digit).collect();
+        let rest = &word[digits.len()..];
+        if !digits.is_empty() {
+            number = digits.parse().ok();
+        }
+        if rest.is_empty() {
+            continue;
+        }
+        let rest = rest.to_lowercase();
+        let factor = if rest.starts_with('h') {
+            60
+        } else if rest.starts_with('m') {
+            1
+        } else {
+            continue;
+        };
+        if let Some(value) = number.take() {
+            total = value
+                .checked_mul(factor)
+                .and_then(|mins| total.checked_add(mins))?;
+            found = true;
+        }
+    }
+    match (found, number) {
+        (true, _) => Some(total),
+        // A bare number is minutes
+        (false, Some(mins)) => Some(mins),
+        (false, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use zip::write::SimpleFileOptions;
+    use zip::{CompressionMethod, ZipWriter};
+
+    use super::*;
+
+    const MEALIE: &[u8] = include_bytes!("fixtures/mealie.json");
+    const TANDOOR: &[u8] = include_bytes!("fixtures/tandoor.json");
+    const PAPRIKA: &[u8] = include_bytes!("fixtures/paprika.json");
+
+    fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        for (name, content) in files {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(content).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn gzip(content: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn recipes(entries: Vec<ImportEntry>) -> Vec<(String, ImportedRecipe)> {
+        entries
+            .into_iter()
+            .map(|entry| (entry.source, entry.recipe.unwrap()))
+            .collect()
+    }
+
+    fn mealie_recipe() -> ImportedRecipe {
+        ImportedRecipe {
+            name: "Garlic Butter Shrimp".to_owned(),
+            prep_time_mins: Some(10),
+            total_time_mins: Some(65),
+            link: Some("https://example.com/garlic-butter-shrimp".to_owned()),
+            instructions: Some("1. Melt the butter.\n2. Cook the shrimp until pink.\n".to_owned()),
+            image: None,
+            rating: Some(4),
+            notes: Some("Tip: Serve with rice.\nKeeps for a day.".to_owned()),
+            ingredients: vec![
+                "1 pound shrimp, peeled".to_owned(),
+                "3 tablespoon butter".to_owned(),
+                "salt to taste".to_owned(),
+            ],
+        }
+    }
+
+    fn tandoor_recipe() -> ImportedRecipe {
+        ImportedRecipe {
+            name: "Pancakes".to_owned(),
+            prep_time_mins: Some(15),
+            total_time_mins: Some(25),
+            link: Some("https://example.com/pancakes".to_owned()),
+            instructions: Some(
+                "1. Whisk the dry ingredients.\n2. Add the milk and eggs. Fry in a hot pan.\n"
+                    .to_owned(),
+            ),
+            image: None,
+            rating: None,
+            notes: Some("Fluffy breakfast pancakes".to_owned()),
+            ingredients: vec![
+                "200 g flour".to_owned(),
+                "2.5 tsp baking powder".to_owned(),
+                "300 ml milk".to_owned(),
+                "2 eggs".to_owned(),
+                "salt".to_owned(),
+            ],
+        }
+    }
+
+    fn paprika_recipe() -> ImportedRecipe {
+        ImportedRecipe {
+            name: "Tomato Soup".to_owned(),
+            prep_time_mins: Some(15),
+            total_time_mins: Some(70),
+            link: None,
+            instructions: Some(
+                "1. Roast the tomatoes.\n2. Blend with the onion and stock.\n".to_owned(),
+            ),
+            image: Some("https://example.com/soup.jpg".to_owned()),
+            rating: None,
+            notes: None,
+            ingredients: vec![
+                "2 lb tomatoes".to_owned(),
+                "1 onion".to_owned(),
+                "2 cups vegetable stock".to_owned(),
+            ],
+        }
+    }
+
+    #[test]
+    fn reads_mealie_export() {
+        assert_eq!(
+            recipes(read_recipes(MEALIE).unwrap()),
+            vec![("#1".to_owned(), mealie_recipe())]
+        );
+    }
+
+    #[test]
+    fn reads_tandoor_export() {
+        assert_eq!(
+            recipes(read_recipes(TANDOOR).unwrap()),
+            vec![("#1".to_owned(), tandoor_recipe())]
+        );
+        // One archive per recipe, with the recipe and its image
+        let recipe = zip(&[("recipe.json", TANDOOR), ("image.jpg", b"\xff\xd8")]);
+        let archive = zip(&[("1.zip", &recipe)]);
+        assert_eq!(
+            recipes(read_recipes(&archive).unwrap()),
+            vec![("1.zip/recipe.json".to_owned(), tandoor_recipe())]
+        );
+    }
+
+    #[test]
+    fn reads_paprika_archive() {
+        let archive = zip(&[
+            ("Tomato Soup.paprikarecipe", &gzip(PAPRIKA)),
+            ("readme.txt", b"Exported from Paprika"),
+        ]);
+        assert_eq!(
+            recipes(read_recipes(&archive).unwrap()),
+            vec![("Tomato Soup.paprikarecipe".to_owned(), paprika_recipe())]
+        );
+    }
+
+    #[test]
+    fn reads_archives_one_level_deep() {
+        let inner = zip(&[("recipe.json", TANDOOR)]);
+        let middle = zip(&[("inner.zip", &inner)]);
+        let archive = zip(&[("middle.zip", &middle), ("recipe.json", MEALIE)]);
+        let entries = read_recipes(&archive).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].source, "middle.zip/inner.zip");
+        assert_eq!(
+            entries[0].recipe,
+            Err("Archives are only read one level deep".to_owned())
+        );
+        assert_eq!(entries[1].source, "recipe.json");
+        assert_eq!(entries[1].recipe, Ok(mealie_recipe()));
+    }
+
+    #[test]
+    fn stops_archives_that_expand_too_far() {
+        let zeros = vec![0; 1024 * 1024];
+        let archive = zip(&[("a.json", &zeros)]);
+        assert!(archive.len() < 10 * 1024);
+        let mut remaining = 1024;
+        assert!(matches!(
+            read_archive(&archive, "", &mut remaining, false),
+            Err(ImportError::TooLarge { .. })
+        ));
+        // Gzipped recipes count too
+        let archive = zip(&[("a.paprikarecipe", &gzip(&zeros))]);
+        let mut remaining = 64 * 1024;
+        assert!(matches!(
+            read_archive(&archive, "", &mut remaining, false),
+            Err(ImportError::TooLarge { .. })
+        ));
+        let mut remaining = 2 * 1024 * 1024;
+        assert!(read_archive(&archive, "", &mut remaining, false).is_ok());
+    }
+
+    #[test]
+    fn reads_text_durations() {
+        let cases = [
+            ("45", Some(45)),
+            ("45 minutes", Some(45)),
+            ("1 hr 30 mins", Some(90)),
+            ("2h", Some(120)),
+            ("1 hour, 5 min", Some(65)),
+            ("overnight", None),
+            ("99999999 hours", None),
+            ("35791394 hours 8 minutes", None),
+        ];
+        for (text, mins) in cases {
+            assert_eq!(text_duration_mins(text), mins, "{text:?}");
+        }
+    }
+}
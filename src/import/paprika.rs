@@ -0,0 +1,62 @@
+//! Paprika archives, a zip of gzipped JSON recipes
+use std::fmt::Write;
+
+use serde::Deserialize;
+
+use super::{text_duration_mins, ImportedRecipe};
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct PaprikaRecipe {
+    name: String,
+    ingredients: Option<String>,
+    directions: Option<String>,
+    notes: Option<String>,
+    prep_time: Option<String>,
+    total_time: Option<String>,
+    image_url: Option<String>,
+    source_url: Option<String>,
+    /// 0 when the recipe is not rated
+    rating: Option<i32>,
+}
+
+/// Reads a recipe that was already gunzipped, so the archive limits its size
+pub fn read_recipe(json: &[u8]) -> Result<ImportedRecipe, String> {
+    let recipe: PaprikaRecipe = serde_json::from_slice(json).map_err(|err| err.to_string())?;
+    if recipe.name.trim().is_empty() {
+        return Err("Recipe has no name".to_owned());
+    }
+    let non_empty = |value: Option<String>| value.filter(|value| !value.trim().is_empty());
+    Ok(ImportedRecipe {
+        name: recipe.name,
+        prep_time_mins: recipe.prep_time.as_deref().and_then(text_duration_mins),
+        total_time_mins: recipe.total_time.as_deref().and_then(text_duration_mins),
+        link: non_empty(recipe.source_url),
+        instructions: non_empty(recipe.directions).map(|directions| numbered(&directions)),
+        image: non_empty(recipe.image_url),
+        rating: recipe.rating.filter(|rating| (1..=5).contains(rating)),
+        notes: non_empty(recipe.notes),
+        ingredients: recipe
+            .ingredients
+            .unwrap_or_default()
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(ToOwned::to_owned)
+            .collect(),
+    })
+}
+
+/// Numbers the direction paragraphs like imported recipe instructions
+fn numbered(directions: &str) -> String {
+    let mut result = String::new();
+    for (number, step) in directions
+        .lines()
+        .map(str::trim)
+        .filter(|step| !step.is_empty())
+        .enumerate()
+    {
+        let _ = writeln!(result, "{}. {step}", number + 1);
+    }
+    result
+}
@@ -0,0 +1,127 @@
+//! schema.org `Recipe` JSON-LD, which Mealie exports with a few extra fields
+use serde_json::Value;
+
+use super::{text_duration_mins, ImportedRecipe};
+use crate::server::routes::parse_recipe_link::{
+    get_image, get_instructions, get_name, get_time_field,
+};
+
+/// Whether the object is a JSON-LD node of another type than `Recipe`
+pub fn is_other_type(json: &Value) -> bool {
+    match json.get("@type") {
+        Some(Value::String(kind)) => kind != "Recipe",
+        Some(Value::Array(kinds)) => !kinds.iter().any(|kind| kind == "Recipe"),
+        _ => false,
+    }
+}
+
+pub fn read_recipe(json: &Value) -> Result<ImportedRecipe, String> {
+    let name = get_name(json).ok_or("Recipe has no name")?;
+    Ok(ImportedRecipe {
+        name,
+        prep_time_mins: duration_mins(json, "prepTime"),
+        total_time_mins: duration_mins(json, "totalTime"),
+        link: ["url", "orgURL"]
+            .iter()
+            .find_map(|key| json.get(*key).and_then(Value::as_str))
+            .map(ToOwned::to_owned),
+        instructions: match json.get("recipeInstructions") {
+            Some(Value::String(instructions)) => Some(instructions.clone()),
+            _ => get_instructions(json),
+        },
+        image: get_image(json).map(Into::into),
+        rating: rating(json),
+        notes: notes(json),
+        ingredients: json
+            .get("recipeIngredient")
+            .and_then(Value::as_array)
+            .map(|ingredients| ingredients.iter().filter_map(ingredient_line).collect())
+            .unwrap_or_default(),
+    })
+}
+
+/// Durations are ISO 8601 in JSON-LD but may be free text in Mealie
+fn duration_mins(json: &Value, key: &str) -> Option<i32> {
+    get_time_field(json, key)
+        .and_then(|mins| i32::try_from(mins).ok())
+        .or_else(|| json.get(key)?.as_str().and_then(text_duration_mins))
+}
+
+fn rating(json: &Value) -> Option<i32> {
+    let rating = json
+        .get("aggregateRating")
+        .and_then(|rating| rating.get("ratingValue"))
+        .or(json.get("rating"))?;
+    let rating = match rating {
+        Value::Number(rating) => rating.as_f64()?,
+        Value::String(rating) => rating.parse().ok()?,
+        _ => return None,
+    };
+    #[allow(clippy::cast_possible_truncation)]
+    let rating = rating.round() as i32;
+    (1..=5).contains(&rating).then_some(rating)
+}
+
+/// Mealie keeps notes as a list of titled paragraphs
+fn notes(json: &Value) -> Option<String> {
+    match json.get("notes")? {
+        Value::String(notes) => Some(notes.clone()),
+        Value::Array(notes) => {
+            let notes: Vec<String> = notes
+                .iter()
+                .filter_map(|note| {
+                    let text = note.get("text")?.as_str()?;
+                    Some(match note.get("title").and_then(Value::as_str) {
+                        Some(title) if !title.is_empty() => format!("{title}: {text}"),
+                        _ => text.to_owned(),
+                    })
+                })
+                .collect();
+            (!notes.is_empty()).then(|| notes.join("\n"))
+        }
+        _ => None,
+    }
+}
+
+/// Ingredients are plain lines in JSON-LD and objects in Mealie
+fn ingredient_line(ingredient: &Value) -> Option<String> {
+    match ingredient {
+        Value::String(line) => Some(line.clone()),
+        Value::Object(ingredient) => {
+            if let Some(line) = ["originalText", "display"].iter().find_map(|key| {
+                ingredient
+                    .get(*key)
+                    .and_then(Value::as_str)
+                    .filter(|line| !line.trim().is_empty())
+            }) {
+                return Some(line.to_owned());
+            }
+            let quantity = ingredient
+                .get("quantity")
+                .and_then(Value::as_f64)
+                .filter(|quantity| *quantity > 0.0)
+                .map(|quantity| quantity.to_string());
+            let named = |key: &str| {
+                ingredient
+                    .get(key)
+                    .and_then(|value| value.get("name"))
+                    .and_then(Value::as_str)
+                    .map(ToOwned::to_owned)
+            };
+            let food = named("food").or_else(|| {
+                ingredient
+                    .get("note")
+                    .and_then(Value::as_str)
+                    .map(ToOwned::to_owned)
+            })?;
+            Some(
+                [quantity, named("unit"), Some(food)]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<String>>()
+                    .join(" "),
+            )
+        }
+        _ => None,
+    }
+}
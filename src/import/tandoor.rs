@@ -0,0 +1,106 @@
+//! Tandoor exports, one `recipe.json` per recipe
+use std::fmt::Write;
+
+use serde_json::Value;
+
+use super::ImportedRecipe;
+
+pub fn is_tandoor(json: &Value) -> bool {
+    json.get("steps").is_some_and(Value::is_array) && json.get("working_time").is_some()
+}
+
+pub fn read_recipe(json: &Value) -> Result<ImportedRecipe, String> {
+    let name = json
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or("Recipe has no name")?
+        .to_owned();
+    let mins = |key: &str| {
+        json.get(key)
+            .and_then(Value::as_i64)
+            .and_then(|mins| i32::try_from(mins).ok())
+            .unwrap_or_default()
+    };
+    let (working_time, waiting_time) = (mins("working_time"), mins("waiting_time"));
+    let steps = json
+        .get("steps")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    let mut instructions = String::new();
+    let mut ingredients = Vec::new();
+    for step in &steps {
+        if let Some(text) = step
+            .get("instruction")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+        {
+            let number = instructions.lines().count() + 1;
+            let _ = writeln!(instructions, "{number}. {}", text.replace('\n', " "));
+        }
+        for ingredient in step
+            .get("ingredients")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            if let Some(line) = ingredient_line(ingredient) {
+                ingredients.push(line);
+            }
+        }
+    }
+    Ok(ImportedRecipe {
+        name,
+        prep_time_mins: (working_time > 0).then_some(working_time),
+        total_time_mins: (working_time + waiting_time > 0).then_some(working_time + waiting_time),
+        link: json
+            .get("source_url")
+            .and_then(Value::as_str)
+            .filter(|link| !link.is_empty())
+            .map(ToOwned::to_owned),
+        instructions: (!instructions.is_empty()).then_some(instructions),
+        image: None,
+        rating: None,
+        notes: json
+            .get("description")
+            .and_then(Value::as_str)
+            .filter(|notes| !notes.is_empty())
+            .map(ToOwned::to_owned),
+        ingredients,
+    })
+}
+
+fn ingredient_line(ingredient: &Value) -> Option<String> {
+    if ingredient.get("is_header").and_then(Value::as_bool) == Some(true) {
+        return None;
+    }
+    let named = |key: &str| {
+        ingredient
+            .get(key)
+            .and_then(|value| value.get("name"))
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned)
+    };
+    let food = named("food")?;
+    let amount = if ingredient.get("no_amount").and_then(Value::as_bool) == Some(true) {
+        None
+    } else {
+        ingredient
+            .get("amount")
+            .and_then(|amount| match amount {
+                Value::Number(amount) => amount.as_f64(),
+                Value::String(amount) => amount.parse().ok(),
+                _ => None,
+            })
+            .filter(|amount| *amount > 0.0)
+            .map(|amount| amount.to_string())
+    };
+    Some(
+        [amount, named("unit"), Some(food)]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<String>>()
+            .join(" "),
+    )
+}
@@ -2,14 +2,23 @@
 #![warn(clippy::pedantic)]
 #![allow(clippy::module_name_repetitions)]
 mod database;
+mod expiration;
+mod fetch;
+mod import;
+mod matching;
+mod oidc;
+mod products;
 mod redis;
+mod scrapers;
 mod server;
 mod settings;
 mod test;
+mod units;
 
 use clap::Parser;
 use color_eyre::Result as AnyResult;
 use dotenvy::dotenv;
+use expiration::DigestScheduler;
 use migrations::{Migrator, MigratorTrait};
 use sea_orm::Database;
 use server::Server;
@@ -28,8 +37,28 @@ async fn main() -> AnyResult<()> {
 
     match cli.command {
         Commands::Run(args) => {
-            let state = AppState::new(db_connection, redis_sender);
-            let server = Server::new(state);
+            let state = AppState::new(
+                db_connection,
+                redis_sender,
+                args.fetch.fetcher()?,
+                args.fetch.recipe_cache_ttl_secs,
+                args.session.settings(),
+                args.oidc.client()?,
+                args.limits.login(),
+            )
+            .with_scrapers(args.fetch.scrapers());
+            let scheduler = DigestScheduler::new(
+                state.db_client.clone(),
+                args.notifier()?,
+                args.expiration_window_days,
+                args.digest_time,
+            );
+            tokio::spawn(scheduler.run());
+            let server = Server::new(
+                state,
+                args.limits.parse_recipe_link(),
+                args.limits.trusted_proxies(),
+            );
 
             log::info!("Server listening on {}", args.socket);
             server.run(args.socket).await.unwrap();
@@ -39,6 +68,15 @@ async fn main() -> AnyResult<()> {
             let client = database::DBClient::new(db_connection);
             test::migrate_test_data(client).await?;
         }
+        Commands::Import(args) => {
+            let client = database::DBClient::new(db_connection);
+            import::run(&client, &args).await?;
+        }
+        Commands::SeedProducts(args) => {
+            let client = database::DBClient::new(db_connection);
+            let count = products::seed_products(client, &args.file).await?;
+            log::info!("Loaded {count} products from {}", args.file.display());
+        }
     }
     Ok(())
 }
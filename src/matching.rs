@@ -0,0 +1,380 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use uuid::Uuid;
+
+use crate::database::errors::ListError;
+use crate::database::ingredient_aliases::dto::IngredientAliasDto;
+use crate::database::ingredients::dto::IngredientDto;
+use crate::database::DBTrait;
+use crate::server::routes::parse_ingredients::payload::ParsedRecipeIngredient;
+
+/// Matches below this confidence are not reported
+pub const MIN_CONFIDENCE: f64 = 0.5;
+/// Matches at or above this confidence are used when recipes are created
+/// without anyone reviewing the ingredients
+pub const AUTO_MATCH_CONFIDENCE: f64 = 0.9;
+
+/// How long a loaded matcher is shared between requests. Ingredients created along with
+/// recipes are matched once it runs out.
+const CACHE_TTL: Duration = Duration::from_mins(1);
+
+/// Words in ingredient lines that do not change which ingredient is meant
+const FILLER_WORDS: &[&str] = &[
+    "boneless", "skinless", "fresh", "freshly", "large", "medium", "small", "whole", "ripe",
+    "organic", "chopped", "diced", "sliced", "minced", "ground", "dried", "frozen",
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct IngredientMatch {
+    pub ingredient_id: Uuid,
+    pub ingredient_name: String,
+    pub confidence: f64,
+}
+
+struct Candidate {
+    ingredient_id: Uuid,
+    ingredient_name: String,
+    normalized: String,
+    trigrams: HashSet<String>,
+}
+
+/// Finds the existing ingredient a free-form name refers to.
+/// Confirmed aliases match exactly, other names by trigram similarity of their
+/// normalised (lowercase, singular) forms.
+pub struct IngredientMatcher {
+    candidates: Vec<Candidate>,
+    exact: HashMap<String, usize>,
+}
+
+impl IngredientMatcher {
+    /// Loads every ingredient and alias
+    pub async fn load(client: &(dyn DBTrait + Send + Sync)) -> Result<Self, ListError> {
+        Ok(IngredientMatcher::new(
+            client.list_all_ingredients().await?.items,
+            client.list_all_ingredient_aliases().await?.items,
+        ))
+    }
+
+    pub fn new(ingredients: Vec<IngredientDto>, aliases: Vec<IngredientAliasDto>) -> Self {
+        let mut candidates: Vec<Candidate> = ingredients
+            .into_iter()
+            .map(|ingredient| {
+                let normalized = normalize_name(&ingredient.name);
+                Candidate {
+                    ingredient_id: ingredient.id,
+                    ingredient_name: ingredient.name,
+                    trigrams: trigrams(&normalized),
+                    normalized,
+                }
+            })
+            .collect();
+        let mut exact: HashMap<String, usize> = candidates
+            .iter()
+            .enumerate()
+            .map(|(index, candidate)| (candidate.normalized.clone(), index))
+            .collect();
+        let by_id: HashMap<Uuid, usize> = candidates
+            .iter()
+            .enumerate()
+            .map(|(index, candidate)| (candidate.ingredient_id, index))
+            .collect();
+        for alias in aliases {
+            if let Some(&index) = by_id.get(&alias.ingredient_id) {
+                // Aliases win over ingredients of the same name, they were confirmed
+                exact.insert(alias.alias.clone(), index);
+                let name = candidates[index].ingredient_name.clone();
+                candidates.push(Candidate {
+                    ingredient_id: alias.ingredient_id,
+                    ingredient_name: name,
+                    trigrams: trigrams(&alias.alias),
+                    normalized: alias.alias,
+                });
+            }
+        }
+        IngredientMatcher { candidates, exact }
+    }
+
+    pub fn best_match(&self, name: &str) -> Option<IngredientMatch> {
+        let normalized = normalize_name(name);
+        if normalized.is_empty() {
+            return None;
+        }
+        let found = |candidate: &Candidate, confidence: f64| IngredientMatch {
+            ingredient_id: candidate.ingredient_id,
+            ingredient_name: candidate.ingredient_name.clone(),
+            confidence,
+        };
+        if let Some(&index) = self.exact.get(&normalized) {
+            return Some(found(&self.candidates[index], 1.0));
+        }
+        let essential = strip_filler(&normalized);
+        if let Some(&index) = self.exact.get(&essential) {
+            return Some(found(&self.candidates[index], 0.95));
+        }
+        let query = trigrams(&essential);
+        self.candidates
+            .iter()
+            .map(|candidate| (candidate, similarity(&query, &candidate.trigrams)))
+            .filter(|(_, confidence)| *confidence >= MIN_CONFIDENCE)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(candidate, confidence)| found(candidate, (confidence * 100.0).round() / 100.0))
+    }
+
+    /// Sets the best match and its confidence on each parsed ingredient
+    pub fn annotate(&self, ingredients: &mut [ParsedRecipeIngredient]) {
+        for ingredient in ingredients {
+            if let Some(found) = self.best_match(&ingredient.name) {
+                ingredient.ingredient_id = Some(found.ingredient_id);
+                ingredient.confidence = Some(found.confidence);
+            }
+        }
+    }
+}
+
+/// Matcher and when it was loaded
+type Loaded = Option<(Instant, Arc<IngredientMatcher>)>;
+
+/// Loaded matcher shared between requests, so the ingredients and aliases are not read
+/// for every parsed line. The ingredient routes clear it when they change either.
+#[derive(Clone, Default)]
+pub struct IngredientMatcherCache {
+    loaded: Arc<Mutex<Loaded>>,
+    /// Bumped by `clear`, so a load that started before is not stored
+    generation: Arc<AtomicU64>,
+}
+
+impl IngredientMatcherCache {
+    pub async fn get(
+        &self,
+        client: &(dyn DBTrait + Send + Sync),
+    ) -> Result<Arc<IngredientMatcher>, ListError> {
+        if let Some((loaded_at, matcher)) = self.loaded.lock().unwrap().as_ref() {
+            if loaded_at.elapsed() < CACHE_TTL {
+                return Ok(Arc::clone(matcher));
+            }
+        }
+        let generation = self.generation.load(Ordering::SeqCst);
+        let matcher = Arc::new(IngredientMatcher::load(client).await?);
+        let mut loaded = self.loaded.lock().unwrap();
+        if self.generation.load(Ordering::SeqCst) == generation {
+            *loaded = Some((Instant::now(), Arc::clone(&matcher)));
+        }
+        Ok(matcher)
+    }
+
+    pub fn clear(&self) {
+        let mut loaded = self.loaded.lock().unwrap();
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        *loaded = None;
+    }
+}
+
+/// Lowercase, singular words without punctuation, e.g. "Chicken Breasts" becomes "chicken breast"
+pub fn normalize_name(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(singularize)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn strip_filler(normalized: &str) -> String {
+    let words: Vec<&str> = normalized
+        .split(' ')
+        .filter(|word| !FILLER_WORDS.contains(word))
+        .collect();
+    if words.is_empty() {
+        normalized.to_owned()
+    } else {
+        words.join(" ")
+    }
+}
+
+fn singularize(word: &str) -> String {
+    const IRREGULAR: &[(&str, &str)] = &[
+        ("leaves", "leaf"),
+        ("halves", "half"),
+        ("loaves", "loaf"),
+        ("knives", "knife"),
+        ("geese", "goose"),
+        ("mice", "mouse"),
+        ("teeth", "tooth"),
+    ];
+    // Words ending in "s" that are already singular
+    const SINGULAR: &[&str] = &[
+        "asparagus",
+        "couscous",
+        "hummus",
+        "molasses",
+        "swiss",
+        "citrus",
+        "octopus",
+        "series",
+        "species",
+        "grits",
+    ];
+    if let Some((_, singular)) = IRREGULAR.iter().find(|(plural, _)| *plural == word) {
+        return (*singular).to_owned();
+    }
+    if word.len() <= 3 || SINGULAR.contains(&word) {
+        return word.to_owned();
+    }
+    if let Some(stem) = word.strip_suffix("ies") {
+        return format!("{stem}y");
+    }
+    for suffix in ["oes", "ches", "shes", "sses", "xes"] {
+        if word.ends_with(suffix) {
+            return word[..word.len() - 2].to_owned();
+        }
+    }
+    if word.ends_with("ss") || word.ends_with("us") || word.ends_with("is") {
+        return word.to_owned();
+    }
+    word.strip_suffix('s').unwrap_or(word).to_owned()
+}
+
+/// Trigrams of each word padded with spaces, as `pg_trgm` builds them
+fn trigrams(normalized: &str) -> HashSet<String> {
+    let mut trigrams = HashSet::new();
+    for word in normalized.split(' ').filter(|word| !word.is_empty()) {
+        let padded: Vec<char> = format!("  {word} ").chars().collect();
+        for window in padded.windows(3) {
+            trigrams.insert(window.iter().collect());
+        }
+    }
+    trigrams
+}
+
+/// Shared trigrams over all distinct trigrams, 1 for equal names
+#[allow(clippy::cast_precision_loss)]
+fn similarity(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let shared = a.intersection(b).count();
+    let total = a.len() + b.len() - shared;
+    if total == 0 {
+        0.0
+    } else {
+        shared as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    fn ingredient(name: &str) -> IngredientDto {
+        IngredientDto {
+            id: Uuid::new_v4(),
+            name: name.to_owned(),
+            freezer_shelf_life_days: None,
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+
+    fn alias(alias: &str, ingredient: &IngredientDto) -> IngredientAliasDto {
+        IngredientAliasDto {
+            id: Uuid::new_v4(),
+            alias: normalize_name(alias),
+            ingredient_id: ingredient.id,
+            user_id: None,
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+
+    #[test]
+    fn words_are_singularized() {
+        let cases = [
+            ("eggs", "egg"),
+            ("berries", "berry"),
+            ("tomatoes", "tomato"),
+            ("peaches", "peach"),
+            ("radishes", "radish"),
+            ("glasses", "glass"),
+            ("boxes", "box"),
+            ("leaves", "leaf"),
+            ("halves", "half"),
+            ("asparagus", "asparagus"),
+            ("hummus", "hummus"),
+            ("grits", "grits"),
+            ("bass", "bass"),
+            ("peas", "pea"),
+            ("oil", "oil"),
+        ];
+        for (word, singular) in cases {
+            assert_eq!(singularize(word), singular, "{word}");
+        }
+    }
+
+    #[test]
+    fn names_are_normalized() {
+        let cases = [
+            ("Chicken Breasts", "chicken breast"),
+            ("  Cherry-Tomatoes, halved ", "cherry tomato halved"),
+            ("Bay Leaves", "bay leaf"),
+            ("100% Whole Wheat Flour", "100 whole wheat flour"),
+            ("!!", ""),
+        ];
+        for (name, normalized) in cases {
+            assert_eq!(normalize_name(name), normalized, "{name}");
+        }
+    }
+
+    #[test]
+    fn names_match_existing_ingredients() {
+        let chicken = ingredient("Chicken Breast");
+        let thighs = ingredient("Chicken Thigh");
+        let onion = ingredient("Onion");
+        let scallion = ingredient("Scallion");
+        let matcher = IngredientMatcher::new(
+            vec![chicken.clone(), thighs, onion.clone(), scallion.clone()],
+            vec![alias("green onions", &scallion)],
+        );
+        let cases = [
+            ("chicken breasts", Some((&chicken, 1.0))),
+            ("boneless chicken breast", Some((&chicken, 0.95))),
+            ("Boneless Skinless Chicken Breasts", Some((&chicken, 0.95))),
+            ("onions", Some((&onion, 1.0))),
+            ("diced onion", Some((&onion, 0.95))),
+            ("Green Onions", Some((&scallion, 1.0))),
+            ("sugar", None),
+            ("", None),
+        ];
+        for (name, expected) in cases {
+            let found = matcher.best_match(name);
+            assert_eq!(
+                found.map(|found| (found.ingredient_id, found.confidence)),
+                expected.map(|(ingredient, confidence)| (ingredient.id, confidence)),
+                "{name}"
+            );
+        }
+        let found = matcher.best_match("chicken breast fillets").unwrap();
+        assert_eq!(found.ingredient_id, chicken.id);
+        assert!((MIN_CONFIDENCE..AUTO_MATCH_CONFIDENCE).contains(&found.confidence));
+    }
+
+    #[test]
+    fn aliases_win_over_ingredients_of_the_same_name() {
+        let scallion = ingredient("Scallion");
+        let green_onion = ingredient("Green Onion");
+        let matcher = IngredientMatcher::new(
+            vec![scallion.clone(), green_onion],
+            vec![alias("green onion", &scallion)],
+        );
+        let found = matcher.best_match("green onions").unwrap();
+        assert_eq!(found.ingredient_id, scallion.id);
+        assert_eq!(found.ingredient_name, "Scallion");
+    }
+
+    #[test]
+    fn aliases_of_unknown_ingredients_are_ignored() {
+        let onion = ingredient("Onion");
+        let deleted = ingredient("Shallot");
+        let matcher = IngredientMatcher::new(vec![onion], vec![alias("shallots", &deleted)]);
+        assert_eq!(matcher.best_match("shallots"), None);
+    }
+}
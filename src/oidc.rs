@@ -0,0 +1,427 @@
+//! OIDC login with the authorization code flow and PKCE.
+//! The ID token is received directly from the token endpoint, so its claims are checked
+//! but not its signature, as section 3.1.3.7 of `OpenID` Connect Core 1.0 allows.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::Utc;
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tokio::sync::OnceCell;
+use url::Url;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const STATE_LENGTH: usize = 32;
+/// RFC 7636 allows 43 to 128 characters
+const CODE_VERIFIER_LENGTH: usize = 64;
+
+#[derive(Error, Debug)]
+pub enum OidcError {
+    #[error("OIDC login needs an issuer URL, a client id and a redirect URL")]
+    Config,
+    #[error("Provider discovery failed: {reason}")]
+    Discovery { reason: String },
+    #[error("Token endpoint responded with {status}: {body}")]
+    TokenRequest { status: StatusCode, body: String },
+    #[error("Invalid ID token: {reason}")]
+    InvalidToken { reason: String },
+    #[error("{error}")]
+    Request { error: reqwest::Error },
+}
+
+impl From<reqwest::Error> for OidcError {
+    fn from(error: reqwest::Error) -> Self {
+        OidcError::Request { error }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct OidcSettings {
+    pub issuer: Url,
+    pub client_id: String,
+    /// `None` for public clients, which rely on PKCE alone
+    pub client_secret: Option<String>,
+    /// Callback the provider sends the user back to
+    pub redirect_url: Url,
+    pub scopes: String,
+}
+
+/// Values kept between sending the user to the provider and the provider's callback
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AuthorizationRequest {
+    pub state: String,
+    pub nonce: String,
+    pub code_verifier: String,
+}
+
+impl AuthorizationRequest {
+    pub fn new() -> Self {
+        AuthorizationRequest {
+            state: random_string(STATE_LENGTH),
+            nonce: random_string(STATE_LENGTH),
+            code_verifier: random_string(CODE_VERIFIER_LENGTH),
+        }
+    }
+
+    /// S256 code challenge sent with the authorization request
+    pub fn code_challenge(&self) -> String {
+        URL_SAFE_NO_PAD.encode(Sha256::digest(self.code_verifier.as_bytes()))
+    }
+}
+
+/// User as identified by the provider
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Identity {
+    pub issuer: String,
+    pub subject: String,
+    pub preferred_username: Option<String>,
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: Url,
+    token_endpoint: Url,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Audience {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Audience {
+    fn contains(&self, client_id: &str) -> bool {
+        match self {
+            Audience::One(audience) => audience == client_id,
+            Audience::Many(audiences) => audiences.iter().any(|audience| audience == client_id),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Claims {
+    iss: String,
+    sub: String,
+    aud: Audience,
+    exp: i64,
+    nonce: Option<String>,
+    preferred_username: Option<String>,
+    email: Option<String>,
+}
+
+/// Client for the configured provider. The provider is discovered on first use,
+/// so the server starts even while the provider is unreachable.
+#[derive(Clone)]
+pub struct OidcClient {
+    client: Client,
+    settings: Arc<OidcSettings>,
+    metadata: Arc<OnceCell<ProviderMetadata>>,
+}
+
+impl OidcClient {
+    pub fn new(settings: OidcSettings) -> Result<Self, OidcError> {
+        Ok(OidcClient {
+            client: Client::builder()
+                .timeout(TIMEOUT)
+                .connect_timeout(TIMEOUT)
+                .build()?,
+            settings: Arc::new(settings),
+            metadata: Arc::new(OnceCell::new()),
+        })
+    }
+
+    async fn metadata(&self) -> Result<&ProviderMetadata, OidcError> {
+        self.metadata
+            .get_or_try_init(|| async {
+                let url = format!(
+                    "{}/.well-known/openid-configuration",
+                    self.settings.issuer.as_str().trim_end_matches('/')
+                );
+                let response = self.client.get(&url).send().await?;
+                if !response.status().is_success() {
+                    return Err(OidcError::Discovery {
+                        reason: format!("{url} responded with {}", response.status()),
+                    });
+                }
+                let metadata: ProviderMetadata = serde_json::from_str(&response.text().await?)
+                    .map_err(|err| OidcError::Discovery {
+                        reason: format!("Bad provider metadata: {err}"),
+                    })?;
+                if !same_issuer(&metadata.issuer, self.settings.issuer.as_str()) {
+                    return Err(OidcError::Discovery {
+                        reason: format!("Provider calls itself {:?}", metadata.issuer),
+                    });
+                }
+                Ok(metadata)
+            })
+            .await
+    }
+
+    /// URL of the provider's login page for the request
+    pub async fn authorization_url(
+        &self,
+        request: &AuthorizationRequest,
+    ) -> Result<Url, OidcError> {
+        let mut url = self.metadata().await?.authorization_endpoint.clone();
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.settings.client_id)
+            .append_pair("redirect_uri", self.settings.redirect_url.as_str())
+            .append_pair("scope", &self.settings.scopes)
+            .append_pair("state", &request.state)
+            .append_pair("nonce", &request.nonce)
+            .append_pair("code_challenge", &request.code_challenge())
+            .append_pair("code_challenge_method", "S256");
+        Ok(url)
+    }
+
+    /// Redeems the authorization code the provider sent to the callback
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        request: &AuthorizationRequest,
+    ) -> Result<Identity, OidcError> {
+        let metadata = self.metadata().await?;
+        let mut token_request = self.client.post(metadata.token_endpoint.clone()).form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.settings.redirect_url.as_str()),
+            ("client_id", &self.settings.client_id),
+            ("code_verifier", &request.code_verifier),
+        ]);
+        if let Some(secret) = &self.settings.client_secret {
+            token_request = token_request.basic_auth(&self.settings.client_id, Some(secret));
+        }
+        let response = token_request.send().await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(OidcError::TokenRequest { status, body });
+        }
+        let tokens: TokenResponse =
+            serde_json::from_str(&body).map_err(|err| OidcError::InvalidToken {
+                reason: format!("Bad token response: {err}"),
+            })?;
+        let claims = id_token_claims(&tokens.id_token)?;
+        self.check_claims(&claims, metadata, request)?;
+        Ok(Identity {
+            issuer: claims.iss,
+            subject: claims.sub,
+            preferred_username: claims.preferred_username,
+            email: claims.email,
+        })
+    }
+
+    fn check_claims(
+        &self,
+        claims: &Claims,
+        metadata: &ProviderMetadata,
+        request: &AuthorizationRequest,
+    ) -> Result<(), OidcError> {
+        let invalid = |reason: &str| {
+            Err(OidcError::InvalidToken {
+                reason: reason.to_owned(),
+            })
+        };
+        if !same_issuer(&claims.iss, &metadata.issuer) {
+            return invalid("issued by another provider");
+        }
+        if !claims.aud.contains(&self.settings.client_id) {
+            return invalid("issued for another client");
+        }
+        if claims.exp <= Utc::now().timestamp() {
+            return invalid("expired");
+        }
+        if claims.nonce.as_deref() != Some(request.nonce.as_str()) {
+            return invalid("nonce does not match");
+        }
+        Ok(())
+    }
+}
+
+/// Decodes the payload of the JWT
+fn id_token_claims(id_token: &str) -> Result<Claims, OidcError> {
+    let payload = id_token
+        .split('.')
+        .nth(1)
+        .ok_or_else(|| OidcError::InvalidToken {
+            reason: "not a JWT".to_owned(),
+        })?;
+    let payload = URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .map_err(|err| OidcError::InvalidToken {
+            reason: err.to_string(),
+        })?;
+    serde_json::from_slice(&payload).map_err(|err| OidcError::InvalidToken {
+        reason: err.to_string(),
+    })
+}
+
+/// Issuers are compared ignoring a trailing slash, which URL parsing may add
+fn same_issuer(a: &str, b: &str) -> bool {
+    a.trim_end_matches('/') == b.trim_end_matches('/')
+}
+
+fn random_string(length: usize) -> String {
+    thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(length)
+        .map(char::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use axum::extract::State;
+    use axum::routing::{get, post};
+    use axum::{Form, Json, Router};
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    const CLIENT_ID: &str = "pantry-tracker";
+    const CODE: &str = "authorization-code";
+
+    /// Identity provider that issues an ID token for `CODE` if the PKCE verifier matches
+    /// the challenge of `request`
+    async fn mock_provider(request: &AuthorizationRequest, audience: &str) -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+        let claims = json!({
+            "iss": issuer,
+            "sub": "user-1",
+            "aud": [audience],
+            "exp": Utc::now().timestamp() + 60,
+            "nonce": request.nonce,
+            "preferred_username": "alice",
+        });
+        let id_token = format!(
+            "{}.{}.signature",
+            URL_SAFE_NO_PAD.encode(r#"{"alg":"RS256"}"#),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        );
+        let discovery = json!({
+            "issuer": issuer,
+            "authorization_endpoint": format!("{issuer}/authorize"),
+            "token_endpoint": format!("{issuer}/token"),
+        });
+        let router = Router::new()
+            .route(
+                "/.well-known/openid-configuration",
+                get(move || {
+                    let discovery = discovery.clone();
+                    async move { Json(discovery) }
+                }),
+            )
+            .route(
+                "/token",
+                post(
+                    |State((challenge, id_token)): State<(String, String)>,
+                     Form(form): Form<HashMap<String, String>>| async move {
+                        let code_verifier = form.get("code_verifier").cloned().unwrap_or_default();
+                        let pkce_ok = AuthorizationRequest {
+                            state: String::new(),
+                            nonce: String::new(),
+                            code_verifier,
+                        }
+                        .code_challenge()
+                            == challenge;
+                        if form.get("code").map(String::as_str) == Some(CODE) && pkce_ok {
+                            (StatusCode::OK, Json(json!({ "id_token": id_token })))
+                        } else {
+                            (
+                                StatusCode::BAD_REQUEST,
+                                Json(json!({ "error": "invalid_grant" })),
+                            )
+                        }
+                    },
+                ),
+            )
+            .with_state((request.code_challenge(), id_token));
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        Url::parse(&issuer).unwrap()
+    }
+
+    fn client(issuer: Url) -> OidcClient {
+        OidcClient::new(OidcSettings {
+            issuer,
+            client_id: CLIENT_ID.to_owned(),
+            client_secret: None,
+            redirect_url: Url::parse("http://localhost:8080/login/oidc/callback").unwrap(),
+            scopes: "openid profile".to_owned(),
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn logs_in_with_code_and_pkce() {
+        let request = AuthorizationRequest::new();
+        let client = client(mock_provider(&request, CLIENT_ID).await);
+
+        let url = client.authorization_url(&request).await.unwrap();
+        let query: HashMap<_, _> = url.query_pairs().into_owned().collect();
+        assert_eq!(url.path(), "/authorize");
+        assert_eq!(query["code_challenge"], request.code_challenge());
+        assert_eq!(query["code_challenge_method"], "S256");
+        assert_eq!(query["state"], request.state);
+
+        let identity = client.exchange_code(CODE, &request).await.unwrap();
+        assert_eq!(identity.subject, "user-1");
+        assert_eq!(identity.preferred_username.as_deref(), Some("alice"));
+    }
+
+    #[tokio::test]
+    async fn rejects_wrong_code_verifier() {
+        let request = AuthorizationRequest::new();
+        let client = client(mock_provider(&request, CLIENT_ID).await);
+        let other = AuthorizationRequest {
+            code_verifier: random_string(CODE_VERIFIER_LENGTH),
+            ..request
+        };
+        assert!(matches!(
+            client.exchange_code(CODE, &other).await,
+            Err(OidcError::TokenRequest { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_token_for_other_client() {
+        let request = AuthorizationRequest::new();
+        let client = client(mock_provider(&request, "other-client").await);
+        assert!(matches!(
+            client.exchange_code(CODE, &request).await,
+            Err(OidcError::InvalidToken { .. })
+        ));
+    }
+
+    #[test]
+    fn code_challenge_matches_rfc_7636_example() {
+        let request = AuthorizationRequest {
+            state: String::new(),
+            nonce: String::new(),
+            code_verifier: "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk".to_owned(),
+        };
+        assert_eq!(
+            request.code_challenge(),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use std::path::Path;
+
+use color_eyre::eyre::{eyre, WrapErr};
+use color_eyre::Result as AnyResult;
+use serde::Deserialize;
+use titlecase::titlecase;
+
+use crate::database::DBTrait;
+
+/// Lengths of GTIN-8, UPC-A (GTIN-12), EAN-13 (GTIN-13) and GTIN-14 codes
+const GTIN_LENGTHS: [usize; 4] = [8, 12, 13, 14];
+const GTIN_PADDED_LENGTH: usize = 14;
+
+/// Validates a scanned barcode and pads it with zeros to a GTIN-14,
+/// so the same product is found whichever format was scanned
+pub fn normalize_gtin(barcode: &str) -> Option<String> {
+    let digits: String = barcode.chars().filter(|c| !c.is_whitespace()).collect();
+    if !GTIN_LENGTHS.contains(&digits.len()) || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let padded = format!("{digits:0>GTIN_PADDED_LENGTH$}");
+    let digits: Vec<u32> = padded.chars().filter_map(|c| c.to_digit(10)).collect();
+    let (check_digit, payload) = digits.split_last()?;
+    // Weights alternate 3 and 1 starting from the digit next to the check digit
+    let sum: u32 = payload
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, digit)| if i % 2 == 0 { digit * 3 } else { *digit })
+        .sum();
+    ((10 - sum % 10) % 10 == *check_digit).then_some(padded)
+}
+
+/// Row of a products CSV file. The file has a header with these column names.
+/// The ingredient is created if no ingredient has that name.
+#[derive(Deserialize, Debug)]
+struct ProductRecord {
+    gtin: String,
+    ingredient: String,
+    name: Option<String>,
+    quantity: Option<i32>,
+    weight_grams: Option<i32>,
+    volume_milli_litres: Option<i32>,
+}
+
+/// Creates or replaces the products listed in a CSV file. Returns the number of products loaded.
+pub async fn seed_products(client: impl DBTrait + Send + Sync, path: &Path) -> AnyResult<u64> {
+    let mut reader = csv::Reader::from_path(path)
+        .wrap_err_with(|| format!("Could not open {}", path.display()))?;
+    let mut count = 0;
+    for (line, record) in reader.deserialize::<ProductRecord>().enumerate() {
+        // The header is the first line
+        let line = line + 2;
+        let record = record.wrap_err_with(|| format!("Invalid product on line {line}"))?;
+        let gtin = normalize_gtin(&record.gtin)
+            .ok_or_else(|| eyre!("Invalid GTIN {:?} on line {line}", record.gtin))?;
+        if [
+            record.quantity,
+            record.weight_grams,
+            record.volume_milli_litres,
+        ]
+        .iter()
+        .flatten()
+        .count()
+            > 1
+        {
+            return Err(eyre!(
+                "Product on line {line} must indicate only one of quantity, weight_grams or volume_milli_litres"
+            ));
+        }
+        let ingredient = client
+            .get_or_create_ingredient(titlecase(record.ingredient.trim()))
+            .await?;
+        client
+            .upsert_product(crate::database::products::dto::CreateDto {
+                gtin,
+                ingredient_id: ingredient.id,
+                name: record.name,
+                quantity: record.quantity,
+                weight_grams: record.weight_grams,
+                volume_milli_litres: record.volume_milli_litres,
+            })
+            .await?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn barcodes_are_padded_to_gtin_14() {
+        let cases = [
+            // EAN-13, UPC-A, EAN-8 and GTIN-14
+            ("4006381333931", Some("04006381333931")),
+            ("036000291452", Some("00036000291452")),
+            ("96385074", Some("00000096385074")),
+            ("10012345678902", Some("10012345678902")),
+            // Whitespace from scanners is ignored
+            (" 4006 3813 3393 1\n", Some("04006381333931")),
+            // The same product scanned as UPC-A and as EAN-13
+            ("0036000291452", Some("00036000291452")),
+        ];
+        for (barcode, gtin) in cases {
+            assert_eq!(normalize_gtin(barcode).as_deref(), gtin, "{barcode:?}");
+        }
+    }
+
+    #[test]
+    fn invalid_barcodes_are_rejected() {
+        let cases = [
+            // Wrong check digit
+            "4006381333932",
+            "4006381333930",
+            "036000291453",
+            // Lengths that are not GTINs
+            "",
+            "1234567",
+            "400638133393",
+            "40063813339310",
+            "400638133393100",
+            // Not digits
+            "400638133393a",
+            "4006381-33393",
+            "٤٠٠٦٣٨١٣٣٣٩٣١",
+        ];
+        for barcode in cases {
+            assert_eq!(normalize_gtin(barcode), None, "{barcode:?}");
+        }
+    }
+}
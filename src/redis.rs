@@ -55,11 +55,11 @@ impl RedisClient {
         self.connection.get(key).map_err(Into::into)
     }
 
-    pub fn set(&mut self, key: &str, value: &str, ttl_days: Option<u16>) -> RedisResult<()> {
-        self.connection.set(key, value)?;
-        if let Some(days) = ttl_days {
+    pub fn set(&mut self, key: &str, value: &str, ttl_secs: Option<u64>) -> RedisResult<()> {
+        self.connection.set::<_, _, ()>(key, value)?;
+        if let Some(secs) = ttl_secs {
             self.connection
-                .expire(key, i64::from(days) * 24 * 60 * 60)?;
+                .expire::<_, ()>(key, i64::try_from(secs).unwrap_or(i64::MAX))?;
         }
         Ok(())
     }
@@ -67,6 +67,39 @@ impl RedisClient {
     pub fn delete(&mut self, key: &str) -> RedisResult<()> {
         self.connection.del(key).map_err(Into::into)
     }
+
+    /// Returns `false` if the key does not exist
+    pub fn expire(&mut self, key: &str, ttl_secs: u64) -> RedisResult<bool> {
+        self.connection
+            .expire(key, i64::try_from(ttl_secs).unwrap_or(i64::MAX))
+            .map_err(Into::into)
+    }
+
+    /// Counts up the key, which expires `ttl_secs` after it was first counted
+    pub fn increment(&mut self, key: &str, ttl_secs: u64) -> RedisResult<u64> {
+        let count: u64 = self.connection.incr(key, 1)?;
+        if count == 1 {
+            self.connection
+                .expire::<_, ()>(key, i64::try_from(ttl_secs).unwrap_or(i64::MAX))?;
+        }
+        Ok(count)
+    }
+
+    pub fn set_add(&mut self, key: &str, member: &str) -> RedisResult<()> {
+        self.connection
+            .sadd::<_, _, ()>(key, member)
+            .map_err(Into::into)
+    }
+
+    pub fn set_remove(&mut self, key: &str, member: &str) -> RedisResult<()> {
+        self.connection
+            .srem::<_, _, ()>(key, member)
+            .map_err(Into::into)
+    }
+
+    pub fn set_members(&mut self, key: &str) -> RedisResult<Vec<String>> {
+        self.connection.smembers(key).map_err(Into::into)
+    }
 }
 
 pub enum RedisCommand {
@@ -77,13 +110,37 @@ pub enum RedisCommand {
     Set {
         key: String,
         val: String,
-        ttl_days: Option<u16>,
+        ttl_secs: Option<u64>,
         resp: Responder<()>,
     },
     Delete {
         key: String,
         resp: Responder<()>,
     },
+    Expire {
+        key: String,
+        ttl_secs: u64,
+        resp: Responder<bool>,
+    },
+    Increment {
+        key: String,
+        ttl_secs: u64,
+        resp: Responder<u64>,
+    },
+    SetAdd {
+        key: String,
+        member: String,
+        resp: Responder<()>,
+    },
+    SetRemove {
+        key: String,
+        member: String,
+        resp: Responder<()>,
+    },
+    SetMembers {
+        key: String,
+        resp: Responder<Vec<String>>,
+    },
 }
 
 type Responder<T> = oneshot::Sender<RedisResult<T>>;
@@ -100,14 +157,37 @@ pub async fn new_redis_sender(mut redis_client: RedisClient) -> Sender<RedisComm
                 RedisCommand::Set {
                     key,
                     val,
-                    ttl_days: expire_days,
+                    ttl_secs,
                     resp,
                 } => {
-                    let _ = resp.send(redis_client.set(&key, &val, expire_days));
+                    let _ = resp.send(redis_client.set(&key, &val, ttl_secs));
                 }
                 RedisCommand::Delete { key, resp } => {
                     let _ = resp.send(redis_client.delete(&key));
                 }
+                RedisCommand::Expire {
+                    key,
+                    ttl_secs,
+                    resp,
+                } => {
+                    let _ = resp.send(redis_client.expire(&key, ttl_secs));
+                }
+                RedisCommand::Increment {
+                    key,
+                    ttl_secs,
+                    resp,
+                } => {
+                    let _ = resp.send(redis_client.increment(&key, ttl_secs));
+                }
+                RedisCommand::SetAdd { key, member, resp } => {
+                    let _ = resp.send(redis_client.set_add(&key, &member));
+                }
+                RedisCommand::SetRemove { key, member, resp } => {
+                    let _ = resp.send(redis_client.set_remove(&key, &member));
+                }
+                RedisCommand::SetMembers { key, resp } => {
+                    let _ = resp.send(redis_client.set_members(&key));
+                }
             }
         }
         Ok::<(), AnyError>(())
@@ -117,8 +197,13 @@ pub async fn new_redis_sender(mut redis_client: RedisClient) -> Sender<RedisComm
 
 pub trait RedisCommands {
     async fn get(&self, key: &str) -> RedisResult<Option<String>>;
-    async fn set(&self, key: &str, value: &str, expire_days: Option<u16>) -> RedisResult<()>;
+    async fn set(&self, key: &str, value: &str, expire_secs: Option<u64>) -> RedisResult<()>;
     async fn delete(&self, key: &str) -> RedisResult<()>;
+    async fn expire(&self, key: &str, ttl_secs: u64) -> RedisResult<bool>;
+    async fn increment(&self, key: &str, ttl_secs: u64) -> RedisResult<u64>;
+    async fn set_add(&self, key: &str, member: &str) -> RedisResult<()>;
+    async fn set_remove(&self, key: &str, member: &str) -> RedisResult<()>;
+    async fn set_members(&self, key: &str) -> RedisResult<Vec<String>>;
 }
 
 impl RedisCommands for Sender<RedisCommand> {
@@ -132,12 +217,12 @@ impl RedisCommands for Sender<RedisCommand> {
         receiver.await?
     }
 
-    async fn set(&self, key: &str, value: &str, expire_days: Option<u16>) -> RedisResult<()> {
+    async fn set(&self, key: &str, value: &str, expire_secs: Option<u64>) -> RedisResult<()> {
         let (sender, receiver) = oneshot::channel::<RedisResult<()>>();
         self.send(RedisCommand::Set {
             key: key.to_owned(),
             val: value.to_owned(),
-            ttl_days: expire_days,
+            ttl_secs: expire_secs,
             resp: sender,
         })
         .await?;
@@ -153,4 +238,57 @@ impl RedisCommands for Sender<RedisCommand> {
         .await?;
         receiver.await?
     }
+    async fn expire(&self, key: &str, ttl_secs: u64) -> RedisResult<bool> {
+        let (sender, receiver) = oneshot::channel::<RedisResult<bool>>();
+        self.send(RedisCommand::Expire {
+            key: key.to_owned(),
+            ttl_secs,
+            resp: sender,
+        })
+        .await?;
+        receiver.await?
+    }
+
+    async fn increment(&self, key: &str, ttl_secs: u64) -> RedisResult<u64> {
+        let (sender, receiver) = oneshot::channel::<RedisResult<u64>>();
+        self.send(RedisCommand::Increment {
+            key: key.to_owned(),
+            ttl_secs,
+            resp: sender,
+        })
+        .await?;
+        receiver.await?
+    }
+
+    async fn set_add(&self, key: &str, member: &str) -> RedisResult<()> {
+        let (sender, receiver) = oneshot::channel::<RedisResult<()>>();
+        self.send(RedisCommand::SetAdd {
+            key: key.to_owned(),
+            member: member.to_owned(),
+            resp: sender,
+        })
+        .await?;
+        receiver.await?
+    }
+
+    async fn set_remove(&self, key: &str, member: &str) -> RedisResult<()> {
+        let (sender, receiver) = oneshot::channel::<RedisResult<()>>();
+        self.send(RedisCommand::SetRemove {
+            key: key.to_owned(),
+            member: member.to_owned(),
+            resp: sender,
+        })
+        .await?;
+        receiver.await?
+    }
+
+    async fn set_members(&self, key: &str) -> RedisResult<Vec<String>> {
+        let (sender, receiver) = oneshot::channel::<RedisResult<Vec<String>>>();
+        self.send(RedisCommand::SetMembers {
+            key: key.to_owned(),
+            resp: sender,
+        })
+        .await?;
+        receiver.await?
+    }
 }
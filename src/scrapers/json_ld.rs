@@ -0,0 +1,39 @@
+use scraper::Html;
+use serde_json::{json, Value};
+
+use super::{selector, RecipeScraper};
+
+/// Reads the `Recipe` object from `application/ld+json` scripts
+pub struct JsonLdScraper;
+
+impl RecipeScraper for JsonLdScraper {
+    fn scrape(&self, html: &Html) -> Option<Value> {
+        html.select(&selector(r#"script[type="application/ld+json"]"#))
+            .filter_map(|element| {
+                serde_json::from_str::<Value>(&element.text().collect::<String>()).ok()
+            })
+            .find_map(|json| find_recipe(&json).cloned())
+    }
+}
+
+fn find_recipe(json: &Value) -> Option<&Value> {
+    match json {
+        Value::Array(items) => items.iter().find_map(find_recipe),
+        Value::Object(object) => {
+            if is_recipe(json) {
+                Some(json)
+            } else {
+                object.get("@graph").and_then(find_recipe)
+            }
+        }
+        _ => None,
+    }
+}
+
+fn is_recipe(json: &Value) -> bool {
+    match json.get("@type") {
+        Some(Value::Array(types)) => types.contains(&json!("Recipe")),
+        Some(otype) => otype.eq(&json!("Recipe")),
+        None => false,
+    }
+}
@@ -0,0 +1,105 @@
+use scraper::{ElementRef, Html};
+use serde_json::Value;
+
+use super::{all_texts, recipe_json, selector, text, RecipeScraper};
+
+/// Reads schema.org `Recipe` microdata (`itemprop` attributes)
+pub struct MicrodataScraper;
+
+impl RecipeScraper for MicrodataScraper {
+    fn scrape(&self, html: &Html) -> Option<Value> {
+        let root = html
+            .select(&selector("[itemscope][itemtype]"))
+            .find(|element| {
+                element.attr("itemtype").is_some_and(|itemtype| {
+                    itemtype
+                        .trim_end_matches('/')
+                        .ends_with("schema.org/Recipe")
+                })
+            })?;
+        let properties: Vec<(ElementRef, &str)> = root
+            .select(&selector("[itemprop]"))
+            .filter(|element| owner(*element).map(|owner| owner.id()) == Some(root.id()))
+            .flat_map(|element| {
+                element
+                    .attr("itemprop")
+                    .unwrap_or_default()
+                    .split_whitespace()
+                    .map(move |property| (element, property))
+            })
+            .collect();
+        let values = |name: &str| -> Vec<ElementRef> {
+            properties
+                .iter()
+                .filter(|(_, property)| *property == name)
+                .map(|(element, _)| *element)
+                .collect()
+        };
+        let first = |name: &str| {
+            values(name)
+                .into_iter()
+                .map(value)
+                .find(|value| !value.is_empty())
+        };
+
+        let mut ingredients: Vec<String> = values("recipeIngredient")
+            .into_iter()
+            .chain(values("ingredients"))
+            .map(value)
+            .filter(|ingredient| !ingredient.is_empty())
+            .collect();
+        ingredients.dedup();
+        let instructions = values("recipeInstructions")
+            .into_iter()
+            .flat_map(instruction_steps)
+            .collect();
+        recipe_json(
+            first("name"),
+            &ingredients,
+            instructions,
+            first("prepTime"),
+            first("totalTime"),
+            first("image"),
+        )
+    }
+}
+
+/// Nearest enclosing `itemscope`, the item the property belongs to
+fn owner(element: ElementRef) -> Option<ElementRef> {
+    element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .find(|ancestor| ancestor.attr("itemscope").is_some())
+}
+
+/// Property value as defined by the microdata spec for the common elements
+fn value(element: ElementRef) -> String {
+    let attribute = match element.value().name() {
+        "img" | "audio" | "source" | "video" => element.attr("src"),
+        "a" | "link" | "area" => element.attr("href"),
+        "time" => element.attr("datetime"),
+        // `meta` and the non-standard `content` attribute on other elements
+        _ => element.attr("content"),
+    };
+    attribute.map_or_else(|| text(element), |value| value.trim().to_owned())
+}
+
+/// Instructions are either `HowToStep` items, a list or a single block of text
+fn instruction_steps(element: ElementRef) -> Vec<String> {
+    if element.attr("itemscope").is_some() {
+        let steps = all_texts(element, r#"[itemprop="text"]"#);
+        if !steps.is_empty() {
+            return steps;
+        }
+    }
+    let steps = all_texts(element, "li");
+    if !steps.is_empty() {
+        return steps;
+    }
+    let step = value(element);
+    if step.is_empty() {
+        Vec::new()
+    } else {
+        vec![step]
+    }
+}
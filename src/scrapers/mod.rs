@@ -0,0 +1,409 @@
+mod json_ld;
+mod microdata;
+mod wordpress;
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use scraper::{ElementRef, Html, Selector};
+use serde_json::{json, Value};
+use url::Url;
+
+pub use json_ld::JsonLdScraper;
+pub use microdata::MicrodataScraper;
+pub use wordpress::{TastyRecipesScraper, WpRecipeMakerScraper};
+
+/// Extracts a recipe from a page as a schema.org `Recipe` object,
+/// so every scraper feeds the same field readers
+pub trait RecipeScraper: Send + Sync {
+    fn scrape(&self, html: &Html) -> Option<Value>;
+}
+
+/// Scrapers for specific sites, tried before the generic ones
+pub struct ScraperRegistry {
+    by_host: HashMap<String, Box<dyn RecipeScraper>>,
+    fallbacks: Vec<Box<dyn RecipeScraper>>,
+}
+
+impl Default for ScraperRegistry {
+    fn default() -> Self {
+        ScraperRegistry {
+            by_host: HashMap::new(),
+            fallbacks: vec![
+                Box::new(JsonLdScraper),
+                Box::new(MicrodataScraper),
+                Box::new(WpRecipeMakerScraper),
+                Box::new(TastyRecipesScraper),
+            ],
+        }
+    }
+}
+
+impl ScraperRegistry {
+    /// Generic scrapers, with the sites' scrapers tried first on their hosts
+    pub fn new(sites: &[SiteScraper]) -> Self {
+        let mut registry = ScraperRegistry::default();
+        for site in sites {
+            registry.register(&site.host, site.kind.scraper());
+        }
+        registry
+    }
+
+    /// Registers a scraper for a host, `www.` is ignored
+    pub fn register(&mut self, host: &str, scraper: Box<dyn RecipeScraper>) {
+        self.by_host
+            .insert(normalize_host(host).to_owned(), scraper);
+    }
+
+    /// Returns the recipe from the host scraper if there is one and it finds the recipe,
+    /// otherwise from the first generic scraper that does
+    pub fn scrape(&self, url: &Url, body: &str) -> Option<Value> {
+        let html = Html::parse_document(body);
+        url.host_str()
+            .and_then(|host| self.by_host.get(normalize_host(host)))
+            .and_then(|scraper| scraper.scrape(&html))
+            .or_else(|| {
+                self.fallbacks
+                    .iter()
+                    .find_map(|scraper| scraper.scrape(&html))
+            })
+    }
+}
+
+/// Scraper a site can be pinned to, for sites whose markup misleads the generic order,
+/// e.g. with incomplete JSON-LD next to complete WP Recipe Maker markup
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScraperKind {
+    JsonLd,
+    Microdata,
+    WpRecipeMaker,
+    TastyRecipes,
+}
+
+impl ScraperKind {
+    fn scraper(self) -> Box<dyn RecipeScraper> {
+        match self {
+            ScraperKind::JsonLd => Box::new(JsonLdScraper),
+            ScraperKind::Microdata => Box::new(MicrodataScraper),
+            ScraperKind::WpRecipeMaker => Box::new(WpRecipeMakerScraper),
+            ScraperKind::TastyRecipes => Box::new(TastyRecipesScraper),
+        }
+    }
+}
+
+impl FromStr for ScraperKind {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.trim() {
+            "json-ld" => Ok(ScraperKind::JsonLd),
+            "microdata" => Ok(ScraperKind::Microdata),
+            "wprm" => Ok(ScraperKind::WpRecipeMaker),
+            "tasty-recipes" => Ok(ScraperKind::TastyRecipes),
+            other => Err(format!(
+                "Unknown scraper {other:?}, expected json-ld, microdata, wprm or tasty-recipes"
+            )),
+        }
+    }
+}
+
+/// Scraper pinned to a host, written as `<host>=<scraper>`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SiteScraper {
+    pub host: String,
+    pub kind: ScraperKind,
+}
+
+impl FromStr for SiteScraper {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (host, kind) = value
+            .split_once('=')
+            .ok_or_else(|| format!("Expected <host>=<scraper>, got {value:?}"))?;
+        let host = host.trim().to_lowercase();
+        if host.is_empty() {
+            return Err(format!("Missing host in {value:?}"));
+        }
+        Ok(SiteScraper {
+            host,
+            kind: kind.parse()?,
+        })
+    }
+}
+
+fn normalize_host(host: &str) -> &str {
+    host.strip_prefix("www.").unwrap_or(host)
+}
+
+fn selector(selector: &str) -> Selector {
+    Selector::parse(selector).expect("scraper selectors are valid")
+}
+
+/// Text content with whitespace collapsed
+fn text(element: ElementRef) -> String {
+    element
+        .text()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn first_text(root: ElementRef, css: &str) -> Option<String> {
+    root.select(&selector(css))
+        .map(text)
+        .find(|text| !text.is_empty())
+}
+
+fn all_texts(root: ElementRef, css: &str) -> Vec<String> {
+    root.select(&selector(css))
+        .map(text)
+        .filter(|text| !text.is_empty())
+        .collect()
+}
+
+/// Builds the schema.org `Recipe` object the field readers understand
+fn recipe_json(
+    name: Option<String>,
+    ingredients: &[String],
+    instructions: Vec<String>,
+    prep_time: Option<String>,
+    total_time: Option<String>,
+    image: Option<String>,
+) -> Option<Value> {
+    if ingredients.is_empty() {
+        return None;
+    }
+    let mut recipe = json!({
+        "@type": "Recipe",
+        "recipeIngredient": ingredients,
+        "recipeInstructions": instructions
+            .into_iter()
+            .map(|text| json!({"@type": "HowToStep", "text": text}))
+            .collect::<Vec<_>>(),
+    });
+    let fields = [
+        ("name", name),
+        ("prepTime", prep_time),
+        ("totalTime", total_time),
+        ("image", image),
+    ];
+    for (key, value) in fields {
+        if let Some(value) = value {
+            recipe[key] = Value::String(value);
+        }
+    }
+    Some(recipe)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::routes::parse_recipe_link::{
+        get_image, get_ingredients, get_instructions, get_name, get_time_field,
+    };
+
+    struct Fixture {
+        file: &'static str,
+        html: &'static str,
+        name: &'static str,
+        prep_time_mins: Option<u32>,
+        total_time_mins: Option<u32>,
+        image: Option<&'static str>,
+        ingredients: &'static [&'static str],
+        steps: usize,
+    }
+
+    const FIXTURES: &[Fixture] = &[
+        Fixture {
+            file: "json_ld_graph.html",
+            html: include_str!("fixtures/json_ld_graph.html"),
+            name: "Lemon Pancakes",
+            prep_time_mins: Some(10),
+            total_time_mins: Some(25),
+            image: Some("https://example.com/pancakes.jpg"),
+            ingredients: &["flour", "milk", "lemon"],
+            steps: 3,
+        },
+        Fixture {
+            file: "json_ld_object.html",
+            html: include_str!("fixtures/json_ld_object.html"),
+            name: "Tomato Soup",
+            prep_time_mins: Some(15),
+            total_time_mins: Some(60),
+            image: Some("https://example.com/soup.jpg"),
+            ingredients: &["tomatoes", "onion", "vegetable stock"],
+            steps: 2,
+        },
+        Fixture {
+            file: "microdata.html",
+            html: include_str!("fixtures/microdata.html"),
+            name: "Banana Bread",
+            prep_time_mins: Some(15),
+            total_time_mins: Some(75),
+            image: Some("https://example.com/banana-bread.jpg"),
+            ingredients: &["ripe bananas", "butter", "sugar", "flour"],
+            steps: 3,
+        },
+        Fixture {
+            file: "wprm.html",
+            html: include_str!("fixtures/wprm.html"),
+            name: "Garlic Butter Rice",
+            prep_time_mins: Some(5),
+            total_time_mins: Some(80),
+            image: Some("https://example.com/wp-content/uploads/rice.jpg"),
+            ingredients: &["rice", "butter", "garlic"],
+            steps: 2,
+        },
+        Fixture {
+            file: "tasty_recipes.html",
+            html: include_str!("fixtures/tasty_recipes.html"),
+            name: "Overnight Oats",
+            prep_time_mins: Some(5),
+            total_time_mins: Some(485),
+            image: Some("https://example.com/wp-content/uploads/oats.jpg"),
+            ingredients: &["oats", "milk", "honey"],
+            steps: 2,
+        },
+    ];
+
+    #[test]
+    fn scrapes_fixtures() {
+        let registry = ScraperRegistry::default();
+        let url = Url::parse("https://example.com/recipe").unwrap();
+        for fixture in FIXTURES {
+            let json = registry
+                .scrape(&url, fixture.html)
+                .unwrap_or_else(|| panic!("{}: no recipe found", fixture.file));
+            assert_eq!(
+                get_name(&json).as_deref(),
+                Some(fixture.name),
+                "{}",
+                fixture.file
+            );
+            assert_eq!(
+                get_time_field(&json, "prepTime"),
+                fixture.prep_time_mins,
+                "{}",
+                fixture.file
+            );
+            assert_eq!(
+                get_time_field(&json, "totalTime"),
+                fixture.total_time_mins,
+                "{}",
+                fixture.file
+            );
+            assert_eq!(
+                get_image(&json).as_ref().map(Url::as_str),
+                fixture.image,
+                "{}",
+                fixture.file
+            );
+            let ingredients: Vec<String> = get_ingredients(&json)
+                .into_iter()
+                .map(|ingredient| ingredient.name.trim().to_lowercase())
+                .collect();
+            assert_eq!(ingredients, fixture.ingredients, "{}", fixture.file);
+            let instructions = get_instructions(&json).unwrap_or_default();
+            assert_eq!(
+                instructions.lines().count(),
+                fixture.steps,
+                "{}",
+                fixture.file
+            );
+            assert!(!instructions.contains("---"), "{}", fixture.file);
+        }
+    }
+
+    #[test]
+    fn page_without_recipe() {
+        let registry = ScraperRegistry::default();
+        let url = Url::parse("https://example.com/about").unwrap();
+        assert!(registry
+            .scrape(&url, include_str!("fixtures/no_recipe.html"))
+            .is_none());
+    }
+
+    struct TitleOnly;
+
+    impl RecipeScraper for TitleOnly {
+        fn scrape(&self, html: &Html) -> Option<Value> {
+            let title = first_text(html.root_element(), "title")?;
+            recipe_json(
+                Some(title),
+                &["Water".to_owned()],
+                Vec::new(),
+                None,
+                None,
+                None,
+            )
+        }
+    }
+
+    #[test]
+    fn site_scrapers_are_parsed() {
+        let cases = [
+            (
+                "www.example.com=wprm",
+                Ok(SiteScraper {
+                    host: "www.example.com".to_owned(),
+                    kind: ScraperKind::WpRecipeMaker,
+                }),
+            ),
+            (
+                " Example.org = tasty-recipes ",
+                Ok(SiteScraper {
+                    host: "example.org".to_owned(),
+                    kind: ScraperKind::TastyRecipes,
+                }),
+            ),
+            ("example.com", Err(())),
+            ("=json-ld", Err(())),
+            ("example.com=html", Err(())),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(
+                value.parse::<SiteScraper>().map_err(|_| ()),
+                expected,
+                "{value:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn site_scrapers_are_registered() {
+        // Pinned to microdata, the site's JSON-LD is not read first
+        let registry = ScraperRegistry::new(&["www.example.org=microdata".parse().unwrap()]);
+        let html = format!(
+            "{}{}",
+            include_str!("fixtures/json_ld_object.html"),
+            include_str!("fixtures/microdata.html")
+        );
+        let url = Url::parse("https://example.org/recipe").unwrap();
+        let json = registry.scrape(&url, &html).unwrap();
+        assert_eq!(get_name(&json).as_deref(), Some("Banana Bread"));
+        let url = Url::parse("https://example.com/recipe").unwrap();
+        let json = registry.scrape(&url, &html).unwrap();
+        assert_eq!(get_name(&json).as_deref(), Some("Tomato Soup"));
+    }
+
+    #[test]
+    fn host_scraper_takes_precedence() {
+        let mut registry = ScraperRegistry::default();
+        registry.register("www.example.org", Box::new(TitleOnly));
+        let html = include_str!("fixtures/microdata.html");
+
+        let url = Url::parse("https://example.org/banana-bread").unwrap();
+        let json = registry.scrape(&url, html).unwrap();
+        assert_eq!(
+            get_name(&json).as_deref(),
+            Some("Banana Bread | Example Kitchen")
+        );
+
+        let url = Url::parse("https://example.com/banana-bread").unwrap();
+        let json = registry.scrape(&url, html).unwrap();
+        assert_eq!(get_name(&json).as_deref(), Some("Banana Bread"));
+    }
+}
@@ -0,0 +1,88 @@
+use scraper::{ElementRef, Html};
+use serde_json::Value;
+
+use super::{all_texts, first_text, recipe_json, selector, text, RecipeScraper};
+use crate::import::text_duration_mins;
+
+/// Reads the card rendered by the WP Recipe Maker plugin
+pub struct WpRecipeMakerScraper;
+
+impl RecipeScraper for WpRecipeMakerScraper {
+    fn scrape(&self, html: &Html) -> Option<Value> {
+        let root = html.select(&selector(".wprm-recipe")).next()?;
+        recipe_json(
+            first_text(root, ".wprm-recipe-name"),
+            &root
+                .select(&selector(".wprm-recipe-ingredient"))
+                .filter_map(wprm_ingredient)
+                .collect::<Vec<_>>(),
+            all_texts(root, ".wprm-recipe-instruction-text"),
+            wprm_time(root, "prep_time"),
+            wprm_time(root, "total_time"),
+            image(root, ".wprm-recipe-image img"),
+        )
+    }
+}
+
+/// Amount, unit and name of an ingredient, leaving out the notes
+fn wprm_ingredient(ingredient: ElementRef) -> Option<String> {
+    let line = ["amount", "unit", "name"]
+        .into_iter()
+        .filter_map(|part| first_text(ingredient, &format!(".wprm-recipe-ingredient-{part}")))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if line.is_empty() {
+        Some(text(ingredient)).filter(|line| !line.is_empty())
+    } else {
+        Some(line)
+    }
+}
+
+/// WP Recipe Maker splits times into separate days, hours and minutes fields
+fn wprm_time(root: ElementRef, field: &str) -> Option<String> {
+    let part = |unit: &str| {
+        first_text(root, &format!(".wprm-recipe-{field}-{unit}"))
+            .and_then(|text| text.split_whitespace().next()?.parse::<u32>().ok())
+    };
+    let (days, hours, minutes) = (part("days"), part("hours"), part("minutes"));
+    if days.is_none() && hours.is_none() && minutes.is_none() {
+        return None;
+    }
+    Some(format!(
+        "P{}DT{}H{}M",
+        days.unwrap_or_default(),
+        hours.unwrap_or_default(),
+        minutes.unwrap_or_default()
+    ))
+}
+
+/// Reads the card rendered by the Tasty Recipes plugin
+pub struct TastyRecipesScraper;
+
+impl RecipeScraper for TastyRecipesScraper {
+    fn scrape(&self, html: &Html) -> Option<Value> {
+        let root = html.select(&selector(".tasty-recipes")).next()?;
+        let time = |css: &str| {
+            first_text(root, css)
+                .and_then(|text| text_duration_mins(&text))
+                .map(|mins| format!("PT{mins}M"))
+        };
+        recipe_json(
+            first_text(root, ".tasty-recipes-title"),
+            &all_texts(root, ".tasty-recipes-ingredients li"),
+            all_texts(root, ".tasty-recipes-instructions li"),
+            time(".tasty-recipes-prep-time"),
+            time(".tasty-recipes-total-time"),
+            image(root, ".tasty-recipes-image img"),
+        )
+    }
+}
+
+/// Image source, preferring the lazy-loading attributes `WordPress` themes add
+fn image(root: ElementRef, css: &str) -> Option<String> {
+    let img = root.select(&selector(css)).next()?;
+    ["data-lazy-src", "data-src", "src"]
+        .into_iter()
+        .find_map(|attribute| img.attr(attribute).filter(|src| !src.starts_with("data:")))
+        .map(ToOwned::to_owned)
+}
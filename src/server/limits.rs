@@ -0,0 +1,436 @@
+//! Failed logins are counted in Redis per username and per client IP as
+//! `login_failures:<kind>:<id>`. Once a counter reaches its limit the username or IP is
+//! locked out through `login_lockout:<kind>:<id>`, which holds the unix time the lockout
+//! ends. Every further failure doubles the lockout. A locked out username only turns away
+//! IPs that have failed logins themselves, so guessing at an account cannot lock its owner
+//! out. Rate limited routes count the requests of every IP in fixed windows as
+//! `rate_limit:<name>:<ip>`.
+//!
+//! The IP is the client's, which is the peer address unless the peer is a trusted proxy.
+//! Requests from those are counted by the `X-Real-IP` or `X-Forwarded-For` they set.
+
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use axum::async_trait;
+use axum::extract::{ConnectInfo, FromRequestParts, Request, State};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use chrono::Utc;
+use futures::future::BoxFuture;
+use http::request::Parts;
+use http::{Extensions, HeaderMap};
+use tokio::sync::mpsc::Sender;
+use tower::{Layer, Service};
+
+use crate::redis::{RedisCommand, RedisCommands, RedisResult};
+use color_eyre::eyre::eyre;
+
+use crate::server::routes::errors::AppError;
+
+const REAL_IP_HEADER: &str = "x-real-ip";
+const FORWARDED_FOR_HEADER: &str = "x-forwarded-for";
+
+/// Proxies, like the nginx in front of the server, whose client address headers are believed
+#[derive(Clone, Debug, Default)]
+pub struct TrustedProxies(Arc<[IpAddr]>);
+
+impl TrustedProxies {
+    pub fn new(proxies: &[IpAddr]) -> Self {
+        Self(proxies.into())
+    }
+
+    /// `X-Real-IP` if the peer is a trusted proxy, otherwise the last address in
+    /// `X-Forwarded-For` that is not one. Falls back to the peer address.
+    fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        if !self.0.contains(&peer) {
+            return peer;
+        }
+        let real_ip = headers
+            .get(REAL_IP_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok());
+        if let Some(ip) = real_ip {
+            return ip;
+        }
+        // Every proxy appends the address it got the request from, the ones before the
+        // last untrusted address could have been sent by the client
+        let forwarded_for: Vec<&str> = headers
+            .get_all(FORWARDED_FOR_HEADER)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .collect();
+        for address in forwarded_for.into_iter().rev() {
+            match address.trim().parse() {
+                Ok(ip) if !self.0.contains(&ip) => return ip,
+                Ok(_) => {}
+                Err(_) => break,
+            }
+        }
+        peer
+    }
+}
+
+/// IP of the client that sent the request, set by [`client_ip`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+impl ClientIp {
+    /// Falls back to the peer address on routers without [`client_ip`]
+    fn of(extensions: &Extensions) -> Option<Self> {
+        extensions.get::<ClientIp>().copied().or_else(|| {
+            extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(address)| ClientIp(address.ip()))
+        })
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ClientIp {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, AppError> {
+        ClientIp::of(&parts.extensions).ok_or_else(|| AppError::Other {
+            error: eyre!("Request without a client address"),
+        })
+    }
+}
+
+/// Layered on the whole router with `from_fn_with_state` and the [`TrustedProxies`],
+/// so lockouts and rate limits count clients rather than the proxy in front of them
+pub async fn client_ip(
+    State(proxies): State<TrustedProxies>,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let ip = proxies.client_ip(address.ip(), request.headers());
+    request.extensions_mut().insert(ClientIp(ip));
+    next.run(request).await
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct LoginLimits {
+    /// Failed logins to a username before it is locked out
+    pub max_user_failures: u64,
+    /// Failed logins from an IP before it is locked out
+    pub max_ip_failures: u64,
+    /// Seconds of the first lockout
+    pub lockout_secs: u64,
+    pub max_lockout_secs: u64,
+    /// Seconds failures are counted for, since the first one
+    pub failure_window_secs: u64,
+}
+
+impl LoginLimits {
+    /// Returns `None` while the failures are under the limit
+    fn lockout_secs(&self, failures: u64, max_failures: u64) -> Option<u64> {
+        let excess = failures.checked_sub(max_failures)?;
+        let factor = u32::try_from(excess)
+            .ok()
+            .and_then(|excess| 1u64.checked_shl(excess))
+            .unwrap_or(u64::MAX);
+        Some(
+            self.lockout_secs
+                .saturating_mul(factor)
+                .min(self.max_lockout_secs),
+        )
+    }
+}
+
+/// Counters of a login attempt, by the username it tries and the IP it comes from
+#[derive(Clone, Copy, Debug)]
+pub struct LoginAttempt<'a> {
+    pub username: &'a str,
+    pub ip: IpAddr,
+}
+
+impl LoginAttempt<'_> {
+    fn ids(&self) -> [(&'static str, String); 2] {
+        [
+            ("user", self.username.to_owned()),
+            ("ip", self.ip.to_string()),
+        ]
+    }
+}
+
+fn login_failures_key(kind: &str, id: &str) -> String {
+    format!("login_failures:{kind}:{id}")
+}
+
+fn login_lockout_key(kind: &str, id: &str) -> String {
+    format!("login_lockout:{kind}:{id}")
+}
+
+fn rate_limit_key(name: &str, ip: IpAddr) -> String {
+    format!("rate_limit:{name}:{ip}")
+}
+
+pub trait LoginGuard {
+    /// Returns the seconds until the attempt's IP may log in again, `None` if it is not
+    /// locked out. The username's lockout only counts when the IP has failed logins.
+    async fn login_lockout(&self, attempt: &LoginAttempt<'_>) -> RedisResult<Option<u64>>;
+    async fn record_login_failure(
+        &self,
+        limits: &LoginLimits,
+        attempt: &LoginAttempt<'_>,
+    ) -> RedisResult<()>;
+    /// Forgets the failures of the username. Those of the IP are kept, so logging in to
+    /// one's own account does not allow guessing more passwords of others.
+    async fn clear_login_failures(&self, attempt: &LoginAttempt<'_>) -> RedisResult<()>;
+}
+
+impl LoginGuard for Sender<RedisCommand> {
+    async fn login_lockout(&self, attempt: &LoginAttempt<'_>) -> RedisResult<Option<u64>> {
+        let now = Utc::now().timestamp();
+        let ip = attempt.ip.to_string();
+        let ip_failed = self.get(&login_failures_key("ip", &ip)).await?.is_some()
+            || self.get(&login_lockout_key("ip", &ip)).await?.is_some();
+        let mut lockout = None;
+        for (kind, id) in attempt.ids() {
+            if kind == "user" && !ip_failed {
+                continue;
+            }
+            let Some(until) = self.get(&login_lockout_key(kind, &id)).await? else {
+                continue;
+            };
+            let Ok(until) = until.parse::<i64>() else {
+                continue;
+            };
+            let remaining = u64::try_from(until - now).unwrap_or_default().max(1);
+            lockout = lockout.max(Some(remaining));
+        }
+        Ok(lockout)
+    }
+
+    async fn record_login_failure(
+        &self,
+        limits: &LoginLimits,
+        attempt: &LoginAttempt<'_>,
+    ) -> RedisResult<()> {
+        for (kind, id) in attempt.ids() {
+            let failures = self
+                .increment(&login_failures_key(kind, &id), limits.failure_window_secs)
+                .await?;
+            let max_failures = match kind {
+                "user" => limits.max_user_failures,
+                _ => limits.max_ip_failures,
+            };
+            if let Some(secs) = limits.lockout_secs(failures, max_failures) {
+                log::warn!(
+                    "Locking out {kind} {id:?} for {secs} seconds after {failures} failed logins"
+                );
+                let until = Utc::now().timestamp() + i64::try_from(secs).unwrap_or(i64::MAX);
+                self.set(
+                    &login_lockout_key(kind, &id),
+                    &until.to_string(),
+                    Some(secs),
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn clear_login_failures(&self, attempt: &LoginAttempt<'_>) -> RedisResult<()> {
+        self.delete(&login_failures_key("user", attempt.username))
+            .await
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RateLimit {
+    /// Distinguishes the counters of different routes
+    pub name: &'static str,
+    pub max_requests: u64,
+    pub window_secs: u64,
+}
+
+#[derive(Clone)]
+pub struct RateLimiter {
+    redis_sender: Sender<RedisCommand>,
+    limit: RateLimit,
+}
+
+impl RateLimiter {
+    pub fn new(redis_sender: Sender<RedisCommand>, limit: RateLimit) -> Self {
+        Self {
+            redis_sender,
+            limit,
+        }
+    }
+
+    /// Returns the response for requests over the limit
+    async fn check(&self, ip: IpAddr) -> Option<Response> {
+        let RateLimit {
+            name,
+            max_requests,
+            window_secs,
+        } = self.limit;
+        match self
+            .redis_sender
+            .increment(&rate_limit_key(name, ip), window_secs)
+            .await
+        {
+            Ok(requests) if requests > max_requests => {
+                log::info!("{ip} is over the rate limit of {name}");
+                Some(
+                    AppError::TooManyRequests {
+                        retry_after_secs: window_secs,
+                    }
+                    .into_response(),
+                )
+            }
+            Ok(_) => None,
+            Err(err) => {
+                log::error!("Could not count request for rate limit: {err}");
+                None
+            }
+        }
+    }
+}
+
+/// Layered with `route_layer` on routes that are expensive to serve. Requests over the limit
+/// get a 429, requests are let through if Redis fails.
+impl<S> Layer<S> for RateLimiter {
+    type Service = RateLimited<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimited {
+            limiter: self.clone(),
+            inner,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimited<S> {
+    limiter: RateLimiter,
+    inner: S,
+}
+
+impl<S> Service<Request> for RateLimited<S>
+where
+    S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Response, Infallible>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let limiter = self.limiter.clone();
+        // The clone may not be ready, the service that was polled is taken instead
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            if let Some(ClientIp(ip)) = ClientIp::of(request.extensions()) {
+                if let Some(response) = limiter.check(ip).await {
+                    return Ok(response);
+                }
+            }
+            inner.call(request).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::HeaderValue;
+
+    use super::*;
+
+    const LIMITS: LoginLimits = LoginLimits {
+        max_user_failures: 5,
+        max_ip_failures: 20,
+        lockout_secs: 60,
+        max_lockout_secs: 24 * 60 * 60,
+        failure_window_secs: 60 * 60,
+    };
+
+    #[test]
+    fn lockouts_double_up_to_the_maximum() {
+        let cases = [
+            (0, None),
+            (4, None),
+            (5, Some(60)),
+            (6, Some(120)),
+            (7, Some(240)),
+            (15, Some(61_440)),
+            (16, Some(86_400)),
+            (63, Some(86_400)),
+            (64, Some(86_400)),
+            (u64::MAX, Some(86_400)),
+        ];
+        for (failures, secs) in cases {
+            assert_eq!(LIMITS.lockout_secs(failures, 5), secs, "{failures}");
+        }
+    }
+
+    #[test]
+    fn lockouts_are_capped_without_a_maximum() {
+        let limits = LoginLimits {
+            max_lockout_secs: u64::MAX,
+            ..LIMITS
+        };
+        assert_eq!(limits.lockout_secs(5, 5), Some(60));
+        assert_eq!(limits.lockout_secs(100, 5), Some(u64::MAX));
+        assert_eq!(limits.lockout_secs(u64::MAX, 0), Some(u64::MAX));
+    }
+
+    #[test]
+    fn client_ips_are_only_taken_from_trusted_proxies() {
+        let proxy: IpAddr = "10.0.0.2".parse().unwrap();
+        let proxies = TrustedProxies::new(&[proxy, "10.0.0.3".parse().unwrap()]);
+        let headers = |real_ip: Option<&str>, forwarded_for: &[&str]| {
+            let mut headers = HeaderMap::new();
+            if let Some(value) = real_ip {
+                headers.insert(REAL_IP_HEADER, HeaderValue::from_str(value).unwrap());
+            }
+            for value in forwarded_for {
+                headers.append(FORWARDED_FOR_HEADER, HeaderValue::from_str(value).unwrap());
+            }
+            headers
+        };
+        let cases = [
+            ("203.0.113.9", Some("198.51.100.1"), &[][..], "203.0.113.9"),
+            ("10.0.0.2", None, &[][..], "10.0.0.2"),
+            ("10.0.0.2", Some("198.51.100.1"), &[][..], "198.51.100.1"),
+            ("10.0.0.2", Some(" 2001:db8::1 "), &[][..], "2001:db8::1"),
+            ("10.0.0.2", Some("garbage"), &[][..], "10.0.0.2"),
+            (
+                "10.0.0.2",
+                None,
+                &["192.0.2.7, 198.51.100.1, 10.0.0.3"][..],
+                "198.51.100.1",
+            ),
+            (
+                "10.0.0.2",
+                None,
+                &["192.0.2.7", "198.51.100.1"][..],
+                "198.51.100.1",
+            ),
+            ("10.0.0.2", None, &["garbage, 10.0.0.3"][..], "10.0.0.2"),
+        ];
+        for (peer, real_ip, forwarded_for, expected) in cases {
+            let ip = proxies.client_ip(peer.parse().unwrap(), &headers(real_ip, forwarded_for));
+            assert_eq!(
+                ip,
+                expected.parse::<IpAddr>().unwrap(),
+                "{peer} {real_ip:?}"
+            );
+        }
+        assert_eq!(
+            TrustedProxies::default().client_ip(proxy, &headers(Some("198.51.100.1"), &[])),
+            proxy
+        );
+    }
+}
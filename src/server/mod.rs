@@ -1,20 +1,33 @@
+pub mod limits;
+mod payload;
 pub mod routes;
+pub mod session;
 mod state;
-mod payload;
 
+use axum::middleware::from_fn_with_state;
 use axum::routing::get;
 use axum::{extract::State, http::StatusCode, serve, Router};
+use std::net::SocketAddr;
 use thiserror::Error;
 use tokio::net::{TcpListener, ToSocketAddrs};
 
 use crate::server::routes::login::LoginRouter;
 
+use self::limits::{client_ip, RateLimit, RateLimiter, TrustedProxies};
+use self::routes::auth::slide_session;
+use self::routes::categories::CategoryRouter;
+use self::routes::households::HouseholdRouter;
 use self::routes::ingredients::IngredientRouter;
+use self::routes::locations::LocationRouter;
+use self::routes::meal_plans::MealPlanRouter;
 use self::routes::pantry_items::PantryItemRouter;
 use self::routes::parse_ingredients::ParseIngredientsRouter;
 use self::routes::parse_recipe_link::ParsedRecipeLinkRouter;
+use self::routes::products::ProductRouter;
 use self::routes::recipe_ingredients::RecipeIngredientRouter;
 use self::routes::recipes::RecipeRouter;
+use self::routes::sessions::SessionRouter;
+use self::routes::shopping_lists::ShoppingListRouter;
 use self::routes::users::UserRouter;
 pub use state::AppState;
 
@@ -30,11 +43,21 @@ pub enum ServerError {
 
 pub struct Server {
     state: AppState,
+    parse_recipe_link_limit: RateLimit,
+    trusted_proxies: TrustedProxies,
 }
 
 impl Server {
-    pub fn new(state: AppState) -> Self {
-        Self { state }
+    pub fn new(
+        state: AppState,
+        parse_recipe_link_limit: RateLimit,
+        trusted_proxies: TrustedProxies,
+    ) -> Self {
+        Self {
+            state,
+            parse_recipe_link_limit,
+            trusted_proxies,
+        }
     }
 
     pub async fn run(self, bind_address: impl ToSocketAddrs) -> ServerResult<()> {
@@ -45,20 +68,42 @@ impl Server {
             }
         })?;
 
+        let link_limiter = RateLimiter::new(
+            self.state.redis_sender.clone(),
+            self.parse_recipe_link_limit,
+        );
         let router: Router = Router::new()
             .route("/health", get(health))
             .nest("/login", LoginRouter::router())
+            .nest("/categories", CategoryRouter::router())
+            .nest("/households", HouseholdRouter::router())
             .nest("/ingredients", IngredientRouter::router())
+            .nest("/locations", LocationRouter::router())
+            .nest("/meal_plans", MealPlanRouter::router())
             .nest("/pantry_items", PantryItemRouter::router())
             .nest("/parse_ingredients", ParseIngredientsRouter::router())
-            .nest("/parse_recipe_link", ParsedRecipeLinkRouter::router())
-            .nest("/recipes", RecipeRouter::router())
+            .nest(
+                "/parse_recipe_link",
+                ParsedRecipeLinkRouter::router().route_layer(link_limiter.clone()),
+            )
+            .nest("/products", ProductRouter::router())
+            .nest("/recipes", RecipeRouter::router(link_limiter))
             .nest("/recipe_ingredients", RecipeIngredientRouter::router())
+            .nest("/sessions", SessionRouter::router())
+            .nest("/shopping_lists", ShoppingListRouter::router())
             .nest("/users", UserRouter::router())
+            .layer(from_fn_with_state(self.state.clone(), slide_session))
+            .layer(from_fn_with_state(self.trusted_proxies, client_ip))
             .with_state(self.state)
             .fallback(Server::fallback);
 
-        serve(listener, router).await.map_err(|err| {
+        // Client addresses key the login lockouts and rate limits
+        serve(
+            listener,
+            router.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .map_err(|err| {
             log::error!("{}", err.to_string());
             ServerError::RouterServeError {
                 message: err.to_string(),
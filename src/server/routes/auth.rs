@@ -0,0 +1,153 @@
+use axum::async_trait;
+use axum::extract::{FromRequestParts, Request, State};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum_extra::extract::CookieJar;
+use http::header::AUTHORIZATION;
+use http::request::Parts;
+use http::{HeaderValue, Method};
+use uuid::Uuid;
+
+use crate::database::errors::GetError;
+use crate::server::routes::errors::AppError;
+use crate::server::routes::utils::verify_password;
+use crate::server::session::{SessionStore, COOKIE_KEY};
+use crate::server::state::AppState;
+
+const BEARER_PREFIX: &str = "Bearer ";
+
+/// API tokens are the token id and its secret, sent as `Authorization: Bearer <id>.<secret>`
+pub fn api_token(id: Uuid, secret: &str) -> String {
+    format!("{}.{secret}", id.simple())
+}
+
+/// User of the request's API token or session cookie. Requests without valid credentials
+/// get a 401, writes with a read-only API token get a 403.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CurrentUser(pub Uuid);
+
+#[async_trait]
+impl FromRequestParts<AppState> for CurrentUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, AppError> {
+        // The session is looked up once per request, even if several extractors need it
+        if let Some(user) = parts.extensions.get::<CurrentUser>() {
+            return Ok(*user);
+        }
+        let user_id = if let Some(header) = parts.headers.get(AUTHORIZATION) {
+            api_token_user(state, header, &parts.method).await?
+        } else {
+            let jar = CookieJar::from_headers(&parts.headers);
+            let session_id = jar.get(COOKIE_KEY).ok_or(AppError::Unauthorized)?;
+            state
+                .get_sessions_user(session_id.value_trimmed())
+                .await?
+                .ok_or(AppError::Unauthorized)?
+        };
+        let user = CurrentUser(user_id);
+        parts.extensions.insert(user);
+        Ok(user)
+    }
+}
+
+async fn api_token_user(
+    state: &AppState,
+    header: &HeaderValue,
+    method: &Method,
+) -> Result<Uuid, AppError> {
+    let (id, secret) = header
+        .to_str()
+        .ok()
+        .and_then(|value| value.strip_prefix(BEARER_PREFIX))
+        .and_then(|token| token.trim().split_once('.'))
+        .ok_or(AppError::Unauthorized)?;
+    let id = Uuid::try_parse(id).map_err(|_| AppError::Unauthorized)?;
+    let token = match state.db_client.get_api_token(id).await {
+        Ok(token) => token,
+        Err(GetError::NotFound { .. }) => return Err(AppError::Unauthorized),
+        Err(err) => return Err(err.into()),
+    };
+    // Hashing is slow by design, it must not hold up the other requests of the worker
+    let secret = secret.to_owned();
+    let secret_hash = token.secret_hash.clone();
+    let verified = tokio::task::spawn_blocking(move || verify_password(&secret, &secret_hash))
+        .await
+        .map_err(|err| AppError::Other { error: err.into() })?;
+    if !verified {
+        log::info!("Wrong secret for API token {id:?}");
+        return Err(AppError::Unauthorized);
+    }
+    if token.read_only && !method.is_safe() {
+        log::info!("Read-only API token {id:?} used for {method}");
+        return Err(AppError::Forbidden);
+    }
+    Ok(token.user_id)
+}
+
+/// User of the request's session cookie. Requests with an API token get a 403, so a leaked
+/// token cannot change the password or mint more tokens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SessionUser(pub Uuid);
+
+#[async_trait]
+impl FromRequestParts<AppState> for SessionUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, AppError> {
+        let CurrentUser(user_id) = CurrentUser::from_request_parts(parts, state).await?;
+        if parts.headers.contains_key(AUTHORIZATION) {
+            log::info!("API token of user {user_id:?} used where a session is required");
+            return Err(AppError::Forbidden);
+        }
+        Ok(SessionUser(user_id))
+    }
+}
+
+/// Authenticated user that is an admin, other users get a 403
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AdminUser(pub Uuid);
+
+#[async_trait]
+impl FromRequestParts<AppState> for AdminUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, AppError> {
+        let CurrentUser(user_id) = CurrentUser::from_request_parts(parts, state).await?;
+        if state.user_is_admin(user_id).await? {
+            return Ok(AdminUser(user_id));
+        }
+        log::info!("User {user_id:?} is not an admin");
+        Err(AppError::Forbidden)
+    }
+}
+
+/// Keeps sessions that are in use alive, pushing back both their expiry in Redis
+/// and the `Max-Age` of their cookie
+pub async fn slide_session(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(token) = jar
+        .get(COOKIE_KEY)
+        .map(|cookie| cookie.value_trimmed().to_owned())
+    else {
+        return next.run(request).await;
+    };
+    let response = next.run(request).await;
+    // Sessions that the request logged out or replaced are not refreshed
+    match state
+        .redis_sender
+        .refresh_session(&token, state.session_settings.ttl_secs)
+        .await
+    {
+        Ok(true) => (jar.add(state.session_settings.cookie(token)), response).into_response(),
+        Ok(false) => response,
+        Err(err) => {
+            log::error!("Could not refresh session: {err}");
+            response
+        }
+    }
+}
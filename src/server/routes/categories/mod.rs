@@ -0,0 +1,99 @@
+mod payload;
+
+use axum::extract::Query;
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    routing::get,
+    Router,
+};
+use color_eyre::eyre::eyre;
+use uuid::Uuid;
+
+use crate::server::routes::auth::{AdminUser, CurrentUser};
+use crate::server::routes::errors::AppError;
+use crate::server::state::AppState;
+use payload::{
+    CategoryListResponse, CategoryResponse, CreatePayload, ListQueryParams, UpdatePayload,
+};
+
+pub struct CategoryRouter {}
+
+impl CategoryRouter {
+    pub fn router() -> Router<AppState> {
+        Router::new()
+            .route("/", get(CategoryRouter::list).post(CategoryRouter::create))
+            .route(
+                "/:id",
+                get(CategoryRouter::get)
+                    .put(CategoryRouter::update)
+                    .delete(CategoryRouter::delete),
+            )
+    }
+
+    async fn create(
+        State(state): State<AppState>,
+        _user: CurrentUser,
+        Json(payload): Json<CreatePayload>,
+    ) -> Result<(StatusCode, Json<CategoryResponse>), AppError> {
+        let category = state.db_client.create_category(payload.into()).await?;
+        log::info!("Category with id {:?} created", category.id.to_string());
+        Ok((StatusCode::CREATED, Json(category.into())))
+    }
+
+    async fn list(
+        State(state): State<AppState>,
+        _user: CurrentUser,
+        Query(query_params): Query<ListQueryParams>,
+    ) -> Result<(StatusCode, Json<CategoryListResponse>), AppError> {
+        if query_params.name.is_some() && query_params.name_contains.is_some() {
+            return Err(AppError::UnprocessableEntity {
+                error: eyre!("Only one of name or name_contains can be defined."),
+            });
+        }
+        let list_params = query_params.into();
+        let categories: Vec<CategoryResponse> =
+            state.db_client.list_categories(&list_params).await?.into();
+        log::info!("{:?} categories collected", categories.len());
+        let metadata = state
+            .db_client
+            .get_categories_metadata(&list_params)
+            .await?
+            .into();
+        Ok((
+            StatusCode::OK,
+            Json(CategoryListResponse::from(categories, metadata)),
+        ))
+    }
+
+    async fn get(
+        State(state): State<AppState>,
+        _user: CurrentUser,
+        Path(id): Path<Uuid>,
+    ) -> Result<(StatusCode, Json<CategoryResponse>), AppError> {
+        let category = state.db_client.get_category(id).await?;
+        log::info!("Got category with id {:?}", category.id);
+        Ok((StatusCode::OK, Json(category.into())))
+    }
+
+    async fn update(
+        State(state): State<AppState>,
+        _admin: AdminUser,
+        Path(id): Path<Uuid>,
+        Json(payload): Json<UpdatePayload>,
+    ) -> Result<(StatusCode, Json<CategoryResponse>), AppError> {
+        let category = state.db_client.update_category(id, payload.into()).await?;
+        log::info!("Updated category with id {id:?}");
+        Ok((StatusCode::OK, Json(category.into())))
+    }
+
+    async fn delete(
+        State(state): State<AppState>,
+        _admin: AdminUser,
+        Path(id): Path<Uuid>,
+    ) -> Result<StatusCode, AppError> {
+        state.db_client.delete_category(id).await?;
+        log::info!("Deleted category with id {:?}", id);
+        Ok(StatusCode::NO_CONTENT)
+    }
+}
@@ -2,20 +2,28 @@ use axum::response::{IntoResponse, Response};
 use axum::Json;
 use color_eyre::eyre::eyre;
 use color_eyre::Report as AnyError;
-use http::StatusCode;
+use http::header::RETRY_AFTER;
+use http::{HeaderValue, StatusCode};
 use serde::Serialize;
 use thiserror::Error;
 use uuid::Uuid;
 
-use crate::database::errors::{CreateError, DeleteError, GetError, ListError, UpdateError};
+use crate::database::errors::{
+    CreateError, DeleteError, GetError, ListError, MergeError, UpdateError,
+};
+use crate::import::ImportError;
+use crate::oidc::OidcError;
 use crate::redis::RedisError;
 use crate::server::routes::parse_recipe_link::GetRecipeJsonError;
 
 #[derive(Debug)]
 pub enum AppError {
     Unauthorized,
+    Forbidden,
+    TooManyRequests { retry_after_secs: u64 },
     AlreadyExists { id: Uuid },
     NotFound { id: String },
+    Conflict { error: AnyError },
     UnprocessableEntity { error: AnyError },
     Other { error: AnyError },
 }
@@ -28,16 +36,29 @@ impl IntoResponse for AppError {
             message: String,
         }
 
+        let retry_after = match self {
+            AppError::TooManyRequests { retry_after_secs } => Some(retry_after_secs),
+            _ => None,
+        };
         let (status, message) = match self {
             AppError::Unauthorized => {
                 log::info!("User unauthorized");
                 (StatusCode::UNAUTHORIZED, "User is unauthorized".to_owned())
             }
+            AppError::Forbidden => (
+                StatusCode::FORBIDDEN,
+                "User is not allowed to do this".to_owned(),
+            ),
+            AppError::TooManyRequests { retry_after_secs } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                format!("Too many requests, try again in {retry_after_secs} seconds"),
+            ),
             AppError::AlreadyExists { id } => (
                 StatusCode::CONFLICT,
                 format!("Item with id {id} already exists"),
             ),
             AppError::NotFound { id } => (StatusCode::NOT_FOUND, format!("Item {id} not found")),
+            AppError::Conflict { error } => (StatusCode::CONFLICT, error.to_string()),
             AppError::UnprocessableEntity { error } => {
                 (StatusCode::UNPROCESSABLE_ENTITY, error.to_string())
             }
@@ -47,7 +68,13 @@ impl IntoResponse for AppError {
             ),
         };
 
-        (status, Json(ErrorResponse { message })).into_response()
+        let mut response = (status, Json(ErrorResponse { message })).into_response();
+        if let Some(secs) = retry_after {
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(secs));
+        }
+        response
     }
 }
 
@@ -71,6 +98,19 @@ impl From<ListError> for AppError {
     }
 }
 
+impl From<MergeError> for AppError {
+    fn from(val: MergeError) -> Self {
+        log::error!("{}", val);
+        match val {
+            MergeError::NotFound { id } => AppError::NotFound { id: id.to_string() },
+            MergeError::Conflict { id: _, reason } => AppError::Conflict {
+                error: eyre!(reason),
+            },
+            MergeError::Unexpected { id: _, error } => AppError::Other { error },
+        }
+    }
+}
+
 impl From<GetError> for AppError {
     fn from(val: GetError) -> Self {
         log::error!("{}", val);
@@ -86,6 +126,12 @@ impl From<UpdateError> for AppError {
         log::error!("{}", val);
         match val {
             UpdateError::NotFound { id } => AppError::NotFound { id: id.to_string() },
+            UpdateError::Conflict { id: _, reason } => AppError::Conflict {
+                error: eyre!(reason),
+            },
+            UpdateError::Invalid { id: _, reason } => AppError::UnprocessableEntity {
+                error: eyre!(reason),
+            },
             UpdateError::Unexpected { id: _, error } => AppError::Other { error },
         }
     }
@@ -112,6 +158,27 @@ impl From<RedisError> for AppError {
     }
 }
 
+impl From<ImportError> for AppError {
+    fn from(val: ImportError) -> Self {
+        log::error!("{}", val);
+        AppError::UnprocessableEntity { error: val.into() }
+    }
+}
+
+impl From<OidcError> for AppError {
+    fn from(val: OidcError) -> Self {
+        log::error!("{}", val);
+        match val {
+            OidcError::TokenRequest { .. } | OidcError::InvalidToken { .. } => {
+                AppError::Unauthorized
+            }
+            OidcError::Config | OidcError::Discovery { .. } | OidcError::Request { .. } => {
+                AppError::Other { error: val.into() }
+            }
+        }
+    }
+}
+
 impl From<GetRecipeJsonError> for AppError {
     fn from(val: GetRecipeJsonError) -> Self {
         log::error!("{}", val);
@@ -128,8 +195,8 @@ impl From<GetRecipeJsonError> for AppError {
 
 #[derive(Error, Debug)]
 pub enum VerifyError {
-    #[error("Incorrect username or password")]
-    Unauthorized,
+    #[error("User is not allowed to access the item")]
+    Forbidden,
     #[error("Item with id {id} not found")]
     NotFound { id: Uuid },
     #[error("{error}")]
@@ -140,7 +207,7 @@ impl From<VerifyError> for AppError {
     fn from(val: VerifyError) -> Self {
         log::error!("{}", val);
         match val {
-            VerifyError::Unauthorized => AppError::Unauthorized,
+            VerifyError::Forbidden => AppError::Forbidden,
             VerifyError::NotFound { id: user_id } => AppError::NotFound {
                 id: user_id.to_string(),
             },
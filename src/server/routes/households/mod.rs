@@ -0,0 +1,221 @@
+mod payload;
+
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    routing::get,
+    Router,
+};
+use color_eyre::eyre::eyre;
+use uuid::Uuid;
+
+use crate::database::households::dto::HouseholdRole;
+use crate::server::routes::auth::CurrentUser;
+use crate::server::routes::errors::{AppError, VerifyError};
+use crate::server::state::AppState;
+use payload::{
+    CreateMemberPayload, CreatePayload, HouseholdListResponse, HouseholdMemberListResponse,
+    HouseholdMemberResponse, HouseholdResponse, ListQueryParams, UpdateMemberPayload,
+    UpdatePayload,
+};
+
+pub struct HouseholdRouter {}
+
+impl HouseholdRouter {
+    pub fn router() -> Router<AppState> {
+        Router::new()
+            .route(
+                "/",
+                get(HouseholdRouter::list).post(HouseholdRouter::create),
+            )
+            .route(
+                "/:id",
+                get(HouseholdRouter::get)
+                    .put(HouseholdRouter::update)
+                    .delete(HouseholdRouter::delete),
+            )
+            .route(
+                "/:id/members",
+                get(HouseholdRouter::list_members).post(HouseholdRouter::add_member),
+            )
+            .route(
+                "/:id/members/:user_id",
+                get(HouseholdRouter::get_member)
+                    .put(HouseholdRouter::update_member)
+                    .delete(HouseholdRouter::remove_member),
+            )
+    }
+
+    async fn create(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Json(payload): Json<CreatePayload>,
+    ) -> Result<(StatusCode, Json<HouseholdResponse>), AppError> {
+        let household = state
+            .db_client
+            .create_household(payload.into_dto(user_id))
+            .await?;
+        log::info!("Household with id {:?} created", household.id.to_string());
+        Ok((StatusCode::CREATED, Json(household.into())))
+    }
+
+    async fn list(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Query(query_params): Query<ListQueryParams>,
+    ) -> Result<(StatusCode, Json<HouseholdListResponse>), AppError> {
+        let list_params = query_params.into_dto(user_id);
+        let households: Vec<HouseholdResponse> =
+            state.db_client.list_households(&list_params).await?.into();
+        log::info!("{:?} households collected", households.len());
+        let metadata = state
+            .db_client
+            .get_households_metadata(&list_params)
+            .await?
+            .into();
+        Ok((
+            StatusCode::OK,
+            Json(HouseholdListResponse::from(households, metadata)),
+        ))
+    }
+
+    async fn get(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+    ) -> Result<(StatusCode, Json<HouseholdResponse>), AppError> {
+        verify_member(&state, id, user_id).await?;
+        let household = state.db_client.get_household(id).await?;
+        log::info!("Got household with id {:?}", household.id);
+        Ok((StatusCode::OK, Json(household.into())))
+    }
+
+    async fn update(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+        Json(payload): Json<UpdatePayload>,
+    ) -> Result<(StatusCode, Json<HouseholdResponse>), AppError> {
+        verify_owner(&state, id, user_id).await?;
+        let household = state.db_client.update_household(id, payload.into()).await?;
+        log::info!("Updated household with id {id:?}");
+        Ok((StatusCode::OK, Json(household.into())))
+    }
+
+    async fn delete(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+    ) -> Result<StatusCode, AppError> {
+        verify_owner(&state, id, user_id).await?;
+        state.db_client.delete_household(id).await?;
+        log::info!("Deleted household with id {:?}", id);
+        Ok(StatusCode::NO_CONTENT)
+    }
+
+    async fn list_members(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+    ) -> Result<(StatusCode, Json<HouseholdMemberListResponse>), AppError> {
+        verify_member(&state, id, user_id).await?;
+        let members: HouseholdMemberListResponse =
+            state.db_client.list_household_members(id).await?.into();
+        log::info!(
+            "{:?} members of household {id:?} collected",
+            members.items.len()
+        );
+        Ok((StatusCode::OK, Json(members)))
+    }
+
+    async fn add_member(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+        Json(payload): Json<CreateMemberPayload>,
+    ) -> Result<(StatusCode, Json<HouseholdMemberResponse>), AppError> {
+        verify_owner(&state, id, user_id).await?;
+        state.db_client.get_user(payload.user_id).await?;
+        let member = state
+            .db_client
+            .create_household_member(payload.into_dto(id))
+            .await?;
+        log::info!("User {:?} added to household {id:?}", member.user_id);
+        Ok((StatusCode::CREATED, Json(member.into())))
+    }
+
+    async fn get_member(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path((id, member_id)): Path<(Uuid, Uuid)>,
+    ) -> Result<(StatusCode, Json<HouseholdMemberResponse>), AppError> {
+        verify_member(&state, id, user_id).await?;
+        let member = state.db_client.get_household_member(id, member_id).await?;
+        Ok((StatusCode::OK, Json(member.into())))
+    }
+
+    async fn update_member(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path((id, member_id)): Path<(Uuid, Uuid)>,
+        Json(payload): Json<UpdateMemberPayload>,
+    ) -> Result<(StatusCode, Json<HouseholdMemberResponse>), AppError> {
+        verify_owner(&state, id, user_id).await?;
+        if member_id == user_id {
+            return Err(AppError::UnprocessableEntity {
+                error: eyre!("Owners cannot change their own role."),
+            });
+        }
+        let member = state
+            .db_client
+            .update_household_member(id, member_id, payload.role.into())
+            .await?;
+        log::info!("Updated role of user {member_id:?} in household {id:?}");
+        Ok((StatusCode::OK, Json(member.into())))
+    }
+
+    /// Owners can remove other members, everyone else can only leave
+    async fn remove_member(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path((id, member_id)): Path<(Uuid, Uuid)>,
+    ) -> Result<StatusCode, AppError> {
+        let role = verify_member(&state, id, user_id).await?;
+        if member_id == user_id && role == HouseholdRole::Owner {
+            return Err(AppError::UnprocessableEntity {
+                error: eyre!("Owners cannot leave their household, delete it instead."),
+            });
+        }
+        if member_id != user_id && role != HouseholdRole::Owner {
+            return Err(AppError::Forbidden);
+        }
+        state
+            .db_client
+            .delete_household_member(id, member_id)
+            .await?;
+        log::info!("User {member_id:?} removed from household {id:?}");
+        Ok(StatusCode::NO_CONTENT)
+    }
+}
+
+async fn verify_member(
+    state: &AppState,
+    household_id: Uuid,
+    user_id: Uuid,
+) -> Result<HouseholdRole, VerifyError> {
+    state
+        .household_role(household_id, user_id)
+        .await?
+        .ok_or(VerifyError::Forbidden)
+}
+
+async fn verify_owner(
+    state: &AppState,
+    household_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), VerifyError> {
+    if verify_member(state, household_id, user_id).await? == HouseholdRole::Owner {
+        return Ok(());
+    }
+    Err(VerifyError::Forbidden)
+}
@@ -0,0 +1,189 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::database::households::dto::{
+    CreateDto, CreateMemberDto, HouseholdDto, HouseholdMemberDto, HouseholdMemberJoinDto,
+    HouseholdMembersListDto, HouseholdRole, HouseholdsListDto, ListParamsDto, UpdateDto,
+};
+use crate::server::payload::{MetadataResponse, DEFAULT_PER_PAGE};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RolePayload {
+    Owner,
+    Member,
+    Viewer,
+}
+
+impl From<RolePayload> for HouseholdRole {
+    fn from(val: RolePayload) -> Self {
+        match val {
+            RolePayload::Owner => HouseholdRole::Owner,
+            RolePayload::Member => HouseholdRole::Member,
+            RolePayload::Viewer => HouseholdRole::Viewer,
+        }
+    }
+}
+
+impl From<HouseholdRole> for RolePayload {
+    fn from(val: HouseholdRole) -> Self {
+        match val {
+            HouseholdRole::Owner => RolePayload::Owner,
+            HouseholdRole::Member => RolePayload::Member,
+            HouseholdRole::Viewer => RolePayload::Viewer,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CreatePayload {
+    pub name: String,
+}
+
+impl CreatePayload {
+    pub fn into_dto(self, owner_id: Uuid) -> CreateDto {
+        CreateDto {
+            name: self.name,
+            owner_id,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct UpdatePayload {
+    pub name: String,
+}
+
+impl From<UpdatePayload> for UpdateDto {
+    fn from(val: UpdatePayload) -> Self {
+        UpdateDto { name: val.name }
+    }
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct ListQueryParams {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+}
+
+impl ListQueryParams {
+    pub fn into_dto(self, user_id: Uuid) -> ListParamsDto {
+        ListParamsDto {
+            user_id: Some(user_id),
+            limit: self.per_page.unwrap_or(DEFAULT_PER_PAGE),
+            offset: self.per_page.unwrap_or(DEFAULT_PER_PAGE) * (self.page.unwrap_or(1) - 1),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct HouseholdResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl From<HouseholdDto> for HouseholdResponse {
+    fn from(val: HouseholdDto) -> Self {
+        HouseholdResponse {
+            id: val.id,
+            name: val.name,
+            created_at: val.created_at,
+            updated_at: val.updated_at,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct HouseholdListResponse {
+    #[serde(rename = "_metadata")]
+    pub metadata: MetadataResponse,
+    pub items: Vec<HouseholdResponse>,
+}
+
+impl From<HouseholdsListDto> for Vec<HouseholdResponse> {
+    fn from(val: HouseholdsListDto) -> Self {
+        val.items.into_iter().map(Into::into).collect()
+    }
+}
+
+impl HouseholdListResponse {
+    pub fn from(items: Vec<HouseholdResponse>, metadata: MetadataResponse) -> Self {
+        HouseholdListResponse { metadata, items }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CreateMemberPayload {
+    pub user_id: Uuid,
+    pub role: RolePayload,
+}
+
+impl CreateMemberPayload {
+    pub fn into_dto(self, household_id: Uuid) -> CreateMemberDto {
+        CreateMemberDto {
+            household_id,
+            user_id: self.user_id,
+            role: self.role.into(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct UpdateMemberPayload {
+    pub role: RolePayload,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct HouseholdMemberResponse {
+    pub id: Uuid,
+    pub household_id: Uuid,
+    pub user_id: Uuid,
+    pub user_name: Option<String>,
+    pub role: RolePayload,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl From<HouseholdMemberDto> for HouseholdMemberResponse {
+    fn from(val: HouseholdMemberDto) -> Self {
+        HouseholdMemberResponse {
+            id: val.id,
+            household_id: val.household_id,
+            user_id: val.user_id,
+            user_name: None,
+            role: val.role.into(),
+            created_at: val.created_at,
+            updated_at: val.updated_at,
+        }
+    }
+}
+
+impl From<HouseholdMemberJoinDto> for HouseholdMemberResponse {
+    fn from(val: HouseholdMemberJoinDto) -> Self {
+        HouseholdMemberResponse {
+            id: val.id,
+            household_id: val.household_id,
+            user_id: val.user_id,
+            user_name: Some(val.user_name),
+            role: val.role.into(),
+            created_at: val.created_at,
+            updated_at: val.updated_at,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct HouseholdMemberListResponse {
+    pub items: Vec<HouseholdMemberResponse>,
+}
+
+impl From<HouseholdMembersListDto> for HouseholdMemberListResponse {
+    fn from(val: HouseholdMembersListDto) -> Self {
+        HouseholdMemberListResponse {
+            items: val.items.into_iter().map(Into::into).collect(),
+        }
+    }
+}
@@ -4,17 +4,20 @@ use axum::extract::Query;
 use axum::{
     extract::{Json, Path, State},
     http::StatusCode,
-    routing::get,
+    routing::{delete, get, post},
     Router,
 };
-use axum_extra::extract::CookieJar;
 use color_eyre::eyre::eyre;
 use uuid::Uuid;
 
+use crate::server::routes::auth::{AdminUser, CurrentUser};
 use crate::server::routes::errors::AppError;
-use crate::server::routes::COOKIE_KEY;
 use crate::server::state::AppState;
-use payload::{CreatePayload, IngredientListResponse, IngredientResponse, ListQueryParams};
+use payload::{
+    AliasListQueryParams, AliasListResponse, AliasPayload, AliasResponse, CreatePayload,
+    IngredientListResponse, IngredientResponse, ListQueryParams, MergePayload, MergeResponse,
+    UpdatePayload,
+};
 
 pub struct IngredientRouter {}
 
@@ -27,28 +30,35 @@ impl IngredientRouter {
             )
             .route(
                 "/:id",
-                get(IngredientRouter::get).delete(IngredientRouter::delete),
+                get(IngredientRouter::get)
+                    .put(IngredientRouter::update)
+                    .delete(IngredientRouter::delete),
+            )
+            .route("/:id/merge", post(IngredientRouter::merge))
+            .route(
+                "/:id/aliases",
+                get(IngredientRouter::list_aliases).post(IngredientRouter::create_alias),
+            )
+            .route(
+                "/:id/aliases/:alias_id",
+                delete(IngredientRouter::delete_alias),
             )
     }
 
     async fn create(
         State(state): State<AppState>,
-        jar: CookieJar,
+        _user: CurrentUser,
         Json(payload): Json<CreatePayload>,
     ) -> Result<(StatusCode, Json<IngredientResponse>), AppError> {
-        if let Some(session_id) = jar.get(COOKIE_KEY) {
-            if state.session_is_valid(session_id.value_trimmed()).await? {
-                let ingredient = state.db_client.create_ingredient(payload.into()).await?;
-                log::info!("Ingredient with id {:?} created", ingredient.id.to_string());
-                return Ok((StatusCode::CREATED, Json(ingredient.into())));
-            }
-        }
-        Err(AppError::Unauthorized)
+        let ingredient = state.db_client.create_ingredient(payload.into()).await?;
+        state.ingredient_matcher.clear();
+        log::info!("Ingredient with id {:?} created", ingredient.id.to_string());
+        Ok((StatusCode::CREATED, Json(ingredient.into())))
     }
 
     async fn list(
         State(state): State<AppState>,
-        jar: CookieJar,
+        _user: CurrentUser,
         Query(query_params): Query<ListQueryParams>,
     ) -> Result<(StatusCode, Json<IngredientListResponse>), AppError> {
         if query_params.name.is_some() && query_params.name_contains.is_some() {
@@ -56,55 +66,163 @@ impl IngredientRouter {
                 error: eyre!("Only one of name or name_contains can be defined."),
             });
         }
-        if let Some(session_id) = jar.get(COOKIE_KEY) {
-            if state.session_is_valid(session_id.value_trimmed()).await? {
-                let list_params = query_params.into();
-                let ingredients: Vec<IngredientResponse> =
-                    state.db_client.list_ingredients(&list_params).await?.into();
-                log::info!("{:?} ingredients collected", ingredients.len());
-                let metadata = state
-                    .db_client
-                    .get_ingredients_metadata(&list_params)
-                    .await?
-                    .into();
-                return Ok((
-                    StatusCode::OK,
-                    Json(IngredientListResponse::from(ingredients, metadata)),
-                ));
-            }
-        }
-        Err(AppError::Unauthorized)
+        let list_params = query_params.into();
+        let ingredients: Vec<IngredientResponse> =
+            state.db_client.list_ingredients(&list_params).await?.into();
+        log::info!("{:?} ingredients collected", ingredients.len());
+        let metadata = state
+            .db_client
+            .get_ingredients_metadata(&list_params)
+            .await?
+            .into();
+        Ok((
+            StatusCode::OK,
+            Json(IngredientListResponse::from(ingredients, metadata)),
+        ))
     }
 
     async fn get(
         State(state): State<AppState>,
-        jar: CookieJar,
+        _user: CurrentUser,
         Path(id): Path<Uuid>,
     ) -> Result<(StatusCode, Json<IngredientResponse>), AppError> {
-        if let Some(session_id) = jar.get(COOKIE_KEY) {
-            if state.session_is_valid(session_id.value_trimmed()).await? {
-                let ingredient = state.db_client.get_ingredient(id).await?;
-                log::info!("Got ingredient with id {:?}", ingredient.id);
-                return Ok((StatusCode::OK, Json(ingredient.into())));
-            }
+        let ingredient = state.db_client.get_ingredient(id).await?;
+        log::info!("Got ingredient with id {:?}", ingredient.id);
+        Ok((StatusCode::OK, Json(ingredient.into())))
+    }
+
+    async fn update(
+        State(state): State<AppState>,
+        _admin: AdminUser,
+        Path(id): Path<Uuid>,
+        Json(payload): Json<UpdatePayload>,
+    ) -> Result<(StatusCode, Json<IngredientResponse>), AppError> {
+        if payload
+            .freezer_shelf_life_days
+            .is_some_and(|days| days <= 0)
+        {
+            return Err(AppError::UnprocessableEntity {
+                error: eyre!("freezer_shelf_life_days must be positive."),
+            });
         }
-        Err(AppError::Unauthorized)
+        let ingredient = state
+            .db_client
+            .update_ingredient(id, payload.into())
+            .await?;
+        state.ingredient_matcher.clear();
+        log::info!("Updated ingredient with id {id:?}");
+        Ok((StatusCode::OK, Json(ingredient.into())))
     }
 
     async fn delete(
         State(state): State<AppState>,
-        jar: CookieJar,
+        _admin: AdminUser,
         Path(id): Path<Uuid>,
     ) -> Result<StatusCode, AppError> {
-        if let Some(session_id) = jar.get(COOKIE_KEY) {
-            if let Some(user_id) = state.get_sessions_user(session_id.value_trimmed()).await? {
-                if state.user_is_admin(user_id).await? {
-                    state.db_client.delete_ingredient(id).await?;
-                    log::info!("Deleted ingredient with id {:?}", id);
-                    return Ok(StatusCode::NO_CONTENT);
-                }
-            }
+        state.db_client.delete_ingredient(id).await?;
+        state.ingredient_matcher.clear();
+        log::info!("Deleted ingredient with id {:?}", id);
+        Ok(StatusCode::NO_CONTENT)
+    }
+
+    /// Folds duplicate ingredients into this one, moving their pantry items and recipe
+    /// ingredients over and keeping their names as aliases
+    async fn merge(
+        State(state): State<AppState>,
+        AdminUser(user_id): AdminUser,
+        Path(id): Path<Uuid>,
+        Json(payload): Json<MergePayload>,
+    ) -> Result<Json<MergeResponse>, AppError> {
+        if payload.duplicate_ids.is_empty() {
+            return Err(AppError::UnprocessableEntity {
+                error: eyre!("At least one duplicate ingredient is required."),
+            });
+        }
+        if payload.duplicate_ids.contains(&id) {
+            return Err(AppError::UnprocessableEntity {
+                error: eyre!("An ingredient cannot be merged into itself."),
+            });
+        }
+        let merged = state
+            .db_client
+            .merge_ingredients(id, payload.duplicate_ids, user_id)
+            .await?;
+        state.ingredient_matcher.clear();
+        log::info!(
+            "Merged ingredients {:?} into ingredient with id {:?}",
+            merged.merged_ids,
+            id.to_string()
+        );
+        Ok(Json(merged.into()))
+    }
+
+    /// Confirms that the alias names this ingredient, so parsed names match it exactly.
+    /// Aliases change the matches of every user, only admins may create them.
+    async fn create_alias(
+        State(state): State<AppState>,
+        AdminUser(user_id): AdminUser,
+        Path(id): Path<Uuid>,
+        Json(payload): Json<AliasPayload>,
+    ) -> Result<(StatusCode, Json<AliasResponse>), AppError> {
+        let request = payload.into_dto(id, user_id);
+        if request.alias.is_empty() {
+            return Err(AppError::UnprocessableEntity {
+                error: eyre!("Alias must contain letters or digits."),
+            });
+        }
+        state.db_client.get_ingredient(id).await?;
+        let alias = state.db_client.create_ingredient_alias(request).await?;
+        state.ingredient_matcher.clear();
+        log::info!(
+            "Alias {:?} created for ingredient with id {:?}",
+            alias.alias,
+            id.to_string()
+        );
+        Ok((StatusCode::CREATED, Json(alias.into())))
+    }
+
+    async fn list_aliases(
+        State(state): State<AppState>,
+        _user: CurrentUser,
+        Path(id): Path<Uuid>,
+        Query(query_params): Query<AliasListQueryParams>,
+    ) -> Result<(StatusCode, Json<AliasListResponse>), AppError> {
+        let list_params = query_params.into_dto(id);
+        let aliases: Vec<AliasResponse> = state
+            .db_client
+            .list_ingredient_aliases(&list_params)
+            .await?
+            .into();
+        log::info!("{:?} ingredient aliases collected", aliases.len());
+        let metadata = state
+            .db_client
+            .get_ingredient_aliases_metadata(&list_params)
+            .await?
+            .into();
+        Ok((
+            StatusCode::OK,
+            Json(AliasListResponse::from(aliases, metadata)),
+        ))
+    }
+
+    /// Aliases can be removed by the user who confirmed them and by admins
+    async fn delete_alias(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path((id, alias_id)): Path<(Uuid, Uuid)>,
+    ) -> Result<StatusCode, AppError> {
+        let alias = state.db_client.get_ingredient_alias(alias_id).await?;
+        if alias.ingredient_id != id {
+            return Err(AppError::NotFound {
+                id: alias_id.to_string(),
+            });
+        }
+        if alias.user_id == Some(user_id) || state.user_is_admin(user_id).await? {
+            state.db_client.delete_ingredient_alias(alias_id).await?;
+            state.ingredient_matcher.clear();
+            log::info!("Deleted ingredient alias with id {:?}", alias_id);
+            return Ok(StatusCode::NO_CONTENT);
         }
-        Err(AppError::Unauthorized)
+        Err(AppError::Forbidden)
     }
 }
@@ -3,9 +3,14 @@ use serde::{Deserialize, Serialize};
 use titlecase::titlecase;
 use uuid::Uuid;
 
+use crate::database::ingredient_aliases::dto::{
+    CreateDto as AliasCreateDto, IngredientAliasDto, IngredientAliasesListDto,
+    ListParamsDto as AliasListParamsDto,
+};
 use crate::database::ingredients::dto::{
-    CreateDto, IngredientDto, IngredientsListDto, ListParamsDto,
+    CreateDto, IngredientDto, IngredientsListDto, ListParamsDto, MergeDto, UpdateDto,
 };
+use crate::matching::normalize_name;
 use crate::server::payload::{MetadataResponse, DEFAULT_PER_PAGE};
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -21,6 +26,21 @@ impl From<CreatePayload> for CreateDto {
     }
 }
 
+#[derive(Deserialize, Serialize, Debug)]
+pub struct UpdatePayload {
+    pub name: String,
+    pub freezer_shelf_life_days: Option<i32>,
+}
+
+impl From<UpdatePayload> for UpdateDto {
+    fn from(val: UpdatePayload) -> Self {
+        UpdateDto {
+            name: titlecase(&val.name),
+            freezer_shelf_life_days: val.freezer_shelf_life_days,
+        }
+    }
+}
+
 #[derive(Clone, Deserialize, Debug)]
 pub struct ListQueryParams {
     pub name: Option<String>,
@@ -44,6 +64,7 @@ impl From<ListQueryParams> for ListParamsDto {
 pub struct IngredientResponse {
     pub id: Uuid,
     pub name: String,
+    pub freezer_shelf_life_days: Option<i32>,
     pub created_at: NaiveDateTime,
 }
 
@@ -52,6 +73,7 @@ impl From<IngredientDto> for IngredientResponse {
         IngredientResponse {
             id: val.id,
             name: val.name,
+            freezer_shelf_life_days: val.freezer_shelf_life_days,
             created_at: val.created_at,
         }
     }
@@ -75,3 +97,102 @@ impl IngredientListResponse {
         IngredientListResponse { metadata, items }
     }
 }
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct AliasPayload {
+    pub alias: String,
+}
+
+impl AliasPayload {
+    pub fn into_dto(self, ingredient_id: Uuid, user_id: Uuid) -> AliasCreateDto {
+        AliasCreateDto {
+            alias: normalize_name(&self.alias),
+            ingredient_id,
+            user_id,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct AliasListQueryParams {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+}
+
+impl AliasListQueryParams {
+    pub fn into_dto(self, ingredient_id: Uuid) -> AliasListParamsDto {
+        AliasListParamsDto {
+            ingredient_id: Some(ingredient_id),
+            limit: self.per_page.unwrap_or(DEFAULT_PER_PAGE),
+            offset: self.per_page.unwrap_or(DEFAULT_PER_PAGE) * (self.page.unwrap_or(1) - 1),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct AliasResponse {
+    pub id: Uuid,
+    pub alias: String,
+    pub ingredient_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<IngredientAliasDto> for AliasResponse {
+    fn from(val: IngredientAliasDto) -> Self {
+        AliasResponse {
+            id: val.id,
+            alias: val.alias,
+            ingredient_id: val.ingredient_id,
+            user_id: val.user_id,
+            created_at: val.created_at,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct AliasListResponse {
+    #[serde(rename = "_metadata")]
+    pub metadata: MetadataResponse,
+    pub items: Vec<AliasResponse>,
+}
+
+impl From<IngredientAliasesListDto> for Vec<AliasResponse> {
+    fn from(val: IngredientAliasesListDto) -> Self {
+        val.items.into_iter().map(Into::into).collect()
+    }
+}
+
+impl AliasListResponse {
+    pub fn from(items: Vec<AliasResponse>, metadata: MetadataResponse) -> Self {
+        AliasListResponse { metadata, items }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct MergePayload {
+    pub duplicate_ids: Vec<Uuid>,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct MergeResponse {
+    pub ingredient: IngredientResponse,
+    pub merged_ids: Vec<Uuid>,
+    pub recipe_ingredients_moved: u64,
+    pub recipe_ingredients_combined: u64,
+    pub pantry_items_moved: u64,
+    pub pantry_items_combined: u64,
+}
+
+impl From<MergeDto> for MergeResponse {
+    fn from(val: MergeDto) -> Self {
+        MergeResponse {
+            ingredient: val.ingredient.into(),
+            merged_ids: val.merged_ids,
+            recipe_ingredients_moved: val.recipe_ingredients_moved,
+            recipe_ingredients_combined: val.recipe_ingredients_combined,
+            pantry_items_moved: val.pantry_items_moved,
+            pantry_items_combined: val.pantry_items_combined,
+        }
+    }
+}
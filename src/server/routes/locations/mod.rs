@@ -0,0 +1,135 @@
+mod payload;
+
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    routing::get,
+    Router,
+};
+use uuid::Uuid;
+
+use crate::server::routes::auth::CurrentUser;
+use crate::server::routes::errors::{AppError, VerifyError};
+use crate::server::state::{Access, AppState};
+use payload::{
+    CreatePayload, ListQueryParams, LocationListResponse, LocationResponse, UpdatePayload,
+};
+
+pub struct LocationRouter {}
+
+impl LocationRouter {
+    pub fn router() -> Router<AppState> {
+        Router::new()
+            .route("/", get(LocationRouter::list).post(LocationRouter::create))
+            .route(
+                "/:id",
+                get(LocationRouter::get)
+                    .put(LocationRouter::update)
+                    .delete(LocationRouter::delete),
+            )
+    }
+
+    async fn create(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Json(payload): Json<CreatePayload>,
+    ) -> Result<(StatusCode, Json<LocationResponse>), AppError> {
+        verify_household(&state, payload.household_id, user_id).await?;
+        let location = state
+            .db_client
+            .create_location(payload.into_dto(user_id))
+            .await?;
+        log::info!("Location with id {:?} created", location.id.to_string());
+        Ok((StatusCode::CREATED, Json(location.into())))
+    }
+
+    async fn list(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Query(query_params): Query<ListQueryParams>,
+    ) -> Result<(StatusCode, Json<LocationListResponse>), AppError> {
+        let list_params = query_params.into_dto(user_id);
+        let locations: Vec<LocationResponse> =
+            state.db_client.list_locations(&list_params).await?.into();
+        log::info!("{:?} locations collected", locations.len());
+        let metadata = state
+            .db_client
+            .get_locations_metadata(&list_params)
+            .await?
+            .into();
+        Ok((
+            StatusCode::OK,
+            Json(LocationListResponse::from(locations, metadata)),
+        ))
+    }
+
+    async fn get(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+    ) -> Result<(StatusCode, Json<LocationResponse>), AppError> {
+        verify_user(&state, id, user_id, Access::Read).await?;
+        let location = state.db_client.get_location(id).await?;
+        log::info!("Got location with id {:?}", location.id);
+        Ok((StatusCode::OK, Json(location.into())))
+    }
+
+    async fn update(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+        Json(payload): Json<UpdatePayload>,
+    ) -> Result<(StatusCode, Json<LocationResponse>), AppError> {
+        verify_user(&state, id, user_id, Access::Write).await?;
+        verify_household(&state, payload.household_id, user_id).await?;
+        let location = state.db_client.update_location(id, payload.into()).await?;
+        log::info!("Updated location with id {id:?}");
+        Ok((StatusCode::OK, Json(location.into())))
+    }
+
+    async fn delete(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+    ) -> Result<StatusCode, AppError> {
+        verify_user(&state, id, user_id, Access::Write).await?;
+        state.db_client.delete_location(id).await?;
+        log::info!("Deleted location with id {:?}", id);
+        Ok(StatusCode::NO_CONTENT)
+    }
+}
+
+async fn verify_user(
+    state: &AppState,
+    location_id: Uuid,
+    user_id: Uuid,
+    access: Access,
+) -> Result<(), VerifyError> {
+    let location = state.db_client.get_location(location_id).await?;
+    if state
+        .has_access(user_id, location.user_id, location.household_id, access)
+        .await?
+    {
+        log::info!("Got location with id {:?}", location.id);
+        return Ok(());
+    }
+    Err(VerifyError::Forbidden)
+}
+
+/// Locations can only be shared with households the user may write to
+async fn verify_household(
+    state: &AppState,
+    household_id: Option<Uuid>,
+    user_id: Uuid,
+) -> Result<(), VerifyError> {
+    match household_id {
+        Some(household_id)
+            if !state
+                .household_allows(household_id, user_id, Access::Write)
+                .await? =>
+        {
+            Err(VerifyError::Forbidden)
+        }
+        _ => Ok(()),
+    }
+}
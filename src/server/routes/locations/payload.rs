@@ -0,0 +1,105 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::database::locations::dto::{
+    CreateDto, ListParamsDto, LocationDto, LocationsListDto, UpdateDto,
+};
+use crate::server::payload::{MetadataResponse, DEFAULT_PER_PAGE};
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CreatePayload {
+    pub name: String,
+    pub household_id: Option<Uuid>,
+    #[serde(default)]
+    pub freezer: bool,
+}
+
+impl CreatePayload {
+    pub fn into_dto(self, user_id: Uuid) -> CreateDto {
+        CreateDto {
+            user_id,
+            household_id: self.household_id,
+            name: self.name,
+            freezer: self.freezer,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct UpdatePayload {
+    pub name: String,
+    pub household_id: Option<Uuid>,
+    #[serde(default)]
+    pub freezer: bool,
+}
+
+impl From<UpdatePayload> for UpdateDto {
+    fn from(val: UpdatePayload) -> Self {
+        UpdateDto {
+            household_id: val.household_id,
+            name: val.name,
+            freezer: val.freezer,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct ListQueryParams {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+}
+
+impl ListQueryParams {
+    pub fn into_dto(self, user_id: Uuid) -> ListParamsDto {
+        ListParamsDto {
+            user_id: Some(user_id),
+            limit: self.per_page.unwrap_or(DEFAULT_PER_PAGE),
+            offset: self.per_page.unwrap_or(DEFAULT_PER_PAGE) * (self.page.unwrap_or(1) - 1),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct LocationResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub freezer: bool,
+    pub user_id: Uuid,
+    pub household_id: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl From<LocationDto> for LocationResponse {
+    fn from(val: LocationDto) -> Self {
+        LocationResponse {
+            id: val.id,
+            name: val.name,
+            freezer: val.freezer,
+            user_id: val.user_id,
+            household_id: val.household_id,
+            created_at: val.created_at,
+            updated_at: val.updated_at,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct LocationListResponse {
+    #[serde(rename = "_metadata")]
+    pub metadata: MetadataResponse,
+    pub items: Vec<LocationResponse>,
+}
+
+impl From<LocationsListDto> for Vec<LocationResponse> {
+    fn from(val: LocationsListDto) -> Self {
+        val.items.into_iter().map(Into::into).collect()
+    }
+}
+
+impl LocationListResponse {
+    pub fn from(items: Vec<LocationResponse>, metadata: MetadataResponse) -> Self {
+        LocationListResponse { metadata, items }
+    }
+}
@@ -1,55 +1,101 @@
 mod payload;
 
+use std::sync::LazyLock;
+
 use axum::response::Redirect;
 use axum::{
-    extract::{Json, State},
-    routing::post,
+    extract::{Json, Query, State},
+    http::{header::USER_AGENT, HeaderMap},
+    routing::{get, post},
     Router,
 };
-use axum_extra::extract::cookie::Cookie;
+use axum_extra::extract::cookie::{Cookie, SameSite};
 use axum_extra::extract::CookieJar;
-use rand::distributions::Alphanumeric;
-use rand::{thread_rng, Rng};
-use tokio::sync::mpsc::Sender;
+use color_eyre::eyre::eyre;
+use time::Duration;
 use uuid::Uuid;
 
-use crate::redis::{RedisCommand, RedisCommands, RedisResult};
+use crate::database::errors::CreateError;
+use crate::database::user_identities::dto::{
+    CreateDto as IdentityCreateDto, ListParamsDto as IdentityListParamsDto,
+};
+use crate::database::users::dto::CreateDto as UserCreateDto;
+use crate::oidc::{AuthorizationRequest, Identity};
+use crate::redis::{RedisCommands, RedisResult};
+use crate::server::limits::{ClientIp, LoginAttempt, LoginGuard};
 use crate::server::routes::errors::AppError;
-use crate::server::routes::utils::verify_password;
-use crate::server::routes::COOKIE_KEY;
+use crate::server::routes::utils::{generate_secret, hash_password, verify_password};
+use crate::server::session::{removal_cookie, SessionStore, COOKIE_KEY};
 use crate::server::state::AppState;
-use payload::LoginPayload;
+use payload::{LoginPayload, OidcCallbackQueryParams};
+
+/// Cookie binding the OIDC login to the browser that started it
+const OIDC_STATE_COOKIE: &str = "oidc_state";
+/// Time the user has to log in at the provider
+const OIDC_REQUEST_TTL_SECS: u64 = 10 * 60;
+/// Users created through OIDC get a random password nobody knows
+const OIDC_USER_PASSWORD_LENGTH: usize = 40;
+
+/// Checked against when the username is unknown, no password matches it
+static UNKNOWN_USER_PASSWORD_HASH: LazyLock<String> =
+    LazyLock::new(|| hash_password(&generate_secret(OIDC_USER_PASSWORD_LENGTH)));
 
-const SESSION_TTL_DAYS: u16 = 7;
 pub struct LoginRouter {}
 
 impl LoginRouter {
     pub fn router() -> Router<AppState> {
-        Router::new().route("/", post(LoginRouter::login).delete(LoginRouter::logout))
+        Router::new()
+            .route("/", post(LoginRouter::login).delete(LoginRouter::logout))
+            .route("/oidc", get(LoginRouter::oidc_login))
+            .route("/oidc/callback", get(LoginRouter::oidc_callback))
     }
 
+    /// Unknown usernames and wrong passwords get the same 401, and IPs with too many failed
+    /// logins a 429 until their lockout ends. A locked out username gets a 429 only from IPs
+    /// that failed before, so its owner can still log in from elsewhere.
     async fn login(
         State(state): State<AppState>,
+        ClientIp(ip): ClientIp,
         jar: CookieJar,
+        headers: HeaderMap,
         Json(payload): Json<LoginPayload>,
     ) -> Result<(CookieJar, Redirect), AppError> {
         let username = payload.username.clone();
         let password = payload.password.clone().unwrap_or_default();
-        let users = state.db_client.list_users(&payload.into()).await?;
-        if users.items.is_empty() {
-            return Err(AppError::NotFound { id: username });
+        let attempt = LoginAttempt {
+            username: &username,
+            ip,
+        };
+        if let Some(retry_after_secs) = state.redis_sender.login_lockout(&attempt).await? {
+            log::info!("Login to {username:?} from {} is locked out", attempt.ip);
+            return Err(AppError::TooManyRequests { retry_after_secs });
         }
-        let user = &users.items[0];
-        if verify_password(&password, &user.password_hash) {
-            log::info!("User {:?} logged in", username);
-            let session_id = create_session(user.id, &state.redis_sender).await?;
-            Ok((
-                jar.add(Cookie::new(COOKIE_KEY, session_id)),
-                Redirect::to("/"),
-            ))
-        } else {
-            log::info!("Wrong password from {:?}", username);
-            Err(AppError::Unauthorized)
+        let users = state.db_client.list_users(&payload.into()).await?;
+        // Unknown users take as long to check as known ones, so timing does not tell them apart
+        let (user_id, password_hash) = match users.items.first() {
+            Some(user) => (Some(user.id), user.password_hash.clone()),
+            None => (None, UNKNOWN_USER_PASSWORD_HASH.clone()),
+        };
+        // Hashing is slow by design, it must not hold up the other requests of the worker
+        let verified =
+            tokio::task::spawn_blocking(move || verify_password(&password, &password_hash))
+                .await
+                .map_err(|err| AppError::Other { error: err.into() })?;
+        match user_id {
+            Some(user_id) if verified => {
+                log::info!("User {:?} logged in", username);
+                state.redis_sender.clear_login_failures(&attempt).await?;
+                let jar = start_session(&state, jar, &headers, user_id).await?;
+                Ok((jar, Redirect::to("/")))
+            }
+            _ => {
+                log::info!("Failed login to {:?} from {}", username, attempt.ip);
+                state
+                    .redis_sender
+                    .record_login_failure(&state.login_limits, &attempt)
+                    .await?;
+                Err(AppError::Unauthorized)
+            }
         }
     }
 
@@ -57,30 +103,183 @@ impl LoginRouter {
         State(state): State<AppState>,
         jar: CookieJar,
     ) -> Result<(CookieJar, Redirect), AppError> {
-        if let Some(session_id) = jar.get(COOKIE_KEY) {
-            let session_id = session_id.value_trimmed();
-            if state.session_is_valid(session_id).await? {
-                delete_session(session_id, &state.redis_sender).await?;
-                return Ok((jar.remove(COOKIE_KEY), Redirect::to("/login")));
-            }
+        if delete_session(&state, &jar).await? {
+            return Ok((jar.remove(removal_cookie()), Redirect::to("/login")));
         }
         Err(AppError::Unauthorized)
     }
+
+    /// Sends the user to the OIDC provider to log in
+    async fn oidc_login(
+        State(state): State<AppState>,
+        jar: CookieJar,
+    ) -> Result<(CookieJar, Redirect), AppError> {
+        let oidc = state.oidc.as_ref().ok_or(AppError::NotFound {
+            id: "oidc".to_owned(),
+        })?;
+        let request = AuthorizationRequest::new();
+        let url = oidc.authorization_url(&request).await?;
+        let value =
+            serde_json::to_string(&request).map_err(|err| AppError::Other { error: err.into() })?;
+        state
+            .redis_sender
+            .set(
+                &oidc_request_key(&request.state),
+                &value,
+                Some(OIDC_REQUEST_TTL_SECS),
+            )
+            .await?;
+        let cookie = Cookie::build((OIDC_STATE_COOKIE, request.state))
+            .path("/login")
+            .http_only(true)
+            .secure(state.session_settings.secure_cookie)
+            .same_site(SameSite::Lax)
+            .max_age(Duration::seconds(
+                i64::try_from(OIDC_REQUEST_TTL_SECS).unwrap_or(i64::MAX),
+            ));
+        Ok((jar.add(cookie), Redirect::to(url.as_str())))
+    }
+
+    /// The provider sends the user back here with an authorization code
+    async fn oidc_callback(
+        State(state): State<AppState>,
+        jar: CookieJar,
+        headers: HeaderMap,
+        Query(query_params): Query<OidcCallbackQueryParams>,
+    ) -> Result<(CookieJar, Redirect), AppError> {
+        let oidc = state.oidc.as_ref().ok_or(AppError::NotFound {
+            id: "oidc".to_owned(),
+        })?;
+        if let Some(error) = query_params.error {
+            log::info!(
+                "Provider did not log the user in: {error} {}",
+                query_params.error_description.unwrap_or_default()
+            );
+            return Err(AppError::Unauthorized);
+        }
+        let (Some(code), Some(request_state)) = (query_params.code, query_params.state) else {
+            return Err(AppError::UnprocessableEntity {
+                error: eyre!("code and state are required."),
+            });
+        };
+        // Otherwise anyone could log a victim in to the attacker's account
+        if jar.get(OIDC_STATE_COOKIE).map(Cookie::value_trimmed) != Some(request_state.as_str()) {
+            log::info!("OIDC state does not match the browser's");
+            return Err(AppError::Unauthorized);
+        }
+        let key = oidc_request_key(&request_state);
+        let request = state
+            .redis_sender
+            .get(&key)
+            .await?
+            .ok_or(AppError::Unauthorized)?;
+        state.redis_sender.delete(&key).await?;
+        let request: AuthorizationRequest =
+            serde_json::from_str(&request).map_err(|err| AppError::Other { error: err.into() })?;
+        let identity = oidc.exchange_code(&code, &request).await?;
+        let user_id = identity_user(&state, &jar, identity).await?;
+        log::info!("User {user_id:?} logged in through OIDC");
+        let jar = jar.remove(Cookie::build(OIDC_STATE_COOKIE).path("/login"));
+        let jar = start_session(&state, jar, &headers, user_id).await?;
+        Ok((jar, Redirect::to("/")))
+    }
+}
+
+/// Replaces the request's session with a new one for the user,
+/// so a token planted before logging in is useless
+async fn start_session(
+    state: &AppState,
+    jar: CookieJar,
+    headers: &HeaderMap,
+    user_id: Uuid,
+) -> RedisResult<CookieJar> {
+    delete_session(state, &jar).await?;
+    let user_agent = headers
+        .get(USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(ToOwned::to_owned);
+    let token = state
+        .redis_sender
+        .create_session(user_id, user_agent, state.session_settings.ttl_secs)
+        .await?;
+    Ok(jar.add(state.session_settings.cookie(token)))
 }
 
-async fn create_session(user_id: Uuid, redis_sender: &Sender<RedisCommand>) -> RedisResult<String> {
-    let session_id: String = thread_rng()
-        .sample_iter(&Alphanumeric)
-        .take(30)
-        .map(char::from)
-        .collect();
-    redis_sender
-        .set(&session_id, &user_id.to_string(), Some(SESSION_TTL_DAYS))
+/// Returns `false` if the request had no valid session
+async fn delete_session(state: &AppState, jar: &CookieJar) -> RedisResult<bool> {
+    if let Some(session_id) = jar.get(COOKIE_KEY) {
+        let token = session_id.value_trimmed();
+        if state.redis_sender.get_session(token).await?.is_some() {
+            state.redis_sender.delete_session(token).await?;
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Returns the user the identity is linked to. A new identity is linked to the user
+/// who is logged in, or else to a new user named after it.
+async fn identity_user(
+    state: &AppState,
+    jar: &CookieJar,
+    identity: Identity,
+) -> Result<Uuid, AppError> {
+    if let Some(linked) = state
+        .db_client
+        .list_user_identities(&IdentityListParamsDto {
+            issuer: Some(identity.issuer.clone()),
+            subject: Some(identity.subject.clone()),
+            limit: 1,
+            ..Default::default()
+        })
+        .await?
+        .items
+        .into_iter()
+        .next()
+    {
+        return Ok(linked.user_id);
+    }
+    let session_user = match jar.get(COOKIE_KEY) {
+        Some(session_id) => state.get_sessions_user(session_id.value_trimmed()).await?,
+        None => None,
+    };
+    let user_id = if let Some(user_id) = session_user {
+        user_id
+    } else {
+        let name = identity
+            .preferred_username
+            .or(identity.email)
+            .unwrap_or_else(|| identity.subject.clone());
+        match state
+            .db_client
+            .create_user(UserCreateDto {
+                name: name.clone(),
+                password_hash: hash_password(&generate_secret(OIDC_USER_PASSWORD_LENGTH)),
+                admin: None,
+            })
+            .await
+        {
+            Ok(user) => user.id,
+            Err(CreateError::AlreadyExist { .. }) => {
+                return Err(AppError::UnprocessableEntity {
+                    error: eyre!("User {name:?} already exists. Log in with the password first to link the accounts."),
+                })
+            }
+            Err(err) => return Err(err.into()),
+        }
+    };
+    state
+        .db_client
+        .create_user_identity(IdentityCreateDto {
+            user_id,
+            issuer: identity.issuer,
+            subject: identity.subject,
+        })
         .await?;
-    log::info!("Session created");
-    Ok(session_id)
+    log::info!("Linked OIDC identity to user {user_id:?}");
+    Ok(user_id)
 }
 
-async fn delete_session(session_id: &str, redis_sender: &Sender<RedisCommand>) -> RedisResult<()> {
-    redis_sender.delete(session_id).await
+fn oidc_request_key(state: &str) -> String {
+    format!("oidc_request:{state}")
 }
@@ -17,3 +17,12 @@ impl From<LoginPayload> for ListParamsDto {
         }
     }
 }
+
+/// Sent by the provider, with `error` instead of `code` if the user was not logged in
+#[derive(Clone, Deserialize, Debug)]
+pub struct OidcCallbackQueryParams {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
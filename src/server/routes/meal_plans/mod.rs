@@ -0,0 +1,153 @@
+mod payload;
+
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    routing::{get, post},
+    Router,
+};
+use uuid::Uuid;
+
+use crate::server::routes::auth::CurrentUser;
+use crate::server::routes::errors::{AppError, VerifyError};
+use crate::server::state::{Access, AppState};
+use payload::{
+    CookPayload, CreatePayload, ListQueryParams, MealPlanListResponse, MealPlanResponse,
+    UpdatePayload,
+};
+
+pub struct MealPlanRouter {}
+
+impl MealPlanRouter {
+    pub fn router() -> Router<AppState> {
+        Router::new()
+            .route("/", get(MealPlanRouter::list).post(MealPlanRouter::create))
+            .route(
+                "/:id",
+                get(MealPlanRouter::get)
+                    .put(MealPlanRouter::update)
+                    .delete(MealPlanRouter::delete),
+            )
+            .route("/:id/cook", post(MealPlanRouter::cook))
+    }
+
+    async fn create(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Json(payload): Json<CreatePayload>,
+    ) -> Result<(StatusCode, Json<MealPlanResponse>), AppError> {
+        verify_recipe_user(&state, payload.recipe_id, user_id).await?;
+        let meal_plan = state
+            .db_client
+            .create_meal_plan(payload.into_dto(user_id))
+            .await?;
+        log::info!("Meal plan with id {:?} created", meal_plan.id.to_string());
+        Ok((StatusCode::CREATED, Json(meal_plan.into())))
+    }
+
+    async fn list(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Query(query_params): Query<ListQueryParams>,
+    ) -> Result<(StatusCode, Json<MealPlanListResponse>), AppError> {
+        let list_params = query_params.into_dto(user_id);
+        let meal_plans: Vec<MealPlanResponse> =
+            state.db_client.list_meal_plans(&list_params).await?.into();
+        log::info!("{:?} meal plans collected", meal_plans.len());
+        let metadata = state
+            .db_client
+            .get_meal_plans_metadata(&list_params)
+            .await?
+            .into();
+        Ok((
+            StatusCode::OK,
+            Json(MealPlanListResponse::from(meal_plans, metadata)),
+        ))
+    }
+
+    async fn get(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+    ) -> Result<(StatusCode, Json<MealPlanResponse>), AppError> {
+        let meal_plan = state.db_client.get_meal_plan(id).await?;
+        if state
+            .has_access(user_id, meal_plan.user_id, None, Access::Read)
+            .await?
+        {
+            log::info!("Got meal plan with id {:?}", meal_plan.id);
+            return Ok((StatusCode::OK, Json(meal_plan.into())));
+        }
+        Err(AppError::Forbidden)
+    }
+
+    async fn update(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+        Json(payload): Json<UpdatePayload>,
+    ) -> Result<(StatusCode, Json<MealPlanResponse>), AppError> {
+        verify_user(&state, id, user_id).await?;
+        verify_recipe_user(&state, payload.recipe_id, user_id).await?;
+        let meal_plan = state.db_client.update_meal_plan(id, payload.into()).await?;
+        log::info!("Updated meal plan with id {id:?}");
+        Ok((StatusCode::OK, Json(meal_plan.into())))
+    }
+
+    async fn cook(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+        Json(payload): Json<CookPayload>,
+    ) -> Result<(StatusCode, Json<MealPlanResponse>), AppError> {
+        verify_user(&state, id, user_id).await?;
+        let meal_plan = state
+            .db_client
+            .cook_meal_plan(id, payload.deduct_ingredients)
+            .await?;
+        log::info!("Meal plan with id {id:?} cooked");
+        Ok((StatusCode::OK, Json(meal_plan.into())))
+    }
+
+    async fn delete(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+    ) -> Result<StatusCode, AppError> {
+        verify_user(&state, id, user_id).await?;
+        state.db_client.delete_meal_plan(id).await?;
+        log::info!("Deleted meal plan with id {:?}", id);
+        Ok(StatusCode::NO_CONTENT)
+    }
+}
+
+async fn verify_user(
+    state: &AppState,
+    meal_plan_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), VerifyError> {
+    let meal_plan = state.db_client.get_meal_plan(meal_plan_id).await?;
+    if state
+        .has_access(user_id, meal_plan.user_id, None, Access::Write)
+        .await?
+    {
+        log::info!("Got meal plan with id {:?}", meal_plan.id);
+        return Ok(());
+    }
+    Err(VerifyError::Forbidden)
+}
+
+async fn verify_recipe_user(
+    state: &AppState,
+    recipe_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), VerifyError> {
+    let recipe = state.db_client.get_recipe(recipe_id).await?;
+    if state
+        .has_access(user_id, recipe.user_id, recipe.household_id, Access::Read)
+        .await?
+    {
+        return Ok(());
+    }
+    Err(VerifyError::Forbidden)
+}
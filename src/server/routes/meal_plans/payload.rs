@@ -0,0 +1,161 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::database::meal_plans::dto::{
+    CreateDto, ListParamsDto, Meal, MealPlanDto, MealPlanJoinDto, MealPlansListDto, UpdateDto,
+};
+use crate::server::payload::{MetadataResponse, DEFAULT_PER_PAGE};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MealPayload {
+    Breakfast,
+    Lunch,
+    Dinner,
+}
+
+impl From<MealPayload> for Meal {
+    fn from(val: MealPayload) -> Self {
+        match val {
+            MealPayload::Breakfast => Meal::Breakfast,
+            MealPayload::Lunch => Meal::Lunch,
+            MealPayload::Dinner => Meal::Dinner,
+        }
+    }
+}
+
+impl From<Meal> for MealPayload {
+    fn from(val: Meal) -> Self {
+        match val {
+            Meal::Breakfast => MealPayload::Breakfast,
+            Meal::Lunch => MealPayload::Lunch,
+            Meal::Dinner => MealPayload::Dinner,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CreatePayload {
+    pub recipe_id: Uuid,
+    pub date: NaiveDate,
+    pub meal: MealPayload,
+}
+
+impl CreatePayload {
+    pub fn into_dto(self, user_id: Uuid) -> CreateDto {
+        CreateDto {
+            user_id,
+            recipe_id: self.recipe_id,
+            date: self.date,
+            meal: self.meal.into(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct UpdatePayload {
+    pub recipe_id: Uuid,
+    pub date: NaiveDate,
+    pub meal: MealPayload,
+}
+
+impl From<UpdatePayload> for UpdateDto {
+    fn from(val: UpdatePayload) -> Self {
+        UpdateDto {
+            recipe_id: val.recipe_id,
+            date: val.date,
+            meal: val.meal.into(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CookPayload {
+    #[serde(default)]
+    pub deduct_ingredients: bool,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct ListQueryParams {
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+}
+
+impl ListQueryParams {
+    pub fn into_dto(self, user_id: Uuid) -> ListParamsDto {
+        ListParamsDto {
+            user_id: Some(user_id),
+            from_date: self.from_date,
+            to_date: self.to_date,
+            limit: self.per_page.unwrap_or(DEFAULT_PER_PAGE),
+            offset: self.per_page.unwrap_or(DEFAULT_PER_PAGE) * (self.page.unwrap_or(1) - 1),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct MealPlanResponse {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub recipe_id: Uuid,
+    pub recipe_name: Option<String>,
+    pub date: NaiveDate,
+    pub meal: MealPayload,
+    pub cooked: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl From<MealPlanDto> for MealPlanResponse {
+    fn from(val: MealPlanDto) -> Self {
+        MealPlanResponse {
+            id: val.id,
+            user_id: val.user_id,
+            recipe_id: val.recipe_id,
+            recipe_name: None,
+            date: val.date,
+            meal: val.meal.into(),
+            cooked: val.cooked,
+            created_at: val.created_at,
+            updated_at: val.updated_at,
+        }
+    }
+}
+
+impl From<MealPlanJoinDto> for MealPlanResponse {
+    fn from(val: MealPlanJoinDto) -> Self {
+        MealPlanResponse {
+            id: val.id,
+            user_id: val.user_id,
+            recipe_id: val.recipe_id,
+            recipe_name: Some(val.recipe_name),
+            date: val.date,
+            meal: val.meal.into(),
+            cooked: val.cooked,
+            created_at: val.created_at,
+            updated_at: val.updated_at,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct MealPlanListResponse {
+    #[serde(rename = "_metadata")]
+    pub metadata: MetadataResponse,
+    pub items: Vec<MealPlanResponse>,
+}
+
+impl From<MealPlansListDto> for Vec<MealPlanResponse> {
+    fn from(val: MealPlansListDto) -> Self {
+        val.items.into_iter().map(Into::into).collect()
+    }
+}
+
+impl MealPlanListResponse {
+    pub fn from(items: Vec<MealPlanResponse>, metadata: MetadataResponse) -> Self {
+        MealPlanListResponse { metadata, items }
+    }
+}
@@ -1,12 +1,18 @@
-mod errors;
+pub(crate) mod auth;
+pub mod categories;
+pub(crate) mod errors;
+pub mod households;
 pub mod ingredients;
+pub mod locations;
 pub mod login;
+pub mod meal_plans;
 pub mod pantry_items;
 pub mod parse_ingredients;
 pub mod parse_recipe_link;
+pub mod products;
 pub mod recipe_ingredients;
 pub mod recipes;
+pub mod sessions;
+pub mod shopping_lists;
 pub mod users;
 pub mod utils;
-
-const COOKIE_KEY: &str = "session_id";
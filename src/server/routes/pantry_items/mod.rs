@@ -3,18 +3,28 @@ mod payload;
 use axum::{
     extract::{Json, Path, Query, State},
     http::StatusCode,
-    routing::get,
+    routing::{get, post},
     Router,
 };
-use axum_extra::extract::CookieJar;
+use chrono::Utc;
 use color_eyre::eyre::eyre;
 use uuid::Uuid;
 
+use crate::database::locations::dto::LocationDto;
+use crate::database::pantry_items::dto::{MoveDto, PantryItemDto};
+use crate::database::products::dto::ListParamsDto as ProductListParamsDto;
+use crate::expiration::{frozen_expiration, user_digest, window_end, DEFAULT_WINDOW_DAYS};
+use crate::products::normalize_gtin;
+use crate::server::routes::auth::CurrentUser;
 use crate::server::routes::errors::{AppError, VerifyError};
-use crate::server::routes::COOKIE_KEY;
-use crate::server::state::AppState;
+use crate::server::state::{Access, AppState};
+use crate::units::{Measure, Measurement};
 use payload::{
-    CreatePayload, ListQueryParams, PantryItemListResponse, PantryItemResponse, UpdatePayload,
+    ConsumePayload, ConsumeResponse, ConsumptionQueryParams, ConsumptionStatsResponse,
+    CreatePayload, ExpirationDigestResponse, ExpiringQueryParams, HistoryQueryParams,
+    ListQueryParams, MovePayload, PantryItemEventListResponse, PantryItemEventResponse,
+    PantryItemGroupResponse, PantryItemListResponse, PantryItemLocationListResponse,
+    PantryItemResponse, PeriodPayload, ScanPayload, UpdatePayload,
 };
 
 pub struct PantryItemRouter {}
@@ -26,130 +36,372 @@ impl PantryItemRouter {
                 "/",
                 get(PantryItemRouter::list).post(PantryItemRouter::create),
             )
+            .route("/by_location", get(PantryItemRouter::by_location))
+            .route("/consume", post(PantryItemRouter::consume))
+            .route("/consumption", get(PantryItemRouter::consumption))
+            .route("/expiring", get(PantryItemRouter::expiring))
+            .route("/scan", post(PantryItemRouter::scan))
             .route(
                 "/:id",
                 get(PantryItemRouter::get)
                     .put(PantryItemRouter::update)
                     .delete(PantryItemRouter::delete),
             )
+            .route("/:id/history", get(PantryItemRouter::history))
+            .route("/:id/move", post(PantryItemRouter::move_item))
     }
 
     async fn create(
         State(state): State<AppState>,
-        jar: CookieJar,
+        CurrentUser(user_id): CurrentUser,
         Json(payload): Json<CreatePayload>,
     ) -> Result<(StatusCode, Json<PantryItemResponse>), AppError> {
-        if let Some(session_id) = jar.get(COOKIE_KEY) {
-            if let Some(user_id) = state.get_sessions_user(session_id.value_trimmed()).await? {
-                if !valid_quantity(
-                    payload.quantity,
-                    payload.weight_grams,
-                    payload.volume_milli_litres,
-                ) {
-                    return Err(AppError::UnprocessableEntity { error: eyre!("Invalid amount. Must indicate only one of quantity, weight_grams or volume_milli_litres.") });
-                }
-                let pantry_item = state
-                    .db_client
-                    .create_pantry_item(payload.into_dto(user_id))
-                    .await?;
-                log::info!(
-                    "Pantry item with id {:?} created",
-                    pantry_item.id.to_string()
-                );
-                return Ok((StatusCode::CREATED, Json(pantry_item.into())));
-            }
+        if !valid_quantity(
+            payload.quantity,
+            payload.weight_grams,
+            payload.volume_milli_litres,
+        ) {
+            return Err(AppError::UnprocessableEntity { error: eyre!("Invalid amount. Must indicate only one of quantity, weight_grams or volume_milli_litres.") });
         }
-        Err(AppError::Unauthorized)
+        verify_household(&state, payload.household_id, user_id).await?;
+        verify_location(&state, payload.location_id, user_id).await?;
+        let pantry_item = state
+            .db_client
+            .create_pantry_item(payload.into_dto(user_id))
+            .await?;
+        log::info!(
+            "Pantry item with id {:?} created",
+            pantry_item.id.to_string()
+        );
+        Ok((StatusCode::CREATED, Json(pantry_item.into())))
+    }
+
+    /// Adds one package of the scanned product to the lot of its ingredient at the location
+    /// that expires on the same day, creating a new lot if there is none
+    async fn scan(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Json(payload): Json<ScanPayload>,
+    ) -> Result<(StatusCode, Json<PantryItemResponse>), AppError> {
+        let gtin = normalize_gtin(&payload.barcode).ok_or(AppError::UnprocessableEntity {
+            error: eyre!("Invalid barcode {:?}.", payload.barcode),
+        })?;
+        let product = state
+            .db_client
+            .list_products(&ProductListParamsDto {
+                gtin: Some(gtin.clone()),
+                limit: 1,
+                ..Default::default()
+            })
+            .await?
+            .items
+            .into_iter()
+            .next()
+            .ok_or(AppError::NotFound { id: gtin })?;
+        // Lots the user may only read, as a household viewer, are left alone
+        let pantry_item = state
+            .db_client
+            .find_writable_lot(
+                user_id,
+                product.ingredient_id,
+                payload.location_id,
+                payload.expiration_date,
+            )
+            .await?;
+        let Some(pantry_item) = pantry_item else {
+            verify_household(&state, payload.household_id, user_id).await?;
+            verify_location(&state, payload.location_id, user_id).await?;
+            let pantry_item = state
+                .db_client
+                .create_pantry_item(payload.into_dto(user_id, &product))
+                .await?;
+            log::info!(
+                "Pantry item with id {:?} created from product {:?}",
+                pantry_item.id,
+                product.gtin
+            );
+            return Ok((StatusCode::CREATED, Json(pantry_item.into())));
+        };
+        let package = product.package();
+        let (measure, amount) = match Measure::of_pantry_item(
+            pantry_item.quantity,
+            pantry_item.weight_grams,
+            pantry_item.volume_milli_litres,
+        ) {
+            Some((measure, _)) => (
+                measure,
+                package.in_measure(measure).ok_or(AppError::UnprocessableEntity {
+                    error: eyre!("Package of product {:?} cannot be added to pantry item {:?}, it is measured differently.", product.gtin, pantry_item.id),
+                })?,
+            ),
+            None => package.in_base_measure(),
+        };
+        let pantry_item = state
+            .db_client
+            .restock_pantry_item(pantry_item.id, measure, amount, user_id)
+            .await?;
+        log::info!(
+            "Added product {:?} to pantry item with id {:?}",
+            product.gtin,
+            pantry_item.id
+        );
+        Ok((StatusCode::OK, Json(pantry_item.into())))
+    }
+
+    /// Takes an amount of an ingredient from the pantry, using up the lots that expire first
+    async fn consume(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Json(payload): Json<ConsumePayload>,
+    ) -> Result<(StatusCode, Json<ConsumeResponse>), AppError> {
+        let needed =
+            Measurement::parse(&payload.amount, payload.unit.as_deref()).ok_or_else(|| {
+                AppError::UnprocessableEntity {
+                    error: eyre!(
+                        "Invalid amount {:?} {:?}.",
+                        payload.amount,
+                        payload.unit.as_deref().unwrap_or_default()
+                    ),
+                }
+            })?;
+        let consumed = state
+            .db_client
+            .consume_pantry_items(user_id, payload.ingredient_id, needed)
+            .await?
+            .ok_or_else(|| AppError::UnprocessableEntity {
+                error: eyre!(
+                    "No pantry item of ingredient {:?} holds an amount that can be measured in {:?}.",
+                    payload.ingredient_id,
+                    payload.unit.as_deref().unwrap_or_default()
+                ),
+            })?;
+        log::info!(
+            "Consumed ingredient with id {:?} from {:?} pantry items",
+            payload.ingredient_id,
+            consumed.lots.len()
+        );
+        Ok((
+            StatusCode::OK,
+            Json(ConsumeResponse::from(payload.ingredient_id, consumed)),
+        ))
     }
 
     async fn list(
         State(state): State<AppState>,
-        jar: CookieJar,
+        CurrentUser(user_id): CurrentUser,
         Query(query_params): Query<ListQueryParams>,
     ) -> Result<(StatusCode, Json<PantryItemListResponse>), AppError> {
-        if let Some(session_id) = jar.get(COOKIE_KEY) {
-            if let Some(user_id) = state.get_sessions_user(session_id.value_trimmed()).await? {
-                let list_params = query_params.into_dto(user_id);
-                let pantry_items: Vec<PantryItemResponse> = state
-                    .db_client
-                    .list_pantry_items_join(&list_params)
-                    .await?
-                    .into();
-                log::info!("{:?} pantry items collected", pantry_items.len());
-                let metadata = state
-                    .db_client
-                    .get_pantry_items_join_metadata(&list_params)
-                    .await?
-                    .into();
-                return Ok((
-                    StatusCode::OK,
-                    Json(PantryItemListResponse::from(pantry_items, metadata)),
-                ));
-            }
-        }
-        Err(AppError::Unauthorized)
+        let list_params = query_params.into_dto(user_id);
+        let pantry_items: Vec<PantryItemGroupResponse> = state
+            .db_client
+            .list_pantry_item_groups(&list_params)
+            .await?
+            .into();
+        log::info!("{:?} pantry item groups collected", pantry_items.len());
+        let metadata = state
+            .db_client
+            .get_pantry_item_groups_metadata(&list_params)
+            .await?
+            .into();
+        Ok((
+            StatusCode::OK,
+            Json(PantryItemListResponse::from(pantry_items, metadata)),
+        ))
+    }
+
+    /// Lists every lot of the user grouped by the location it is kept in
+    async fn by_location(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Query(query_params): Query<ListQueryParams>,
+    ) -> Result<(StatusCode, Json<PantryItemLocationListResponse>), AppError> {
+        let locations: PantryItemLocationListResponse = state
+            .db_client
+            .list_pantry_items_by_location(&query_params.into_dto(user_id))
+            .await?
+            .into();
+        log::info!(
+            "{:?} pantry item locations collected",
+            locations.items.len()
+        );
+        Ok((StatusCode::OK, Json(locations)))
+    }
+
+    async fn expiring(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Query(query_params): Query<ExpiringQueryParams>,
+    ) -> Result<(StatusCode, Json<ExpirationDigestResponse>), AppError> {
+        let until = window_end(
+            Utc::now().date_naive(),
+            query_params.within_days.unwrap_or(DEFAULT_WINDOW_DAYS),
+        );
+        let digest = user_digest(state.db_client.as_ref(), user_id, until).await?;
+        log::info!("{:?} expiring pantry items collected", digest.items.len());
+        Ok((StatusCode::OK, Json(digest.into())))
     }
 
     async fn get(
         State(state): State<AppState>,
-        jar: CookieJar,
+        CurrentUser(user_id): CurrentUser,
         Path(id): Path<Uuid>,
     ) -> Result<(StatusCode, Json<PantryItemResponse>), AppError> {
-        if let Some(session_id) = jar.get(COOKIE_KEY) {
-            if let Some(user_id) = state.get_sessions_user(session_id.value_trimmed()).await? {
-                let pantry_item = state.db_client.get_pantry_item(id).await?;
-                if pantry_item.user_id == user_id {
-                    log::info!("Got pantry item with id {:?}", pantry_item.id);
-                    return Ok((StatusCode::OK, Json(pantry_item.into())));
-                }
+        let pantry_item = state.db_client.get_pantry_item_join(id).await?;
+        if state
+            .has_access(
+                user_id,
+                pantry_item.user_id,
+                pantry_item.household_id,
+                Access::Read,
+            )
+            .await?
+        {
+            log::info!("Got pantry item with id {:?}", pantry_item.id);
+            return Ok((StatusCode::OK, Json(pantry_item.into())));
+        }
+        Err(AppError::Forbidden)
+    }
+
+    /// The history outlives the pantry item. Once it is deleted, users see the events they
+    /// made or that were made while it was shared with one of their households.
+    async fn history(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+        Query(query_params): Query<HistoryQueryParams>,
+    ) -> Result<(StatusCode, Json<PantryItemEventListResponse>), AppError> {
+        let visible_to = match verify_user(&state, id, user_id, Access::Read).await {
+            Err(VerifyError::NotFound { .. }) if !state.user_is_admin(user_id).await? => {
+                Some(user_id)
             }
+            Ok(_) | Err(VerifyError::NotFound { .. }) => None,
+            Err(err) => return Err(err.into()),
+        };
+        let list_params = query_params.into_dto(id, visible_to);
+        let metadata = state
+            .db_client
+            .get_pantry_item_events_metadata(&list_params)
+            .await?;
+        // Deleted items the user never saw are not found, like those that never existed
+        if visible_to.is_some() && metadata.total_count == 0 {
+            return Err(AppError::NotFound { id: id.to_string() });
+        }
+        let events: Vec<PantryItemEventResponse> = state
+            .db_client
+            .list_pantry_item_events(&list_params)
+            .await?
+            .into();
+        log::info!("Got history of pantry item with id {id:?}");
+        Ok((
+            StatusCode::OK,
+            Json(PantryItemEventListResponse::from(events, metadata.into())),
+        ))
+    }
+
+    async fn consumption(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Query(query_params): Query<ConsumptionQueryParams>,
+    ) -> Result<(StatusCode, Json<ConsumptionStatsResponse>), AppError> {
+        let period = query_params.period.unwrap_or(PeriodPayload::Week);
+        let params = query_params.into_dto(user_id, Utc::now().date_naive());
+        if params.from_date > params.to_date {
+            return Err(AppError::UnprocessableEntity {
+                error: eyre!("from_date must not be after to_date."),
+            });
         }
-        Err(AppError::Unauthorized)
+        let consumption = state.db_client.get_consumption_stats(&params).await?;
+        log::info!("Got consumption stats of user with id {user_id:?}");
+        Ok((
+            StatusCode::OK,
+            Json(ConsumptionStatsResponse {
+                from_date: params.from_date,
+                to_date: params.to_date,
+                period,
+                items: consumption.items.into_iter().map(Into::into).collect(),
+            }),
+        ))
     }
 
     async fn update(
         State(state): State<AppState>,
-        jar: CookieJar,
+        CurrentUser(user_id): CurrentUser,
         Path(id): Path<Uuid>,
         Json(payload): Json<UpdatePayload>,
     ) -> Result<(StatusCode, Json<PantryItemResponse>), AppError> {
-        if let Some(session_id) = jar.get(COOKIE_KEY) {
-            if let Some(user_id) = state.get_sessions_user(session_id.value_trimmed()).await? {
-                verify_user(&state, id, user_id).await?;
-                if !valid_quantity(
-                    payload.quantity,
-                    payload.weight_grams,
-                    payload.volume_milli_litres,
-                ) {
-                    return Err(AppError::UnprocessableEntity { error: eyre!("Invalid amount. Must indicate only one of quantity, weight_grams or volume_milli_litres.") });
-                }
-                let pantry_item = state
-                    .db_client
-                    .update_pantry_item(id, payload.into_dto(user_id))
-                    .await?;
-                log::info!("Updated pantry item with id {id:?}");
-                return Ok((StatusCode::OK, Json(pantry_item.into())));
+        let pantry_item = verify_user(&state, id, user_id, Access::Write).await?;
+        if !valid_quantity(
+            payload.quantity,
+            payload.weight_grams,
+            payload.volume_milli_litres,
+        ) {
+            return Err(AppError::UnprocessableEntity { error: eyre!("Invalid amount. Must indicate only one of quantity, weight_grams or volume_milli_litres.") });
+        }
+        if payload.household_id != pantry_item.household_id {
+            verify_owner(&state, pantry_item.user_id, user_id).await?;
+            verify_household(&state, payload.household_id, user_id).await?;
+        }
+        verify_location(&state, payload.location_id, user_id).await?;
+        let pantry_item = state
+            .db_client
+            .update_pantry_item(id, payload.into_dto(user_id))
+            .await?;
+        log::info!("Updated pantry item with id {id:?}");
+        Ok((StatusCode::OK, Json(pantry_item.into())))
+    }
+
+    /// Moves the pantry item to another location. Moving it into a freezer can push its
+    /// expiration date out to the ingredient's freezer shelf life.
+    async fn move_item(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+        Json(payload): Json<MovePayload>,
+    ) -> Result<(StatusCode, Json<PantryItemResponse>), AppError> {
+        verify_user(&state, id, user_id, Access::Write).await?;
+        let location = verify_location(&state, payload.location_id, user_id).await?;
+        let pantry_item = state.db_client.get_pantry_item(id).await?;
+        let mut expiration_date = pantry_item.expiration_date;
+        if payload.extend_expiration && location.is_some_and(|location| location.freezer) {
+            let ingredient = state
+                .db_client
+                .get_ingredient(pantry_item.ingredient_id)
+                .await?;
+            if let Some(days) = ingredient.freezer_shelf_life_days {
+                expiration_date = Some(frozen_expiration(
+                    expiration_date,
+                    Utc::now().date_naive(),
+                    u64::try_from(days).unwrap_or_default(),
+                ));
             }
         }
-        Err(AppError::Unauthorized)
+        let pantry_item = state
+            .db_client
+            .move_pantry_item(
+                id,
+                MoveDto {
+                    user_id,
+                    location_id: payload.location_id,
+                    expiration_date,
+                },
+            )
+            .await?;
+        log::info!(
+            "Moved pantry item with id {id:?} to location {:?}",
+            pantry_item.location_id
+        );
+        Ok((StatusCode::OK, Json(pantry_item.into())))
     }
 
     async fn delete(
         State(state): State<AppState>,
-        jar: CookieJar,
+        CurrentUser(user_id): CurrentUser,
         Path(id): Path<Uuid>,
     ) -> Result<StatusCode, AppError> {
-        if let Some(session_id) = jar.get(COOKIE_KEY) {
-            if let Some(user_id) = state.get_sessions_user(session_id.value_trimmed()).await? {
-                verify_user(&state, id, user_id).await?;
-                state.db_client.delete_pantry_item(id).await?;
-                {
-                    log::info!("Deleted pantry item with id {:?}", id);
-                    return Ok(StatusCode::NO_CONTENT);
-                }
-            }
-        }
-        Err(AppError::Unauthorized)
+        verify_user(&state, id, user_id, Access::Write).await?;
+        state.db_client.delete_pantry_item(id, user_id).await?;
+        log::info!("Deleted pantry item with id {:?}", id);
+        Ok(StatusCode::NO_CONTENT)
     }
 }
 
@@ -157,16 +409,72 @@ async fn verify_user(
     state: &AppState,
     pantry_item_id: Uuid,
     user_id: Uuid,
-) -> Result<(), VerifyError> {
-    if state.user_is_admin(user_id).await? {
-        return Ok(());
-    }
+    access: Access,
+) -> Result<PantryItemDto, VerifyError> {
     let pantry_item = state.db_client.get_pantry_item(pantry_item_id).await?;
-    if pantry_item.user_id == user_id {
+    if state
+        .has_access(
+            user_id,
+            pantry_item.user_id,
+            pantry_item.household_id,
+            access,
+        )
+        .await?
+    {
         log::info!("Got pantry item with id {:?}", pantry_item.id);
+        return Ok(pantry_item);
+    }
+    Err(VerifyError::Forbidden)
+}
+
+/// Only the owner of a pantry item, or an admin, decides which household it is shared with
+async fn verify_owner(state: &AppState, owner_id: Uuid, user_id: Uuid) -> Result<(), VerifyError> {
+    if owner_id == user_id || state.user_is_admin(user_id).await? {
         return Ok(());
     }
-    Err(VerifyError::Unauthorized)
+    Err(VerifyError::Forbidden)
+}
+
+/// Pantry items can only be shared with households the user may write to
+async fn verify_household(
+    state: &AppState,
+    household_id: Option<Uuid>,
+    user_id: Uuid,
+) -> Result<(), VerifyError> {
+    match household_id {
+        Some(household_id)
+            if !state
+                .household_allows(household_id, user_id, Access::Write)
+                .await? =>
+        {
+            Err(VerifyError::Forbidden)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Pantry items can only be put in locations the user may write to
+async fn verify_location(
+    state: &AppState,
+    location_id: Option<Uuid>,
+    user_id: Uuid,
+) -> Result<Option<LocationDto>, VerifyError> {
+    let Some(location_id) = location_id else {
+        return Ok(None);
+    };
+    let location = state.db_client.get_location(location_id).await?;
+    if state
+        .has_access(
+            user_id,
+            location.user_id,
+            location.household_id,
+            Access::Write,
+        )
+        .await?
+    {
+        return Ok(Some(location));
+    }
+    Err(VerifyError::Forbidden)
 }
 
 fn valid_quantity(
@@ -1,15 +1,29 @@
-use chrono::{NaiveDate, NaiveDateTime};
+use chrono::{Days, NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::database::pantry_item_events::dto::{
+    ConsumedDto, ConsumptionDto, ConsumptionParamsDto, ConsumptionPeriodDto, EventKind,
+    ListParamsDto as HistoryParamsDto, PantryItemEventJoinDto, PantryItemEventsListDto, Period,
+};
 use crate::database::pantry_items::dto::{
-    CreateDto, ListParamsDto, PantryItemDto, PantryItemJoinDto, PantryItemsListDto, UpdateDto,
+    ConsumedLotsDto, CreateDto, ListParamsDto, PantryItemDto, PantryItemGroupDto,
+    PantryItemGroupsListDto, PantryItemJoinDto, PantryItemLocationGroupDto,
+    PantryItemLocationGroupsListDto, UpdateDto,
 };
+use crate::database::products::dto::ProductDto;
+use crate::expiration::ExpirationDigest;
 use crate::server::payload::{MetadataResponse, DEFAULT_PER_PAGE};
+use crate::units::Measure;
+
+const DEFAULT_CONSUMPTION_DAYS: u64 = 90;
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct CreatePayload {
     pub ingredient_id: Uuid,
+    pub household_id: Option<Uuid>,
+    pub location_id: Option<Uuid>,
+    pub purchase_date: Option<NaiveDate>,
     pub expiration_date: Option<NaiveDate>,
     pub quantity: Option<i32>,
     pub weight_grams: Option<i32>,
@@ -23,6 +37,9 @@ impl CreatePayload {
         CreateDto {
             ingredient_id: self.ingredient_id,
             user_id,
+            household_id: self.household_id,
+            location_id: self.location_id,
+            purchase_date: self.purchase_date,
             expiration_date: self.expiration_date,
             quantity: self.quantity,
             weight_grams: self.weight_grams,
@@ -36,6 +53,9 @@ impl CreatePayload {
 #[derive(Deserialize, Serialize, Debug)]
 pub struct UpdatePayload {
     pub ingredient_id: Uuid,
+    pub household_id: Option<Uuid>,
+    pub location_id: Option<Uuid>,
+    pub purchase_date: Option<NaiveDate>,
     pub expiration_date: Option<NaiveDate>,
     pub quantity: Option<i32>,
     pub weight_grams: Option<i32>,
@@ -47,8 +67,11 @@ pub struct UpdatePayload {
 impl UpdatePayload {
     pub fn into_dto(self, user_id: Uuid) -> UpdateDto {
         UpdateDto {
-            ingredient_id: self.ingredient_id,
             user_id,
+            ingredient_id: self.ingredient_id,
+            household_id: self.household_id,
+            location_id: self.location_id,
+            purchase_date: self.purchase_date,
             expiration_date: self.expiration_date,
             quantity: self.quantity,
             weight_grams: self.weight_grams,
@@ -64,6 +87,7 @@ pub struct ListQueryParams {
     pub name_contains: Option<String>,
     pub max_expiration_date: Option<NaiveDate>,
     pub ingredient_id: Option<Uuid>,
+    pub location_id: Option<Uuid>,
     pub page: Option<u64>,
     pub per_page: Option<u64>,
 }
@@ -74,6 +98,7 @@ impl ListQueryParams {
             max_expiration_date: self.max_expiration_date,
             user_id: Some(user_id),
             ingredient_id: self.ingredient_id,
+            location_id: self.location_id,
             name_contains: self.name_contains,
             limit: self.per_page.unwrap_or(DEFAULT_PER_PAGE),
             offset: self.per_page.unwrap_or(DEFAULT_PER_PAGE) * (self.page.unwrap_or(1) - 1),
@@ -81,12 +106,112 @@ impl ListQueryParams {
     }
 }
 
+#[derive(Clone, Deserialize, Debug)]
+pub struct ExpiringQueryParams {
+    pub within_days: Option<u64>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ScanPayload {
+    pub barcode: String,
+    /// Household the pantry item is shared with if a new one is created
+    pub household_id: Option<Uuid>,
+    pub location_id: Option<Uuid>,
+    pub purchase_date: Option<NaiveDate>,
+    pub expiration_date: Option<NaiveDate>,
+}
+
+impl ScanPayload {
+    /// Pantry item holding one package of the product
+    pub fn into_dto(self, user_id: Uuid, product: &ProductDto) -> CreateDto {
+        let (measure, amount) = product.package().in_base_measure();
+        CreateDto {
+            ingredient_id: product.ingredient_id,
+            user_id,
+            household_id: self.household_id,
+            location_id: self.location_id,
+            purchase_date: self.purchase_date,
+            expiration_date: self.expiration_date,
+            quantity: (measure == Measure::Quantity).then_some(amount),
+            weight_grams: (measure == Measure::WeightGrams).then_some(amount),
+            volume_milli_litres: (measure == Measure::VolumeMilliLitres).then_some(amount),
+            essential: false,
+            running_low: None,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct HistoryQueryParams {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+}
+
+impl HistoryQueryParams {
+    pub fn into_dto(self, pantry_item_id: Uuid, visible_to: Option<Uuid>) -> HistoryParamsDto {
+        HistoryParamsDto {
+            pantry_item_id,
+            visible_to,
+            limit: self.per_page.unwrap_or(DEFAULT_PER_PAGE),
+            offset: self.per_page.unwrap_or(DEFAULT_PER_PAGE) * (self.page.unwrap_or(1) - 1),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PeriodPayload {
+    Day,
+    Week,
+    Month,
+}
+
+impl From<PeriodPayload> for Period {
+    fn from(val: PeriodPayload) -> Self {
+        match val {
+            PeriodPayload::Day => Period::Day,
+            PeriodPayload::Week => Period::Week,
+            PeriodPayload::Month => Period::Month,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct ConsumptionQueryParams {
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+    pub ingredient_id: Option<Uuid>,
+    pub period: Option<PeriodPayload>,
+}
+
+impl ConsumptionQueryParams {
+    /// Defaults to weekly consumption over the 90 days up to `today`.
+    pub fn into_dto(self, user_id: Uuid, today: NaiveDate) -> ConsumptionParamsDto {
+        let to_date = self.to_date.unwrap_or(today);
+        ConsumptionParamsDto {
+            user_id,
+            ingredient_id: self.ingredient_id,
+            from_date: self.from_date.unwrap_or_else(|| {
+                to_date
+                    .checked_sub_days(Days::new(DEFAULT_CONSUMPTION_DAYS))
+                    .unwrap_or(to_date)
+            }),
+            to_date,
+            period: self.period.unwrap_or(PeriodPayload::Week).into(),
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
 pub struct PantryItemResponse {
     pub id: Uuid,
     pub ingredient_id: Uuid,
     pub ingredient_name: Option<String>,
     pub user_id: Uuid,
+    pub household_id: Option<Uuid>,
+    pub location_id: Option<Uuid>,
+    pub location_name: Option<String>,
+    pub purchase_date: Option<String>,
     pub expiration_date: Option<String>,
     pub quantity: Option<i32>,
     pub weight_grams: Option<i32>,
@@ -104,6 +229,10 @@ impl From<PantryItemDto> for PantryItemResponse {
             ingredient_id: val.ingredient_id,
             ingredient_name: None,
             user_id: val.user_id,
+            household_id: val.household_id,
+            location_id: val.location_id,
+            location_name: None,
+            purchase_date: val.purchase_date.map(|date| date.to_string()),
             expiration_date: val.expiration_date.map(|date| date.to_string()),
             quantity: val.quantity,
             weight_grams: val.weight_grams,
@@ -123,6 +252,10 @@ impl From<PantryItemJoinDto> for PantryItemResponse {
             ingredient_id: val.ingredient_id,
             ingredient_name: Some(val.ingredient_name),
             user_id: val.user_id,
+            household_id: val.household_id,
+            location_id: val.location_id,
+            location_name: val.location_name,
+            purchase_date: val.purchase_date.map(|date| date.to_string()),
             expiration_date: val.expiration_date.map(|date| date.to_string()),
             quantity: val.quantity,
             weight_grams: val.weight_grams,
@@ -135,21 +268,288 @@ impl From<PantryItemJoinDto> for PantryItemResponse {
     }
 }
 
+/// Lots of one ingredient, earliest expiration first
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct PantryItemGroupResponse {
+    pub ingredient_id: Uuid,
+    pub ingredient_name: String,
+    /// Sum of the lots in each measure they are kept in
+    pub total: AmountResponse,
+    pub earliest_expiration_date: Option<String>,
+    pub lots: Vec<PantryItemResponse>,
+}
+
+impl From<PantryItemGroupDto> for PantryItemGroupResponse {
+    fn from(val: PantryItemGroupDto) -> Self {
+        let sum = |amount: fn(&PantryItemJoinDto) -> Option<i32>| {
+            val.lots
+                .iter()
+                .filter_map(amount)
+                .reduce(i32::saturating_add)
+        };
+        PantryItemGroupResponse {
+            ingredient_id: val.ingredient_id,
+            ingredient_name: val.ingredient_name.clone(),
+            total: AmountResponse {
+                quantity: sum(|lot| lot.quantity),
+                weight_grams: sum(|lot| lot.weight_grams),
+                volume_milli_litres: sum(|lot| lot.volume_milli_litres),
+            },
+            earliest_expiration_date: val
+                .lots
+                .iter()
+                .filter_map(|lot| lot.expiration_date)
+                .min()
+                .map(|date| date.to_string()),
+            lots: val.lots.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
 pub struct PantryItemListResponse {
     #[serde(rename = "_metadata")]
     pub metadata: MetadataResponse,
-    pub items: Vec<PantryItemResponse>,
+    pub items: Vec<PantryItemGroupResponse>,
 }
 
-impl From<PantryItemsListDto> for Vec<PantryItemResponse> {
-    fn from(val: PantryItemsListDto) -> Self {
+impl From<PantryItemGroupsListDto> for Vec<PantryItemGroupResponse> {
+    fn from(val: PantryItemGroupsListDto) -> Self {
         val.items.into_iter().map(Into::into).collect()
     }
 }
 
 impl PantryItemListResponse {
-    pub fn from(items: Vec<PantryItemResponse>, metadata: MetadataResponse) -> Self {
+    pub fn from(items: Vec<PantryItemGroupResponse>, metadata: MetadataResponse) -> Self {
         PantryItemListResponse { metadata, items }
     }
 }
+
+/// Lots kept in one location, `location_id` is `None` for lots without a location
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct PantryItemLocationGroupResponse {
+    pub location_id: Option<Uuid>,
+    pub location_name: Option<String>,
+    pub items: Vec<PantryItemGroupResponse>,
+}
+
+impl From<PantryItemLocationGroupDto> for PantryItemLocationGroupResponse {
+    fn from(val: PantryItemLocationGroupDto) -> Self {
+        PantryItemLocationGroupResponse {
+            location_id: val.location_id,
+            location_name: val.location_name,
+            items: val.items.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct PantryItemLocationListResponse {
+    pub items: Vec<PantryItemLocationGroupResponse>,
+}
+
+impl From<PantryItemLocationGroupsListDto> for PantryItemLocationListResponse {
+    fn from(val: PantryItemLocationGroupsListDto) -> Self {
+        PantryItemLocationListResponse {
+            items: val.items.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct MovePayload {
+    /// `None` takes the item out of its location
+    pub location_id: Option<Uuid>,
+    /// Extends the expiration date by the ingredient's freezer shelf life
+    /// if the location is a freezer
+    #[serde(default)]
+    pub extend_expiration: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ConsumePayload {
+    pub ingredient_id: Uuid,
+    pub amount: String,
+    pub unit: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct ConsumeResponse {
+    pub ingredient_id: Uuid,
+    /// Amount the pantry was short of, in `unit`
+    pub missing: i32,
+    pub unit: Option<String>,
+    /// Lots the amount was taken from, in the order they were used
+    pub lots: Vec<PantryItemResponse>,
+}
+
+impl ConsumeResponse {
+    pub fn from(ingredient_id: Uuid, val: ConsumedLotsDto) -> Self {
+        ConsumeResponse {
+            ingredient_id,
+            missing: val.missing,
+            unit: val.measure.unit().map(|unit| unit.symbol().to_owned()),
+            lots: val.lots.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct ExpirationDigestResponse {
+    pub until: NaiveDate,
+    pub items: Vec<PantryItemResponse>,
+}
+
+impl From<ExpirationDigest> for ExpirationDigestResponse {
+    fn from(val: ExpirationDigest) -> Self {
+        ExpirationDigestResponse {
+            until: val.until,
+            items: val.items.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EventKindPayload {
+    Created,
+    Updated,
+    Consumed,
+    Deleted,
+}
+
+impl From<EventKind> for EventKindPayload {
+    fn from(val: EventKind) -> Self {
+        match val {
+            EventKind::Created => EventKindPayload::Created,
+            EventKind::Updated => EventKindPayload::Updated,
+            EventKind::Consumed => EventKindPayload::Consumed,
+            EventKind::Deleted => EventKindPayload::Deleted,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct AmountResponse {
+    pub quantity: Option<i32>,
+    pub weight_grams: Option<i32>,
+    pub volume_milli_litres: Option<i32>,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct PantryItemEventResponse {
+    pub id: Uuid,
+    pub pantry_item_id: Uuid,
+    pub ingredient_id: Uuid,
+    pub ingredient_name: String,
+    pub user_id: Uuid,
+    pub household_id: Option<Uuid>,
+    pub kind: EventKindPayload,
+    pub before: Option<AmountResponse>,
+    pub after: Option<AmountResponse>,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<PantryItemEventJoinDto> for PantryItemEventResponse {
+    fn from(val: PantryItemEventJoinDto) -> Self {
+        PantryItemEventResponse {
+            id: val.id,
+            pantry_item_id: val.pantry_item_id,
+            ingredient_id: val.ingredient_id,
+            ingredient_name: val.ingredient_name,
+            user_id: val.user_id,
+            household_id: val.household_id,
+            before: (val.kind != EventKind::Created).then_some(AmountResponse {
+                quantity: val.quantity_before,
+                weight_grams: val.weight_grams_before,
+                volume_milli_litres: val.volume_milli_litres_before,
+            }),
+            after: (val.kind != EventKind::Deleted).then_some(AmountResponse {
+                quantity: val.quantity_after,
+                weight_grams: val.weight_grams_after,
+                volume_milli_litres: val.volume_milli_litres_after,
+            }),
+            kind: val.kind.into(),
+            created_at: val.created_at,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct PantryItemEventListResponse {
+    #[serde(rename = "_metadata")]
+    pub metadata: MetadataResponse,
+    pub items: Vec<PantryItemEventResponse>,
+}
+
+impl From<PantryItemEventsListDto> for Vec<PantryItemEventResponse> {
+    fn from(val: PantryItemEventsListDto) -> Self {
+        val.items.into_iter().map(Into::into).collect()
+    }
+}
+
+impl PantryItemEventListResponse {
+    pub fn from(items: Vec<PantryItemEventResponse>, metadata: MetadataResponse) -> Self {
+        PantryItemEventListResponse { metadata, items }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct ConsumedResponse {
+    pub quantity: i64,
+    pub weight_grams: i64,
+    pub volume_milli_litres: i64,
+}
+
+impl From<ConsumedDto> for ConsumedResponse {
+    fn from(val: ConsumedDto) -> Self {
+        ConsumedResponse {
+            quantity: val.quantity,
+            weight_grams: val.weight_grams,
+            volume_milli_litres: val.volume_milli_litres,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct ConsumptionPeriodResponse {
+    pub start: NaiveDate,
+    #[serde(flatten)]
+    pub consumed: ConsumedResponse,
+}
+
+impl From<ConsumptionPeriodDto> for ConsumptionPeriodResponse {
+    fn from(val: ConsumptionPeriodDto) -> Self {
+        ConsumptionPeriodResponse {
+            start: val.start,
+            consumed: val.consumed.into(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct ConsumptionResponse {
+    pub ingredient_id: Uuid,
+    pub ingredient_name: String,
+    pub total: ConsumedResponse,
+    pub periods: Vec<ConsumptionPeriodResponse>,
+}
+
+impl From<ConsumptionDto> for ConsumptionResponse {
+    fn from(val: ConsumptionDto) -> Self {
+        ConsumptionResponse {
+            ingredient_id: val.ingredient_id,
+            ingredient_name: val.ingredient_name,
+            total: val.total.into(),
+            periods: val.periods.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct ConsumptionStatsResponse {
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    pub period: PeriodPayload,
+    pub items: Vec<ConsumptionResponse>,
+}
@@ -0,0 +1,778 @@
+use num_rational::Ratio;
+use num_traits::CheckedAdd;
+
+use super::payload::ParsedRecipeIngredient;
+use crate::units::{parse_amount, Amount, Unit};
+
+/// Units that are counted rather than measured, by their singular name
+const COUNT_UNITS: &[(&str, &[&str])] = &[
+    ("bag", &["bag", "bags"]),
+    ("bottle", &["bottle", "bottles"]),
+    ("bunch", &["bunch", "bunches"]),
+    ("can", &["can", "cans", "tin", "tins"]),
+    ("clove", &["clove", "cloves"]),
+    ("dash", &["dash", "dashes"]),
+    ("drop", &["drop", "drops"]),
+    ("handful", &["handful", "handfuls"]),
+    ("head", &["head", "heads"]),
+    ("jar", &["jar", "jars"]),
+    (
+        "package",
+        &[
+            "package", "packages", "packet", "packets", "pack", "packs", "pkg",
+        ],
+    ),
+    ("piece", &["piece", "pieces"]),
+    ("pinch", &["pinch", "pinches"]),
+    ("slice", &["slice", "slices"]),
+    ("sprig", &["sprig", "sprigs"]),
+    ("stalk", &["stalk", "stalks"]),
+    ("stick", &["stick", "sticks"]),
+];
+
+/// Phrases that mark an ingredient as optional, removed from the name and note
+const OPTIONAL_PHRASES: &[&str] = &[
+    "to taste",
+    "optional",
+    "if desired",
+    "as needed",
+    "for serving",
+    "to serve",
+    "for garnish",
+];
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(Amount),
+    Dash,
+    Word(String),
+}
+
+/// Parses one ingredient line, e.g. "1-2 cloves garlic, minced".
+/// Whatever cannot be read as an amount or unit stays in the name.
+pub fn parse_ingredient(line: &str) -> ParsedRecipeIngredient {
+    let line = normalize(line);
+    let mut optional = false;
+    let mut notes = Vec::new();
+
+    let (head, parentheses) = take_parentheses(&line);
+    for note in parentheses {
+        push_note(&mut notes, &note, &mut optional);
+    }
+    let (head, note) = split_note(&head);
+    if let Some(note) = note {
+        push_note(&mut notes, &note, &mut optional);
+    }
+    let head = strip_optional(&head, &mut optional);
+
+    let tokens = tokenize(&head);
+    let mut position = 0;
+    let (amount, amount_max) = read_amount(&tokens, &mut position);
+    let unit = read_unit(&tokens, &mut position, amount.is_some());
+    if unit.is_some() && word_at(&tokens, position) == Some("of") {
+        position += 1;
+    }
+    let name = tokens[position..]
+        .iter()
+        .map(|token| match token {
+            Token::Number(amount) => format_amount(*amount),
+            Token::Dash => "-".to_owned(),
+            Token::Word(word) => word.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    ParsedRecipeIngredient {
+        amount: amount.map(format_amount),
+        amount_max: amount_max.map(format_amount),
+        unit,
+        name,
+        note: if notes.is_empty() {
+            None
+        } else {
+            Some(notes.join(", "))
+        },
+        optional,
+        ingredient_id: None,
+        confidence: None,
+    }
+}
+
+/// Collapses whitespace and unifies dashes
+fn normalize(line: &str) -> String {
+    line.replace(['–', '—', '‐'], "-")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Removes parenthesised text from the line and returns it separately
+fn take_parentheses(line: &str) -> (String, Vec<String>) {
+    let mut head = String::new();
+    let mut groups = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0_usize;
+    for c in line.chars() {
+        match c {
+            '(' | '[' => {
+                if depth > 0 {
+                    current.push(c);
+                }
+                depth += 1;
+            }
+            ')' | ']' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    groups.push(std::mem::take(&mut current));
+                    head.push(' ');
+                } else {
+                    current.push(c);
+                }
+            }
+            _ if depth > 0 => current.push(c),
+            _ => head.push(c),
+        }
+    }
+    if depth > 0 {
+        groups.push(current);
+    }
+    (normalize(&head), groups)
+}
+
+/// Splits at the first comma that is not a decimal separator, "garlic, minced"
+fn split_note(line: &str) -> (String, Option<String>) {
+    let chars: Vec<char> = line.chars().collect();
+    for (i, c) in chars.iter().enumerate() {
+        let decimal = i > 0
+            && chars[i - 1].is_ascii_digit()
+            && chars.get(i + 1).is_some_and(char::is_ascii_digit);
+        if *c == ',' && !decimal {
+            let head: String = chars[..i].iter().collect();
+            let note: String = chars[i + 1..].iter().collect();
+            return (head.trim().to_owned(), Some(note.trim().to_owned()));
+        }
+    }
+    (line.to_owned(), None)
+}
+
+fn push_note(notes: &mut Vec<String>, note: &str, optional: &mut bool) {
+    let note = strip_optional(note, optional);
+    let note = note.trim_matches(|c: char| c == ',' || c == ';' || c.is_whitespace());
+    if !note.is_empty() {
+        notes.push(note.to_owned());
+    }
+}
+
+/// Removes the optional phrases, setting `optional` if there were any
+fn strip_optional(text: &str, optional: &mut bool) -> String {
+    let mut words: Vec<&str> = text.split_whitespace().collect();
+    for phrase in OPTIONAL_PHRASES {
+        let phrase: Vec<&str> = phrase.split(' ').collect();
+        while let Some(start) = words.windows(phrase.len()).position(|window| {
+            window
+                .iter()
+                .zip(&phrase)
+                .all(|(word, expected)| clean(word).eq_ignore_ascii_case(expected))
+        }) {
+            words.drain(start..start + phrase.len());
+            *optional = true;
+        }
+    }
+    let text = words.join(" ");
+    if *optional {
+        // Joining words such as "or" or "and" left dangling by the removal
+        let trimmed = text.trim_end_matches(|c: char| c == ',' || c.is_whitespace());
+        for dangling in [" or", " and", " plus"] {
+            if let Some(stripped) = trimmed.strip_suffix(dangling) {
+                return stripped.to_owned();
+            }
+        }
+        return trimmed.to_owned();
+    }
+    text
+}
+
+fn clean(word: &str) -> &str {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+}
+
+/// Splits into numbers, dashes and words, separating numbers glued to units ("200g")
+/// and ranges glued together ("1-2")
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    for word in text.split_whitespace() {
+        let mut rest = word;
+        while !rest.is_empty() {
+            if let Some(stripped) = rest.strip_prefix('-') {
+                tokens.push(Token::Dash);
+                rest = stripped;
+                continue;
+            }
+            let number_len = number_prefix_len(rest);
+            if number_len > 0 && splits_after_number(&rest[number_len..]) {
+                if let Some(amount) = parse_amount(&rest[..number_len]) {
+                    tokens.push(Token::Number(amount));
+                    rest = &rest[number_len..];
+                    continue;
+                }
+            }
+            // Words such as "all-purpose", "7-up" or "2%" stay whole
+            tokens.push(Token::Word(rest.to_owned()));
+            rest = "";
+        }
+    }
+    tokens
+}
+
+/// Numbers are split from units ("200g") and ranges ("1-2"), not from other text
+fn splits_after_number(rest: &str) -> bool {
+    let mut chars = rest.chars();
+    match chars.next() {
+        None => true,
+        Some('-') => chars
+            .next()
+            .is_some_and(|c| c.is_ascii_digit() || is_vulgar_fraction(c)),
+        Some(c) => c.is_alphabetic(),
+    }
+}
+
+/// Length of the leading amount, e.g. "1/2", "1.5", "1½" or "½"
+fn number_prefix_len(word: &str) -> usize {
+    let mut len = 0;
+    let mut previous: Option<char> = None;
+    for c in word.chars() {
+        let accepted = c.is_ascii_digit()
+            || is_vulgar_fraction(c)
+            || ((c == '/' || c == '.' || c == ',') && previous.is_some_and(|p| p.is_ascii_digit()));
+        if !accepted {
+            break;
+        }
+        len += c.len_utf8();
+        previous = Some(c);
+        if is_vulgar_fraction(c) {
+            break;
+        }
+    }
+    // A trailing separator belongs to the text, "2." or "1,"
+    if previous.is_some_and(|p| p == '/' || p == '.' || p == ',') {
+        len -= 1;
+    }
+    len
+}
+
+fn is_vulgar_fraction(c: char) -> bool {
+    matches!(
+        c,
+        '½' | '⅓'
+            | '⅔'
+            | '¼'
+            | '¾'
+            | '⅕'
+            | '⅖'
+            | '⅗'
+            | '⅘'
+            | '⅙'
+            | '⅚'
+            | '⅛'
+            | '⅜'
+            | '⅝'
+            | '⅞'
+    )
+}
+
+/// Reads "1", "1 1/2", "a", "1-2", "1 to 2" and "1 or 2"
+fn read_amount(tokens: &[Token], position: &mut usize) -> (Option<Amount>, Option<Amount>) {
+    let Some(amount) = read_number(tokens, position).or_else(|| {
+        let article = word_at(tokens, *position)?;
+        if !(article.eq_ignore_ascii_case("a") || article.eq_ignore_ascii_case("an")) {
+            return None;
+        }
+        // Only "a pinch of salt", not "a bit more" without a unit
+        let mut next = *position + 1;
+        read_unit(tokens, &mut next, true)?;
+        *position += 1;
+        Some(Ratio::from_integer(1))
+    }) else {
+        return (None, None);
+    };
+    let separator = match tokens.get(*position) {
+        Some(Token::Dash) => Some(1),
+        Some(Token::Word(word)) if word == "to" || word == "or" => Some(1),
+        _ => None,
+    };
+    if let Some(skip) = separator {
+        let mut next = *position + skip;
+        if let Some(max) = read_number(tokens, &mut next) {
+            *position = next;
+            return (Some(amount), Some(max));
+        }
+    }
+    (Some(amount), None)
+}
+
+/// Reads a number, joining a whole number with a following fraction, "1 1/2".
+/// Numbers too large to join are not amounts.
+fn read_number(tokens: &[Token], position: &mut usize) -> Option<Amount> {
+    let Some(Token::Number(amount)) = tokens.get(*position) else {
+        return None;
+    };
+    let mut amount = *amount;
+    let mut next = *position + 1;
+    if amount.is_integer() {
+        if let Some(Token::Number(fraction)) = tokens.get(next) {
+            if !fraction.is_integer() && *fraction < Ratio::from_integer(1) {
+                amount = amount.checked_add(fraction)?;
+                next += 1;
+            }
+        }
+    }
+    *position = next;
+    Some(amount)
+}
+
+/// Reads a measurement unit, normalised to its symbol, or a count unit.
+/// Without an amount only "pinch of salt" style units are read.
+fn read_unit(tokens: &[Token], position: &mut usize, has_amount: bool) -> Option<String> {
+    let word = word_at(tokens, *position)?;
+    if let Some(next) = word_at(tokens, *position + 1) {
+        if let Ok(unit) = format!("{word} {next}").parse::<Unit>() {
+            *position += 2;
+            return Some(unit.symbol().to_owned());
+        }
+    }
+    if !has_amount && word_at(tokens, *position + 1) != Some("of") {
+        return None;
+    }
+    let lower = word.trim_end_matches('.').to_lowercase();
+    if let Ok(unit) = lower.parse::<Unit>() {
+        // A lone "c" only means cup after an amount
+        if lower != "c" || has_amount {
+            *position += 1;
+            return Some(unit.symbol().to_owned());
+        }
+    }
+    let count_unit = COUNT_UNITS
+        .iter()
+        .find(|(_, names)| names.contains(&lower.as_str()))
+        .map(|(unit, _)| (*unit).to_owned())?;
+    *position += 1;
+    Some(count_unit)
+}
+
+fn word_at(tokens: &[Token], position: usize) -> Option<&str> {
+    match tokens.get(position) {
+        Some(Token::Word(word)) => Some(word.as_str()),
+        _ => None,
+    }
+}
+
+/// Formats as "2", "1/2" or "1 1/2", which `parse_amount` reads back
+fn format_amount(amount: Amount) -> String {
+    let whole = amount.trunc();
+    let fraction = amount.fract();
+    match (whole.to_integer(), fraction.numer()) {
+        (_, 0) => whole.to_string(),
+        (0, _) => fraction.to_string(),
+        _ => format!("{whole} {fraction}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Case {
+        line: &'static str,
+        amount: Option<&'static str>,
+        amount_max: Option<&'static str>,
+        unit: Option<&'static str>,
+        name: &'static str,
+        note: Option<&'static str>,
+        optional: bool,
+    }
+
+    const fn case(
+        line: &'static str,
+        amount: Option<&'static str>,
+        unit: Option<&'static str>,
+        name: &'static str,
+    ) -> Case {
+        Case {
+            line,
+            amount,
+            amount_max: None,
+            unit,
+            name,
+            note: None,
+            optional: false,
+        }
+    }
+
+    const fn range(
+        line: &'static str,
+        amount: &'static str,
+        amount_max: &'static str,
+        unit: Option<&'static str>,
+        name: &'static str,
+        note: Option<&'static str>,
+    ) -> Case {
+        Case {
+            line,
+            amount: Some(amount),
+            amount_max: Some(amount_max),
+            unit,
+            name,
+            note,
+            optional: false,
+        }
+    }
+
+    const fn noted(
+        line: &'static str,
+        amount: Option<&'static str>,
+        unit: Option<&'static str>,
+        name: &'static str,
+        note: &'static str,
+    ) -> Case {
+        Case {
+            line,
+            amount,
+            amount_max: None,
+            unit,
+            name,
+            note: Some(note),
+            optional: false,
+        }
+    }
+
+    const fn optional(
+        line: &'static str,
+        amount: Option<&'static str>,
+        unit: Option<&'static str>,
+        name: &'static str,
+        note: Option<&'static str>,
+    ) -> Case {
+        Case {
+            line,
+            amount,
+            amount_max: None,
+            unit,
+            name,
+            note,
+            optional: true,
+        }
+    }
+
+    const CASES: &[Case] = &[
+        // Whole numbers, with and without units
+        case("2 eggs", Some("2"), None, "eggs"),
+        case("1 onion", Some("1"), None, "onion"),
+        case("3 cups flour", Some("3"), Some("cup"), "flour"),
+        case("1 cup sugar", Some("1"), Some("cup"), "sugar"),
+        case(
+            "2 tablespoons olive oil",
+            Some("2"),
+            Some("tbsp"),
+            "olive oil",
+        ),
+        case("1 tsp salt", Some("1"), Some("tsp"), "salt"),
+        case("1 tsp. baking soda", Some("1"), Some("tsp"), "baking soda"),
+        case("2 Tbsp butter", Some("2"), Some("tbsp"), "butter"),
+        case("4 oz cheddar", Some("4"), Some("oz"), "cheddar"),
+        case("1 lb ground beef", Some("1"), Some("lb"), "ground beef"),
+        case("2 pounds potatoes", Some("2"), Some("lb"), "potatoes"),
+        case("1 pint cream", Some("1"), Some("pt"), "cream"),
+        case("1 quart stock", Some("1"), Some("qt"), "stock"),
+        case("1 gallon water", Some("1"), Some("gal"), "water"),
+        case("8 fl oz milk", Some("8"), Some("fl oz"), "milk"),
+        case("2 fluid ounces rum", Some("2"), Some("fl oz"), "rum"),
+        case("2 c flour", Some("2"), Some("cup"), "flour"),
+        // Metric, glued and separate
+        case("200g flour", Some("200"), Some("g"), "flour"),
+        case("200 g flour", Some("200"), Some("g"), "flour"),
+        case("1kg potatoes", Some("1"), Some("kg"), "potatoes"),
+        case("500ml milk", Some("500"), Some("ml"), "milk"),
+        case("1 l water", Some("1"), Some("l"), "water"),
+        case("250 grams butter", Some("250"), Some("g"), "butter"),
+        case("1 litre stock", Some("1"), Some("l"), "stock"),
+        case("2 liters water", Some("2"), Some("l"), "water"),
+        case("100 millilitres cream", Some("100"), Some("ml"), "cream"),
+        // Fractions and decimals
+        case("1/2 cup milk", Some("1/2"), Some("cup"), "milk"),
+        case("1 1/2 cups sugar", Some("1 1/2"), Some("cup"), "sugar"),
+        case("½ tsp salt", Some("1/2"), Some("tsp"), "salt"),
+        case("1½ cups", Some("1 1/2"), Some("cup"), ""),
+        case("1 ½ cups rice", Some("1 1/2"), Some("cup"), "rice"),
+        case("¾ cup water", Some("3/4"), Some("cup"), "water"),
+        case("2⅓ cups flour", Some("2 1/3"), Some("cup"), "flour"),
+        case(
+            "1.5 kg pork shoulder",
+            Some("1 1/2"),
+            Some("kg"),
+            "pork shoulder",
+        ),
+        case("0.5 l milk", Some("1/2"), Some("l"), "milk"),
+        case("1,5 kg potatoes", Some("1 1/2"), Some("kg"), "potatoes"),
+        case(".5 tsp pepper", None, None, ".5 tsp pepper"),
+        // Ranges
+        range(
+            "1-2 cloves garlic, minced",
+            "1",
+            "2",
+            Some("clove"),
+            "garlic",
+            Some("minced"),
+        ),
+        range("1 - 2 tbsp honey", "1", "2", Some("tbsp"), "honey", None),
+        range("1–2 tbsp honey", "1", "2", Some("tbsp"), "honey", None),
+        range("2 to 3 carrots", "2", "3", None, "carrots", None),
+        range(
+            "2 or 3 sprigs thyme",
+            "2",
+            "3",
+            Some("sprig"),
+            "thyme",
+            None,
+        ),
+        range(
+            "1/2-1 tsp chilli flakes",
+            "1/2",
+            "1",
+            Some("tsp"),
+            "chilli flakes",
+            None,
+        ),
+        range("3-4 cups stock", "3", "4", Some("cup"), "stock", None),
+        range("200-250g pasta", "200", "250", Some("g"), "pasta", None),
+        // Count units
+        case("2 cloves garlic", Some("2"), Some("clove"), "garlic"),
+        case("1 clove garlic", Some("1"), Some("clove"), "garlic"),
+        case("1 can coconut milk", Some("1"), Some("can"), "coconut milk"),
+        case(
+            "2 tins chopped tomatoes",
+            Some("2"),
+            Some("can"),
+            "chopped tomatoes",
+        ),
+        case("1 bunch coriander", Some("1"), Some("bunch"), "coriander"),
+        case("4 slices bread", Some("4"), Some("slice"), "bread"),
+        case("1 stick butter", Some("1"), Some("stick"), "butter"),
+        case("2 stalks celery", Some("2"), Some("stalk"), "celery"),
+        case("1 head lettuce", Some("1"), Some("head"), "lettuce"),
+        case("1 package yeast", Some("1"), Some("package"), "yeast"),
+        case("2 handfuls spinach", Some("2"), Some("handful"), "spinach"),
+        case(
+            "3 drops vanilla extract",
+            Some("3"),
+            Some("drop"),
+            "vanilla extract",
+        ),
+        noted(
+            "2 (14 oz) cans tomatoes",
+            Some("2"),
+            Some("can"),
+            "tomatoes",
+            "14 oz",
+        ),
+        noted(
+            "1 (400g) tin chickpeas, drained and rinsed",
+            Some("1"),
+            Some("can"),
+            "chickpeas",
+            "400g, drained and rinsed",
+        ),
+        // Articles and "of"
+        case("a pinch of salt", Some("1"), Some("pinch"), "salt"),
+        case("A pinch of salt", Some("1"), Some("pinch"), "salt"),
+        case("a dash of hot sauce", Some("1"), Some("dash"), "hot sauce"),
+        case(
+            "an 8 oz block of cheese",
+            None,
+            None,
+            "an 8 oz block of cheese",
+        ),
+        case("pinch of nutmeg", None, Some("pinch"), "nutmeg"),
+        case("2 cups of milk", Some("2"), Some("cup"), "milk"),
+        case("a cup of flour", Some("1"), Some("cup"), "flour"),
+        case("a few basil leaves", None, None, "a few basil leaves"),
+        // Preparation notes
+        noted(
+            "1 onion, finely chopped",
+            Some("1"),
+            None,
+            "onion",
+            "finely chopped",
+        ),
+        noted(
+            "2 carrots, peeled and diced",
+            Some("2"),
+            None,
+            "carrots",
+            "peeled and diced",
+        ),
+        noted("3 eggs, beaten", Some("3"), None, "eggs", "beaten"),
+        noted(
+            "1 cup parsley (chopped)",
+            Some("1"),
+            Some("cup"),
+            "parsley",
+            "chopped",
+        ),
+        noted(
+            "100 g butter, softened",
+            Some("100"),
+            Some("g"),
+            "butter",
+            "softened",
+        ),
+        noted(
+            "1 lemon, zested and juiced",
+            Some("1"),
+            None,
+            "lemon",
+            "zested and juiced",
+        ),
+        noted("Chives, snipped", None, None, "Chives", "snipped"),
+        noted("1 [large] egg", Some("1"), None, "egg", "large"),
+        // Optional ingredients
+        optional("salt to taste", None, None, "salt", None),
+        optional(
+            "Salt and pepper, to taste",
+            None,
+            None,
+            "Salt and pepper",
+            None,
+        ),
+        optional(
+            "salt and pepper to taste",
+            None,
+            None,
+            "salt and pepper",
+            None,
+        ),
+        optional(
+            "1 tbsp sesame seeds (optional)",
+            Some("1"),
+            Some("tbsp"),
+            "sesame seeds",
+            None,
+        ),
+        optional(
+            "1 tbsp sesame seeds, optional",
+            Some("1"),
+            Some("tbsp"),
+            "sesame seeds",
+            None,
+        ),
+        optional("optional: 1 chilli", Some("1"), None, "chilli", None),
+        optional(
+            "fresh parsley, for garnish",
+            None,
+            None,
+            "fresh parsley",
+            None,
+        ),
+        optional(
+            "1/2 cup walnuts, chopped (optional)",
+            Some("1/2"),
+            Some("cup"),
+            "walnuts",
+            Some("chopped"),
+        ),
+        optional("Sugar, if desired", None, None, "Sugar", None),
+        optional("water as needed", None, None, "water", None),
+        optional("sour cream, to serve", None, None, "sour cream", None),
+        // No amount or unit
+        case("Salt", None, None, "Salt"),
+        case("olive oil", None, None, "olive oil"),
+        case("cup cakes", None, None, "cup cakes"),
+        case("c", None, None, "c"),
+        case("Ice", None, None, "Ice"),
+        // Words and numbers that are part of the name
+        case("2 large eggs", Some("2"), None, "large eggs"),
+        case(
+            "1 cup all-purpose flour",
+            Some("1"),
+            Some("cup"),
+            "all-purpose flour",
+        ),
+        case("2 cups 2% milk", Some("2"), Some("cup"), "2% milk"),
+        case("7-up", None, None, "7-up"),
+        case("1 x-large egg", Some("1"), None, "x-large egg"),
+        case("  2   cups   water  ", Some("2"), Some("cup"), "water"),
+        case("1 g.", Some("1"), Some("g"), ""),
+        case("3", Some("3"), None, ""),
+        // Numbers too large to be amounts
+        case(
+            "9223372036854775807 1/2 cups",
+            None,
+            None,
+            "9223372036854775807 1/2 cups",
+        ),
+        case(
+            "9223372036854775807.5 cups sugar",
+            None,
+            None,
+            "9223372036854775807.5 cups sugar",
+        ),
+        case(
+            "9223372036854775808 eggs",
+            None,
+            None,
+            "9223372036854775808 eggs",
+        ),
+        case(
+            "9223372036854775807 cups flour",
+            Some("9223372036854775807"),
+            Some("cup"),
+            "flour",
+        ),
+        case(
+            "0.123456789012345678 cup milk",
+            Some("61728394506172839/500000000000000000"),
+            Some("cup"),
+            "milk",
+        ),
+    ];
+
+    #[test]
+    fn corpus() {
+        for case in CASES {
+            let parsed = parse_ingredient(case.line);
+            assert_eq!(
+                parsed.amount.as_deref(),
+                case.amount,
+                "amount of {:?}",
+                case.line
+            );
+            assert_eq!(
+                parsed.amount_max.as_deref(),
+                case.amount_max,
+                "amount_max of {:?}",
+                case.line
+            );
+            assert_eq!(parsed.unit.as_deref(), case.unit, "unit of {:?}", case.line);
+            assert_eq!(parsed.name, case.name, "name of {:?}", case.line);
+            assert_eq!(parsed.note.as_deref(), case.note, "note of {:?}", case.line);
+            assert_eq!(
+                parsed.optional, case.optional,
+                "optional of {:?}",
+                case.line
+            );
+        }
+    }
+
+    #[test]
+    fn amounts_read_back() {
+        for case in CASES {
+            let parsed = parse_ingredient(case.line);
+            for amount in [parsed.amount, parsed.amount_max].into_iter().flatten() {
+                assert!(
+                    parse_amount(&amount).is_some(),
+                    "{amount:?} of {:?}",
+                    case.line
+                );
+            }
+        }
+    }
+}
@@ -1,4 +1,9 @@
 use serde::{Deserialize, Serialize};
+use titlecase::titlecase;
+use uuid::Uuid;
+
+use crate::database::recipes::dto::NewRecipeIngredientDto;
+use crate::matching::AUTO_MATCH_CONFIDENCE;
 
 #[derive(Clone, Deserialize, Debug)]
 pub struct ListQueryParams {
@@ -8,8 +13,32 @@ pub struct ListQueryParams {
 #[derive(Deserialize, Serialize, Debug, Default)]
 pub struct ParsedRecipeIngredient {
     pub amount: Option<String>,
+    /// Upper bound of a range such as "1-2"
+    pub amount_max: Option<String>,
     pub unit: Option<String>,
     pub name: String,
+    /// Preparation such as "finely chopped"
+    pub note: Option<String>,
+    /// Set for "to taste", "optional" and similar
+    pub optional: bool,
+    /// Existing ingredient that best matches the name
+    pub ingredient_id: Option<Uuid>,
+    /// How closely the name matches the ingredient, from 0 to 1
+    pub confidence: Option<f64>,
+}
+
+impl From<ParsedRecipeIngredient> for NewRecipeIngredientDto {
+    fn from(val: ParsedRecipeIngredient) -> Self {
+        NewRecipeIngredientDto {
+            name: titlecase(val.name.trim()),
+            ingredient_id: val
+                .ingredient_id
+                .filter(|_| val.confidence >= Some(AUTO_MATCH_CONFIDENCE)),
+            amount: val.amount,
+            unit: val.unit,
+            optional: val.optional,
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -9,20 +9,24 @@ use axum::{
 };
 use color_eyre::eyre::eyre;
 use htmlentity::entity::ICodedDataTrait;
-use scraper::{Html, Selector};
-use serde_json::json;
 use serde_json::Value;
 use std::borrow::Borrow;
+use std::fmt::Write;
 use thiserror::Error;
 use url::Url;
 use urlencoding::decode;
 
 use self::payload::{ListQueryParams, ParsedRecipeLinkResponse};
+use crate::redis::RedisCommands;
+use crate::server::routes::auth::CurrentUser;
 use crate::server::routes::errors::AppError;
 use crate::server::routes::parse_ingredients::parse_ingredients;
 use crate::server::routes::parse_ingredients::payload::ParsedRecipeIngredient;
 use crate::server::AppState;
 
+/// Parsed recipes are cached under this prefix followed by the link
+const RECIPE_CACHE_PREFIX: &str = "recipe_link:";
+
 pub struct ParsedRecipeLinkRouter {}
 
 impl ParsedRecipeLinkRouter {
@@ -30,18 +34,23 @@ impl ParsedRecipeLinkRouter {
         Router::new().route("/", get(ParsedRecipeLinkRouter::parse_recipe_link))
     }
 
-    #[allow(clippy::unused_async)]
     async fn parse_recipe_link(
-        State(_): State<AppState>,
+        State(state): State<AppState>,
+        _user: CurrentUser,
         Query(query_params): Query<ListQueryParams>,
     ) -> Result<(StatusCode, Json<ParsedRecipeLinkResponse>), AppError> {
         if let Ok(link) = decode(&query_params.link) {
-            let json = get_recipe_json(link.borrow()).await?;
+            let json = get_recipe_json(&state, link.borrow()).await?;
             let name = get_name(&json);
             let prep_time_mins = get_time_field(&json, "prepTime");
             let total_time_mins = get_time_field(&json, "totalTime");
             let image = get_image(&json);
-            let ingredients = get_ingredients(&json);
+            let mut ingredients = get_ingredients(&json);
+            state
+                .ingredient_matcher
+                .get(state.db_client.as_ref())
+                .await?
+                .annotate(&mut ingredients);
             let instructions = get_instructions(&json);
             return Ok((
                 StatusCode::OK,
@@ -69,66 +78,58 @@ pub enum GetRecipeJsonError {
     BadFormat { link: String, err: String },
 }
 
-async fn get_recipe_json(link: &str) -> Result<Value, GetRecipeJsonError> {
-    let response = match reqwest::get(link).await {
-        Ok(res) => res,
-        Err(e) => {
-            return Err(GetRecipeJsonError::LinkUnavailable {
-                link: link.to_owned(),
-                err: e.to_string(),
-            })
-        }
-    };
-    let Ok(xml) = response.text().await else {
-        return Err(GetRecipeJsonError::BadFormat {
-            link: link.to_owned(),
-            err: "No body".to_owned(),
-        });
-    };
-    let html = Html::parse_fragment(&xml);
-    let Ok(selector) = Selector::parse("script") else {
+/// Returns the cached recipe for the link, or fetches and scrapes it
+pub(crate) async fn get_recipe_json(
+    state: &AppState,
+    link: &str,
+) -> Result<Value, GetRecipeJsonError> {
+    let Ok(url) = Url::parse(link) else {
         return Err(GetRecipeJsonError::BadFormat {
             link: link.to_owned(),
-            err: "No script elements".to_owned(),
+            err: "Invalid link".to_owned(),
         });
     };
-    if let Some(element) = html.select(&selector).find(|el| {
-        el.attr("type") == Some("application/ld+json")
-            && el.inner_html().contains("recipeIngredient")
-    }) {
-        let mut json: serde_json::Value =
-            serde_json::from_str(&element.text().collect::<Vec<&str>>().join("")).unwrap();
-        if json.is_object() {
-            if let Some(graph) = json.get("@graph") {
-                json = graph.clone();
-            } else {
-                return Err(GetRecipeJsonError::BadFormat {
-                    link: link.to_owned(),
-                    err: "Could not parse recipe element".to_owned(),
-                });
-            }
-        }
-        if let Some(arr) = json.as_array() {
-            if let Some(j) = arr.iter().find(|&v| {
-                let Some(otype) = v.get("@type") else {
-                    return false;
-                };
-                match otype.as_array() {
-                    Some(arr) => arr.contains(&json!("Recipe")),
-                    None => otype.eq(&json!("Recipe")),
-                }
-            }) {
-                return Ok(j.clone());
+    let cache_key = format!("{RECIPE_CACHE_PREFIX}{url}");
+    match state.redis_sender.get(&cache_key).await {
+        Ok(Some(cached)) => {
+            if let Ok(json) = serde_json::from_str(&cached) {
+                return Ok(json);
             }
         }
+        Ok(None) => {}
+        Err(err) => log::warn!("Could not read cached recipe for {link}: {err}"),
+    }
+    let body =
+        state
+            .fetcher
+            .get_text(&url)
+            .await
+            .map_err(|err| GetRecipeJsonError::LinkUnavailable {
+                link: link.to_owned(),
+                err: err.to_string(),
+            })?;
+    let json = state
+        .scrapers
+        .scrape(&url, &body)
+        .ok_or_else(|| GetRecipeJsonError::BadFormat {
+            link: link.to_owned(),
+            err: "No recipe element".to_owned(),
+        })?;
+    if let Err(err) = state
+        .redis_sender
+        .set(
+            &cache_key,
+            &json.to_string(),
+            Some(state.recipe_cache_ttl_secs),
+        )
+        .await
+    {
+        log::warn!("Could not cache recipe for {link}: {err}");
     }
-    Err(GetRecipeJsonError::BadFormat {
-        link: link.to_owned(),
-        err: "No recipe element".to_owned(),
-    })
+    Ok(json)
 }
 
-fn get_name(json: &Value) -> Option<String> {
+pub(crate) fn get_name(json: &Value) -> Option<String> {
     if let Some(name) = json.get("name") {
         if let Some(name) = name.as_str() {
             return Some(name.to_owned());
@@ -137,7 +138,7 @@ fn get_name(json: &Value) -> Option<String> {
     None
 }
 
-fn get_time_field(json: &Value, key: &str) -> Option<u32> {
+pub(crate) fn get_time_field(json: &Value, key: &str) -> Option<u32> {
     if let Some(time) = json.get(key) {
         if let Some(time) = time.as_str() {
             return iso8601_to_mins(time);
@@ -146,21 +147,22 @@ fn get_time_field(json: &Value, key: &str) -> Option<u32> {
     None
 }
 
-fn get_instructions(json: &Value) -> Option<String> {
+pub(crate) fn get_instructions(json: &Value) -> Option<String> {
     if let Some(instructions) = json.get("recipeInstructions") {
         if let Some(instructions) = instructions.as_array() {
             let mut result = String::new();
             for (num, instruct) in instructions.iter().enumerate() {
                 if let Some(text) = instruct.get("text") {
-                    result += &format!(
-                        "{}. {}\n",
+                    let _ = writeln!(
+                        result,
+                        "{}. {}",
                         num + 1,
                         htmlentity::entity::decode(text.as_str().unwrap().as_bytes())
                             .to_string()
                             .unwrap()
                     );
                 } else {
-                    result += &format!("{}. ---\n", num + 1);
+                    let _ = writeln!(result, "{}. ---", num + 1);
                 }
             }
             return Some(result);
@@ -185,7 +187,7 @@ fn iso8601_to_mins(duration: &str) -> Option<u32> {
     None
 }
 
-fn get_image(json: &Value) -> Option<Url> {
+pub(crate) fn get_image(json: &Value) -> Option<Url> {
     if let Some(image) = json.get("image") {
         match image {
             Value::String(s) => return Url::parse(s).ok(),
@@ -216,7 +218,7 @@ fn get_image(json: &Value) -> Option<Url> {
     None
 }
 
-fn get_ingredients(json: &Value) -> Vec<ParsedRecipeIngredient> {
+pub(crate) fn get_ingredients(json: &Value) -> Vec<ParsedRecipeIngredient> {
     if let Some(ingredients) = json.get("recipeIngredient") {
         if let Some(arr) = ingredients.as_array() {
             let mut ingredients = Vec::new();
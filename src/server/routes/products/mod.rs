@@ -0,0 +1,81 @@
+mod payload;
+
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    routing::{delete, get},
+    Router,
+};
+use color_eyre::eyre::eyre;
+use uuid::Uuid;
+
+use crate::server::routes::auth::{AdminUser, CurrentUser};
+use crate::server::routes::errors::AppError;
+use crate::server::state::AppState;
+use payload::{CreatePayload, ListQueryParams, ProductListResponse, ProductResponse};
+
+pub struct ProductRouter {}
+
+impl ProductRouter {
+    pub fn router() -> Router<AppState> {
+        Router::new()
+            .route("/", get(ProductRouter::list).post(ProductRouter::create))
+            .route("/:id", delete(ProductRouter::delete))
+    }
+
+    async fn create(
+        State(state): State<AppState>,
+        _admin: AdminUser,
+        Json(payload): Json<CreatePayload>,
+    ) -> Result<(StatusCode, Json<ProductResponse>), AppError> {
+        if [
+            payload.quantity,
+            payload.weight_grams,
+            payload.volume_milli_litres,
+        ]
+        .iter()
+        .flatten()
+        .count()
+            > 1
+        {
+            return Err(AppError::UnprocessableEntity { error: eyre!("Invalid package size. Must indicate only one of quantity, weight_grams or volume_milli_litres.") });
+        }
+        let gtin = payload.gtin.clone();
+        let request = payload.into_dto().ok_or(AppError::UnprocessableEntity {
+            error: eyre!("Invalid GTIN {gtin:?}."),
+        })?;
+        let product = state.db_client.create_product(request).await?;
+        log::info!("Product with id {:?} created", product.id.to_string());
+        Ok((StatusCode::CREATED, Json(product.into())))
+    }
+
+    async fn list(
+        State(state): State<AppState>,
+        _user: CurrentUser,
+        Query(query_params): Query<ListQueryParams>,
+    ) -> Result<(StatusCode, Json<ProductListResponse>), AppError> {
+        let list_params = query_params.into();
+        let products: Vec<ProductResponse> =
+            state.db_client.list_products(&list_params).await?.into();
+        log::info!("{:?} products collected", products.len());
+        let metadata = state
+            .db_client
+            .get_products_metadata(&list_params)
+            .await?
+            .into();
+        Ok((
+            StatusCode::OK,
+            Json(ProductListResponse::from(products, metadata)),
+        ))
+    }
+
+    async fn delete(
+        State(state): State<AppState>,
+        _admin: AdminUser,
+        Path(id): Path<Uuid>,
+    ) -> Result<StatusCode, AppError> {
+        state.db_client.delete_product(id).await?;
+        log::info!("Deleted product with id {:?}", id);
+        Ok(StatusCode::NO_CONTENT)
+    }
+}
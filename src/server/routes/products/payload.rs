@@ -0,0 +1,96 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::database::products::dto::{CreateDto, ListParamsDto, ProductDto, ProductsListDto};
+use crate::products::normalize_gtin;
+use crate::server::payload::{MetadataResponse, DEFAULT_PER_PAGE};
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CreatePayload {
+    pub gtin: String,
+    pub ingredient_id: Uuid,
+    pub name: Option<String>,
+    pub quantity: Option<i32>,
+    pub weight_grams: Option<i32>,
+    pub volume_milli_litres: Option<i32>,
+}
+
+impl CreatePayload {
+    /// Returns `None` if the GTIN is invalid
+    pub fn into_dto(self) -> Option<CreateDto> {
+        Some(CreateDto {
+            gtin: normalize_gtin(&self.gtin)?,
+            ingredient_id: self.ingredient_id,
+            name: self.name,
+            quantity: self.quantity,
+            weight_grams: self.weight_grams,
+            volume_milli_litres: self.volume_milli_litres,
+        })
+    }
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct ListQueryParams {
+    pub gtin: Option<String>,
+    pub ingredient_id: Option<Uuid>,
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+}
+
+impl From<ListQueryParams> for ListParamsDto {
+    fn from(val: ListQueryParams) -> Self {
+        ListParamsDto {
+            gtin: val.gtin.map(|gtin| normalize_gtin(&gtin).unwrap_or(gtin)),
+            ingredient_id: val.ingredient_id,
+            limit: val.per_page.unwrap_or(DEFAULT_PER_PAGE),
+            offset: val.per_page.unwrap_or(DEFAULT_PER_PAGE) * (val.page.unwrap_or(1) - 1),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct ProductResponse {
+    pub id: Uuid,
+    pub gtin: String,
+    pub ingredient_id: Uuid,
+    pub name: Option<String>,
+    pub quantity: Option<i32>,
+    pub weight_grams: Option<i32>,
+    pub volume_milli_litres: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<ProductDto> for ProductResponse {
+    fn from(val: ProductDto) -> Self {
+        ProductResponse {
+            id: val.id,
+            gtin: val.gtin,
+            ingredient_id: val.ingredient_id,
+            name: val.name,
+            quantity: val.quantity,
+            weight_grams: val.weight_grams,
+            volume_milli_litres: val.volume_milli_litres,
+            created_at: val.created_at,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct ProductListResponse {
+    #[serde(rename = "_metadata")]
+    pub metadata: MetadataResponse,
+    pub items: Vec<ProductResponse>,
+}
+
+impl From<ProductsListDto> for Vec<ProductResponse> {
+    fn from(val: ProductsListDto) -> Self {
+        val.items.into_iter().map(Into::into).collect()
+    }
+}
+
+impl ProductListResponse {
+    pub fn from(items: Vec<ProductResponse>, metadata: MetadataResponse) -> Self {
+        ProductListResponse { metadata, items }
+    }
+}
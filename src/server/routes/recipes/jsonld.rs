@@ -0,0 +1,102 @@
+use serde_json::{json, Map, Value};
+
+use crate::database::recipe_ingredients::dto::RecipeIngredientJoinDto;
+use crate::database::recipes::dto::RecipeDto;
+
+/// Ratings are given out of five stars
+const BEST_RATING: i32 = 5;
+const WORST_RATING: i32 = 1;
+
+/// Builds a schema.org `Recipe` object, the reverse of what `parse_recipe_link` reads
+pub fn recipe_json_ld(recipe: &RecipeDto, ingredients: &[RecipeIngredientJoinDto]) -> Value {
+    let mut json = Map::new();
+    json.insert("@context".to_owned(), json!("https://schema.org"));
+    json.insert("@type".to_owned(), json!("Recipe"));
+    json.insert("name".to_owned(), json!(recipe.name));
+    json.insert(
+        "recipeIngredient".to_owned(),
+        ingredients.iter().map(ingredient_line).collect(),
+    );
+    if let Some(instructions) = &recipe.instructions {
+        json.insert(
+            "recipeInstructions".to_owned(),
+            instruction_steps(instructions)
+                .map(|text| json!({"@type": "HowToStep", "text": text}))
+                .collect(),
+        );
+    }
+    if let Some(mins) = recipe.prep_time_mins {
+        json.insert("prepTime".to_owned(), json!(mins_to_iso8601(mins)));
+    }
+    if let Some(mins) = recipe.total_time_mins {
+        json.insert("totalTime".to_owned(), json!(mins_to_iso8601(mins)));
+    }
+    if let Some(image) = &recipe.image {
+        json.insert("image".to_owned(), json!(image));
+    }
+    if let Some(link) = &recipe.link {
+        json.insert("url".to_owned(), json!(link));
+    }
+    if let Some(rating) = recipe.rating {
+        json.insert(
+            "aggregateRating".to_owned(),
+            json!({
+                "@type": "AggregateRating",
+                "ratingValue": rating,
+                "ratingCount": 1,
+                "bestRating": BEST_RATING,
+                "worstRating": WORST_RATING,
+            }),
+        );
+    }
+    json.insert(
+        "dateModified".to_owned(),
+        json!(recipe.updated_at.and_utc().to_rfc3339()),
+    );
+    Value::Object(json)
+}
+
+/// Ingredient line such as "2 cups Rice"
+fn ingredient_line(ingredient: &RecipeIngredientJoinDto) -> Value {
+    let mut line = [
+        ingredient.amount.as_deref(),
+        ingredient.unit.as_deref(),
+        Some(ingredient.ingredient_name.as_str()),
+    ]
+    .into_iter()
+    .flatten()
+    .map(str::trim)
+    .filter(|part| !part.is_empty())
+    .collect::<Vec<&str>>()
+    .join(" ");
+    if ingredient.optional {
+        line.push_str(" (optional)");
+    }
+    json!(line)
+}
+
+/// Splits instructions into steps, one per line. Imported instructions
+/// are numbered ("1. Boil water"), so the numbers are dropped.
+fn instruction_steps(instructions: &str) -> impl Iterator<Item = &str> {
+    instructions
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            match line.split_once(". ") {
+                Some((number, step)) if number.chars().all(|c| c.is_ascii_digit()) => step.trim(),
+                _ => line,
+            }
+        })
+        // Steps that could not be imported are stored as "---"
+        .filter(|step| !step.is_empty() && *step != "---")
+}
+
+/// Formats minutes as an ISO 8601 duration, e.g. "PT1H20M"
+fn mins_to_iso8601(mins: i32) -> String {
+    let (hours, mins) = (mins / 60, mins % 60);
+    match (hours, mins) {
+        (0, mins) => format!("PT{mins}M"),
+        (hours, 0) => format!("PT{hours}H"),
+        (hours, mins) => format!("PT{hours}H{mins}M"),
+    }
+}
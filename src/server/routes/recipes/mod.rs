@@ -1,169 +1,428 @@
+mod jsonld;
 mod payload;
 
 use axum::{
-    extract::{Json, Path, Query, State},
-    http::StatusCode,
-    routing::get,
+    body::Bytes,
+    extract::{DefaultBodyLimit, Json, Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
     Router,
 };
-use axum_extra::extract::CookieJar;
+use chrono::Utc;
 use color_eyre::eyre::eyre;
-use payload::{CreatePayload, ListQueryParams, RecipeListResponse, RecipeResponse, UpdatePayload};
+use jsonld::recipe_json_ld;
+use payload::{
+    CookResponse, CreatePayload, FormatPayload, FromLinkPayload, GetQueryParams, ImportQueryParams,
+    ImportReportResponse, ListQueryParams, RecipeCategoryListResponse, RecipeCategoryPayload,
+    RecipeCategoryResponse, RecipeListResponse, RecipeResponse, RecipeSuggestionListResponse,
+    RecipeWithIngredientsResponse, SuggestionQueryParams, UpdatePayload,
+};
 use urlencoding::decode;
 
 use crate::database::errors::ListError;
+use crate::database::recipe_categories::dto::CreateDto as RecipeCategoryCreateDto;
+use crate::database::recipes::dto::{CookDto, CreateDto, RecipeDto};
+use crate::import::{import_recipes, read_recipes};
+use crate::server::limits::RateLimiter;
+use crate::server::routes::auth::CurrentUser;
 use crate::server::routes::errors::{AppError, VerifyError};
-use crate::server::routes::COOKIE_KEY;
-use crate::server::state::AppState;
+use crate::server::routes::parse_recipe_link::{
+    get_image, get_ingredients, get_instructions, get_name, get_recipe_json, get_time_field,
+};
+use crate::server::state::{Access, AppState};
 use uuid::Uuid;
 
+const JSON_LD_CONTENT_TYPE: &str = "application/ld+json";
+/// Recipe manager archives hold hundreds of recipes
+const IMPORT_BODY_LIMIT: usize = 64 * 1024 * 1024;
+
 pub struct RecipeRouter {}
 
 impl RecipeRouter {
-    pub fn router() -> Router<AppState> {
+    /// Fetching a recipe link shares the limit of `/parse_recipe_link`
+    pub fn router(link_limiter: RateLimiter) -> Router<AppState> {
         Router::new()
             .route("/", get(RecipeRouter::list).post(RecipeRouter::create))
+            .route(
+                "/import",
+                post(RecipeRouter::import).layer(DefaultBodyLimit::max(IMPORT_BODY_LIMIT)),
+            )
+            .route(
+                "/from_link",
+                post(RecipeRouter::from_link).route_layer(link_limiter),
+            )
+            .route("/suggestions", get(RecipeRouter::suggestions))
             .route(
                 "/:id",
                 get(RecipeRouter::get)
                     .put(RecipeRouter::update)
                     .delete(RecipeRouter::delete),
             )
+            .route("/:id/cook", post(RecipeRouter::cook))
+            .route(
+                "/:id/categories",
+                get(RecipeRouter::list_categories).post(RecipeRouter::add_category),
+            )
+            .route(
+                "/:id/categories/:category_id",
+                delete(RecipeRouter::remove_category),
+            )
     }
 
     async fn create(
         State(state): State<AppState>,
-        jar: CookieJar,
+        CurrentUser(user_id): CurrentUser,
         Json(payload): Json<CreatePayload>,
     ) -> Result<(StatusCode, Json<RecipeResponse>), AppError> {
-        if let Some(session_id) = jar.get(COOKIE_KEY) {
-            if let Some(user_id) = state.get_sessions_user(session_id.value_trimmed()).await? {
-                let recipe = state
-                    .db_client
-                    .create_recipe(payload.into_dto(user_id))
-                    .await?;
-                log::info!("Recipe with id {:?} created", recipe.id.to_string());
-                return Ok((StatusCode::CREATED, Json(recipe.into())));
-            }
-        }
-        Err(AppError::Unauthorized)
+        verify_household(&state, payload.household_id, user_id).await?;
+        let recipe = state
+            .db_client
+            .create_recipe(payload.into_dto(user_id))
+            .await?;
+        log::info!("Recipe with id {:?} created", recipe.id.to_string());
+        Ok((StatusCode::CREATED, Json(recipe.into())))
+    }
+
+    /// Parses the recipe at the link and stores it with its ingredients in one transaction
+    async fn from_link(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Json(payload): Json<FromLinkPayload>,
+    ) -> Result<(StatusCode, Json<RecipeWithIngredientsResponse>), AppError> {
+        verify_household(&state, payload.household_id, user_id).await?;
+        let json = get_recipe_json(&state, payload.link.as_str()).await?;
+        let name = get_name(&json).ok_or(AppError::UnprocessableEntity {
+            error: eyre!("Recipe at {} has no name.", payload.link),
+        })?;
+        let mut ingredients = get_ingredients(&json);
+        ingredients.retain(|ingredient| !ingredient.name.trim().is_empty());
+        state
+            .ingredient_matcher
+            .get(state.db_client.as_ref())
+            .await?
+            .annotate(&mut ingredients);
+        let ingredients = ingredients.into_iter().map(Into::into).collect();
+        let mins = |key| get_time_field(&json, key).and_then(|mins| i32::try_from(mins).ok());
+        let request = CreateDto {
+            user_id,
+            household_id: payload.household_id,
+            name,
+            prep_time_mins: mins("prepTime"),
+            total_time_mins: mins("totalTime"),
+            link: Some(payload.link.to_string()),
+            instructions: get_instructions(&json),
+            image: get_image(&json).map(Into::into),
+            last_cooked: None,
+            rating: None,
+            notes: None,
+        };
+        let created = state
+            .db_client
+            .create_recipe_with_ingredients(request, ingredients)
+            .await?;
+        log::info!(
+            "Recipe with id {:?} created from {}",
+            created.recipe.id.to_string(),
+            payload.link
+        );
+        Ok((StatusCode::CREATED, Json(created.into())))
     }
 
     async fn list(
         State(state): State<AppState>,
-        jar: CookieJar,
+        CurrentUser(user_id): CurrentUser,
         Query(query_params): Query<ListQueryParams>,
     ) -> Result<(StatusCode, Json<RecipeListResponse>), AppError> {
-        if let Some(session_id) = jar.get(COOKIE_KEY) {
-            if let Some(user_id) = state.get_sessions_user(session_id.value_trimmed()).await? {
-                let ingredient_ids = query_params.ingredient_ids.clone();
-                let recipes = if ingredient_ids.is_some() {
-                    list_recipes_containing_ingredients(state, user_id, query_params).await?
-                } else {
-                    let list_params = query_params.into_dto(user_id);
-                    let recipes = state.db_client.list_recipes(&list_params).await?.into();
-                    let metadata = state
-                        .db_client
-                        .get_recipes_metadata(&list_params)
-                        .await?
-                        .into();
-                    RecipeListResponse::from(recipes, metadata)
-                };
-                log::info!("{:?} recipes collected", recipes.items.len());
-                return Ok((StatusCode::OK, Json(recipes)));
-            }
-        }
-        Err(AppError::Unauthorized)
+        let category_ids = match &query_params.category_ids {
+            Some(category_ids) => Some(parse_uuid_list(category_ids).ok_or(
+                ListError::Unprocessable {
+                    error: eyre!("category_ids must be list of uuids seperated by commas."),
+                },
+            )?),
+            None => None,
+        };
+        let ingredient_ids = query_params.ingredient_ids.clone();
+        let recipes = if ingredient_ids.is_some() {
+            list_recipes_containing_ingredients(state, user_id, query_params, category_ids).await?
+        } else {
+            let list_params = query_params.into_dto(user_id, category_ids);
+            let recipes = state.db_client.list_recipes(&list_params).await?.into();
+            let metadata = state
+                .db_client
+                .get_recipes_metadata(&list_params)
+                .await?
+                .into();
+            RecipeListResponse::from(recipes, metadata)
+        };
+        log::info!("{:?} recipes collected", recipes.items.len());
+        Ok((StatusCode::OK, Json(recipes)))
+    }
+
+    async fn suggestions(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Query(query_params): Query<SuggestionQueryParams>,
+    ) -> Result<(StatusCode, Json<RecipeSuggestionListResponse>), AppError> {
+        let category_ids = match &query_params.category_ids {
+            Some(category_ids) => Some(parse_uuid_list(category_ids).ok_or(
+                ListError::Unprocessable {
+                    error: eyre!("category_ids must be list of uuids seperated by commas."),
+                },
+            )?),
+            None => None,
+        };
+        let list_params = query_params.into_dto(user_id, category_ids);
+        let suggestions = state
+            .db_client
+            .list_recipe_suggestions(&list_params, Utc::now().date_naive())
+            .await?;
+        let metadata = state
+            .db_client
+            .get_recipes_metadata(&list_params)
+            .await?
+            .into();
+        log::info!("{:?} recipe suggestions collected", suggestions.items.len());
+        Ok((
+            StatusCode::OK,
+            Json(RecipeSuggestionListResponse::from(suggestions, metadata)),
+        ))
+    }
+
+    /// Imports every recipe of an export file. Recipes that cannot be read
+    /// or stored are reported without stopping the import.
+    async fn import(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Query(query_params): Query<ImportQueryParams>,
+        body: Bytes,
+    ) -> Result<(StatusCode, Json<ImportReportResponse>), AppError> {
+        verify_household(&state, query_params.household_id, user_id).await?;
+        // Archives are decompressed off the async runtime
+        let entries = tokio::task::spawn_blocking(move || read_recipes(&body))
+            .await
+            .map_err(|err| AppError::Other { error: err.into() })??;
+        let report = import_recipes(
+            state.db_client.as_ref(),
+            user_id,
+            query_params.household_id,
+            entries,
+        )
+        .await;
+        log::info!(
+            "Imported {} recipes for user with id {user_id:?}, {} failed",
+            report.imported,
+            report.failed
+        );
+        Ok((StatusCode::OK, Json(report.into())))
     }
 
     async fn get(
         State(state): State<AppState>,
-        jar: CookieJar,
+        CurrentUser(user_id): CurrentUser,
         Path(id): Path<Uuid>,
-    ) -> Result<(StatusCode, Json<RecipeResponse>), AppError> {
-        if let Some(session_id) = jar.get(COOKIE_KEY) {
-            if let Some(user_id) = state.get_sessions_user(session_id.value_trimmed()).await? {
-                let recipe = state.db_client.get_recipe(id).await?;
-                if recipe.user_id == user_id {
-                    log::info!("Got recipe with id {:?}", recipe.id);
-                    return Ok((StatusCode::OK, Json(recipe.into())));
-                }
+        Query(query_params): Query<GetQueryParams>,
+    ) -> Result<Response, AppError> {
+        let recipe = state.db_client.get_recipe(id).await?;
+        if state
+            .has_access(user_id, recipe.user_id, recipe.household_id, Access::Read)
+            .await?
+        {
+            log::info!("Got recipe with id {:?}", recipe.id);
+            if query_params.format == Some(FormatPayload::JsonLd) {
+                let ingredients = state
+                    .db_client
+                    .list_all_recipe_ingredients_join(recipe.id)
+                    .await?;
+                return Ok((
+                    StatusCode::OK,
+                    [(header::CONTENT_TYPE, JSON_LD_CONTENT_TYPE)],
+                    Json(recipe_json_ld(&recipe, &ingredients)),
+                )
+                    .into_response());
             }
+            return Ok((StatusCode::OK, Json(RecipeResponse::from(recipe))).into_response());
         }
-        Err(AppError::Unauthorized)
+        Err(AppError::Forbidden)
     }
 
     async fn update(
         State(state): State<AppState>,
-        jar: CookieJar,
+        CurrentUser(user_id): CurrentUser,
         Path(id): Path<Uuid>,
         Json(payload): Json<UpdatePayload>,
     ) -> Result<(StatusCode, Json<RecipeResponse>), AppError> {
-        if let Some(session_id) = jar.get(COOKIE_KEY) {
-            if let Some(user_id) = state.get_sessions_user(session_id.value_trimmed()).await? {
-                verify_user(&state, id, user_id).await?;
-                let recipe = state
-                    .db_client
-                    .update_recipe(id, payload.into_dto(user_id))
-                    .await?;
-                log::info!("Updated recipe with id {id:?}");
-                return Ok((StatusCode::OK, Json(recipe.into())));
-            }
+        let recipe = verify_user(&state, id, user_id, Access::Write).await?;
+        if payload.household_id != recipe.household_id {
+            verify_owner(&state, recipe.user_id, user_id).await?;
+            verify_household(&state, payload.household_id, user_id).await?;
         }
-        Err(AppError::Unauthorized)
+        let recipe = state
+            .db_client
+            .update_recipe(id, payload.into_dto())
+            .await?;
+        log::info!("Updated recipe with id {id:?}");
+        Ok((StatusCode::OK, Json(recipe.into())))
     }
 
     async fn delete(
         State(state): State<AppState>,
-        jar: CookieJar,
+        CurrentUser(user_id): CurrentUser,
         Path(id): Path<Uuid>,
     ) -> Result<StatusCode, AppError> {
-        if let Some(session_id) = jar.get(COOKIE_KEY) {
-            if let Some(user_id) = state.get_sessions_user(session_id.value_trimmed()).await? {
-                verify_user(&state, id, user_id).await?;
-                state.db_client.delete_recipe(id).await?;
-                log::info!("Deleted recipe with id {:?}", id);
-                return Ok(StatusCode::NO_CONTENT);
-            }
-        }
-        Err(AppError::Unauthorized)
+        verify_user(&state, id, user_id, Access::Write).await?;
+        state.db_client.delete_recipe(id).await?;
+        log::info!("Deleted recipe with id {:?}", id);
+        Ok(StatusCode::NO_CONTENT)
+    }
+
+    async fn cook(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+    ) -> Result<(StatusCode, Json<CookResponse>), AppError> {
+        verify_user(&state, id, user_id, Access::Write).await?;
+        let cooked = state
+            .db_client
+            .cook_recipe(CookDto {
+                recipe_id: id,
+                user_id,
+                cooked_on: Utc::now().date_naive(),
+                deduct_ingredients: true,
+            })
+            .await?;
+        log::info!(
+            "Recipe with id {id:?} cooked, {:?} ingredients short",
+            cooked.shortages.len()
+        );
+        Ok((StatusCode::OK, Json(cooked.into())))
+    }
+
+    async fn list_categories(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+    ) -> Result<(StatusCode, Json<RecipeCategoryListResponse>), AppError> {
+        verify_user(&state, id, user_id, Access::Read).await?;
+        let categories: RecipeCategoryListResponse =
+            state.db_client.list_recipe_categories(id).await?.into();
+        log::info!(
+            "{:?} categories of recipe {id:?} collected",
+            categories.items.len()
+        );
+        Ok((StatusCode::OK, Json(categories)))
+    }
+
+    async fn add_category(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+        Json(payload): Json<RecipeCategoryPayload>,
+    ) -> Result<(StatusCode, Json<RecipeCategoryResponse>), AppError> {
+        verify_user(&state, id, user_id, Access::Write).await?;
+        state.db_client.get_category(payload.category_id).await?;
+        let recipe_category = state
+            .db_client
+            .create_recipe_category(RecipeCategoryCreateDto {
+                recipe_id: id,
+                category_id: payload.category_id,
+            })
+            .await?;
+        log::info!(
+            "Category {:?} added to recipe {id:?}",
+            recipe_category.category_id
+        );
+        Ok((StatusCode::CREATED, Json(recipe_category.into())))
+    }
+
+    async fn remove_category(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path((id, category_id)): Path<(Uuid, Uuid)>,
+    ) -> Result<StatusCode, AppError> {
+        verify_user(&state, id, user_id, Access::Write).await?;
+        state
+            .db_client
+            .delete_recipe_category(id, category_id)
+            .await?;
+        log::info!("Category {category_id:?} removed from recipe {id:?}");
+        Ok(StatusCode::NO_CONTENT)
     }
 }
 
-async fn verify_user(state: &AppState, recipe_id: Uuid, user_id: Uuid) -> Result<(), VerifyError> {
+async fn verify_user(
+    state: &AppState,
+    recipe_id: Uuid,
+    user_id: Uuid,
+    access: Access,
+) -> Result<RecipeDto, VerifyError> {
     let recipe = state.db_client.get_recipe(recipe_id).await?;
-    if recipe.user_id == user_id {
+    if state
+        .has_access(user_id, recipe.user_id, recipe.household_id, access)
+        .await?
+    {
         log::info!("Got recipe with id {:?}", recipe.id);
+        return Ok(recipe);
+    }
+    Err(VerifyError::Forbidden)
+}
+
+/// Only the owner of a recipe, or an admin, decides which household it is shared with
+async fn verify_owner(state: &AppState, owner_id: Uuid, user_id: Uuid) -> Result<(), VerifyError> {
+    if owner_id == user_id || state.user_is_admin(user_id).await? {
         return Ok(());
     }
-    Err(VerifyError::Unauthorized)
+    Err(VerifyError::Forbidden)
+}
+
+/// Recipes can only be shared with households the user may write to
+async fn verify_household(
+    state: &AppState,
+    household_id: Option<Uuid>,
+    user_id: Uuid,
+) -> Result<(), VerifyError> {
+    match household_id {
+        Some(household_id)
+            if !state
+                .household_allows(household_id, user_id, Access::Write)
+                .await? =>
+        {
+            Err(VerifyError::Forbidden)
+        }
+        _ => Ok(()),
+    }
 }
 
 async fn list_recipes_containing_ingredients(
     state: AppState,
     user_id: Uuid,
     query_params: ListQueryParams,
+    category_ids: Option<Vec<Uuid>>,
 ) -> Result<RecipeListResponse, ListError> {
-    if let Ok(ingredient_ids) = decode(&query_params.ingredient_ids.clone().unwrap()) {
-        if let Ok(ingredient_ids) = serde_json::from_str::<Vec<Uuid>>(&ingredient_ids) {
-            let list_params = query_params.into_join_dto(user_id, ingredient_ids);
-            let recipes: Vec<RecipeResponse> = state
-                .db_client
-                .list_recipes_join(&list_params)
-                .await?
-                .into_iter()
-                .map(Into::into)
-                .collect();
+    if let Some(ingredient_ids) = parse_uuid_list(&query_params.ingredient_ids.clone().unwrap()) {
+        let list_params = query_params.into_join_dto(user_id, ingredient_ids, category_ids);
+        let recipes: Vec<RecipeResponse> = state
+            .db_client
+            .list_recipes_join(&list_params)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
 
-            let metadata = state
-                .db_client
-                .get_recipes_join_metadata(&list_params)
-                .await?
-                .into();
-            return Ok(RecipeListResponse::from(recipes, metadata));
-        }
+        let metadata = state
+            .db_client
+            .get_recipes_join_metadata(&list_params)
+            .await?
+            .into();
+        return Ok(RecipeListResponse::from(recipes, metadata));
     }
     Err(ListError::Unprocessable {
         error: eyre!("ingredient_ids must be list of uuids seperated by commas."),
     })
 }
+
+/// Parses a urlencoded JSON array of uuids
+fn parse_uuid_list(value: &str) -> Option<Vec<Uuid>> {
+    let value = decode(value).ok()?;
+    serde_json::from_str::<Vec<Uuid>>(&value).ok()
+}
@@ -4,14 +4,22 @@ use titlecase::titlecase;
 use url::Url;
 use uuid::Uuid;
 
+use crate::database::recipe_categories::dto::{
+    RecipeCategoriesListDto, RecipeCategoryDto, RecipeCategoryJoinDto,
+};
 use crate::database::recipes::dto::{
-    CreateDto, ListParamsDto, ListRecipeJoinParamsDto, RecipeDto, RecipesListDto, UpdateDto
+    CookedRecipeDto, CreateDto, ListParamsDto, ListRecipeJoinParamsDto, RecipeDto,
+    RecipeSuggestionDto, RecipeSuggestionsListDto, RecipeWithIngredientsDto, RecipesListDto,
+    ShortageDto, UpdateDto,
 };
+use crate::import::{ImportReport, ImportResult};
 use crate::server::payload::{MetadataResponse, DEFAULT_PER_PAGE};
+use crate::server::routes::recipe_ingredients::payload::RecipeIngredientJoinResponse;
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct CreatePayload {
     pub name: String,
+    pub household_id: Option<Uuid>,
     pub prep_time_mins: Option<i32>,
     pub total_time_mins: Option<i32>,
     pub link: Option<Url>,
@@ -27,6 +35,7 @@ impl CreatePayload {
     pub fn into_dto(self, user_id: Uuid) -> CreateDto {
         CreateDto {
             user_id,
+            household_id: self.household_id,
             name: titlecase(&self.name),
             prep_time_mins: self.prep_time_mins,
             total_time_mins: self.total_time_mins,
@@ -43,6 +52,7 @@ impl CreatePayload {
 #[derive(Deserialize, Serialize, Debug)]
 pub struct UpdatePayload {
     pub name: String,
+    pub household_id: Option<Uuid>,
     pub prep_time_mins: Option<i32>,
     pub total_time_mins: Option<i32>,
     pub link: Option<String>,
@@ -54,15 +64,15 @@ pub struct UpdatePayload {
 }
 
 impl UpdatePayload {
-    pub fn into_dto(self, user_id: Uuid) -> UpdateDto {
+    pub fn into_dto(self) -> UpdateDto {
         UpdateDto {
-            user_id,
+            household_id: self.household_id,
             name: self.name,
             prep_time_mins: self.prep_time_mins,
             total_time_mins: self.total_time_mins,
-            link: self.link.map(|url| url.to_string()),
+            link: self.link,
             instructions: self.instructions,
-            image: self.image.map(|url| url.to_string()),
+            image: self.image,
             last_cooked: self.last_cooked,
             rating: self.rating.map(std::convert::Into::into),
             notes: self.notes,
@@ -75,24 +85,77 @@ pub struct ListQueryParams {
     pub name_contains: Option<String>,
     pub total_time_mins: Option<i32>,
     pub ingredient_ids: Option<String>, // urlencoded array of ingredient_ids
+    pub category_ids: Option<String>,   // urlencoded array of category_ids
     pub page: Option<u64>,
     pub per_page: Option<u64>,
 }
 
 impl ListQueryParams {
-    pub fn into_dto(self, user_id: Uuid) -> ListParamsDto {
+    pub fn into_dto(self, user_id: Uuid, category_ids: Option<Vec<Uuid>>) -> ListParamsDto {
         ListParamsDto {
             name_contains: self.name_contains,
             total_time_mins: self.total_time_mins,
             user_id: Some(user_id),
+            category_ids,
             limit: self.per_page.unwrap_or(DEFAULT_PER_PAGE),
             offset: self.per_page.unwrap_or(DEFAULT_PER_PAGE) * (self.page.unwrap_or(1) - 1),
         }
     }
-    pub fn into_join_dto(self, user_id: Uuid, ingredient_ids: Vec<Uuid>) -> ListRecipeJoinParamsDto {
+    pub fn into_join_dto(
+        self,
+        user_id: Uuid,
+        ingredient_ids: Vec<Uuid>,
+        category_ids: Option<Vec<Uuid>>,
+    ) -> ListRecipeJoinParamsDto {
         ListRecipeJoinParamsDto {
             user_id,
             ingredient_ids,
+            category_ids,
+            limit: self.per_page.unwrap_or(DEFAULT_PER_PAGE),
+            offset: self.per_page.unwrap_or(DEFAULT_PER_PAGE) * (self.page.unwrap_or(1) - 1),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FormatPayload {
+    Json,
+    /// schema.org `Recipe` JSON-LD
+    JsonLd,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct GetQueryParams {
+    pub format: Option<FormatPayload>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct FromLinkPayload {
+    pub link: Url,
+    pub household_id: Option<Uuid>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct ImportQueryParams {
+    /// Household the imported recipes are shared with
+    pub household_id: Option<Uuid>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct SuggestionQueryParams {
+    pub category_ids: Option<String>, // urlencoded array of category_ids
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+}
+
+impl SuggestionQueryParams {
+    pub fn into_dto(self, user_id: Uuid, category_ids: Option<Vec<Uuid>>) -> ListParamsDto {
+        ListParamsDto {
+            name_contains: None,
+            total_time_mins: None,
+            user_id: Some(user_id),
+            category_ids,
             limit: self.per_page.unwrap_or(DEFAULT_PER_PAGE),
             offset: self.per_page.unwrap_or(DEFAULT_PER_PAGE) * (self.page.unwrap_or(1) - 1),
         }
@@ -102,6 +165,7 @@ impl ListQueryParams {
 #[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
 pub struct RecipeResponse {
     pub id: Uuid,
+    pub household_id: Option<Uuid>,
     pub name: String,
     pub prep_time_mins: Option<i32>,
     pub total_time_mins: Option<i32>,
@@ -119,6 +183,7 @@ impl From<RecipeDto> for RecipeResponse {
     fn from(val: RecipeDto) -> Self {
         RecipeResponse {
             id: val.id,
+            household_id: val.household_id,
             name: val.name,
             prep_time_mins: val.prep_time_mins,
             total_time_mins: val.total_time_mins,
@@ -134,6 +199,22 @@ impl From<RecipeDto> for RecipeResponse {
     }
 }
 
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RecipeWithIngredientsResponse {
+    #[serde(flatten)]
+    pub recipe: RecipeResponse,
+    pub ingredients: Vec<RecipeIngredientJoinResponse>,
+}
+
+impl From<RecipeWithIngredientsDto> for RecipeWithIngredientsResponse {
+    fn from(val: RecipeWithIngredientsDto) -> Self {
+        RecipeWithIngredientsResponse {
+            recipe: val.recipe.into(),
+            ingredients: val.ingredients.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
 pub struct RecipeListResponse {
     #[serde(rename = "_metadata")]
@@ -152,3 +233,163 @@ impl RecipeListResponse {
         RecipeListResponse { metadata, items }
     }
 }
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RecipeCategoryPayload {
+    pub category_id: Uuid,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RecipeCategoryResponse {
+    pub id: Uuid,
+    pub recipe_id: Uuid,
+    pub category_id: Uuid,
+    pub category_name: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<RecipeCategoryDto> for RecipeCategoryResponse {
+    fn from(val: RecipeCategoryDto) -> Self {
+        RecipeCategoryResponse {
+            id: val.id,
+            recipe_id: val.recipe_id,
+            category_id: val.category_id,
+            category_name: None,
+            created_at: val.created_at,
+        }
+    }
+}
+
+impl From<RecipeCategoryJoinDto> for RecipeCategoryResponse {
+    fn from(val: RecipeCategoryJoinDto) -> Self {
+        RecipeCategoryResponse {
+            id: val.id,
+            recipe_id: val.recipe_id,
+            category_id: val.category_id,
+            category_name: Some(val.category_name),
+            created_at: val.created_at,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RecipeCategoryListResponse {
+    pub items: Vec<RecipeCategoryResponse>,
+}
+
+impl From<RecipeCategoriesListDto> for RecipeCategoryListResponse {
+    fn from(val: RecipeCategoriesListDto) -> Self {
+        RecipeCategoryListResponse {
+            items: val.items.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ShortageResponse {
+    pub ingredient_id: Uuid,
+    pub amount: Option<String>,
+    pub unit: Option<String>,
+    pub missing: Option<i32>,
+}
+
+impl From<ShortageDto> for ShortageResponse {
+    fn from(val: ShortageDto) -> Self {
+        ShortageResponse {
+            ingredient_id: val.ingredient_id,
+            amount: val.amount,
+            unit: val.unit,
+            missing: val.missing,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CookResponse {
+    pub recipe: RecipeResponse,
+    pub shortages: Vec<ShortageResponse>,
+}
+
+impl From<CookedRecipeDto> for CookResponse {
+    fn from(val: CookedRecipeDto) -> Self {
+        CookResponse {
+            recipe: val.recipe.into(),
+            shortages: val.shortages.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct RecipeSuggestionResponse {
+    pub recipe: RecipeResponse,
+    pub coverage: f64,
+    pub ingredient_count: u64,
+    pub covered_count: u64,
+    pub missing_ingredient_ids: Vec<Uuid>,
+    pub soonest_expiration: Option<NaiveDate>,
+}
+
+impl From<RecipeSuggestionDto> for RecipeSuggestionResponse {
+    fn from(val: RecipeSuggestionDto) -> Self {
+        RecipeSuggestionResponse {
+            coverage: val.coverage(),
+            recipe: val.recipe.into(),
+            ingredient_count: val.ingredient_count,
+            covered_count: val.covered_count,
+            missing_ingredient_ids: val.missing_ingredient_ids,
+            soonest_expiration: val.soonest_expiration,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct RecipeSuggestionListResponse {
+    #[serde(rename = "_metadata")]
+    pub metadata: MetadataResponse,
+    pub items: Vec<RecipeSuggestionResponse>,
+}
+
+impl RecipeSuggestionListResponse {
+    pub fn from(suggestions: RecipeSuggestionsListDto, metadata: MetadataResponse) -> Self {
+        RecipeSuggestionListResponse {
+            metadata,
+            items: suggestions.items.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct ImportResultResponse {
+    pub source: String,
+    pub name: Option<String>,
+    pub recipe_id: Option<Uuid>,
+    pub error: Option<String>,
+}
+
+impl From<ImportResult> for ImportResultResponse {
+    fn from(val: ImportResult) -> Self {
+        ImportResultResponse {
+            source: val.source,
+            name: val.name,
+            recipe_id: val.recipe_id,
+            error: val.error,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct ImportReportResponse {
+    pub imported: usize,
+    pub failed: usize,
+    pub items: Vec<ImportResultResponse>,
+}
+
+impl From<ImportReport> for ImportReportResponse {
+    fn from(val: ImportReport) -> Self {
+        ImportReportResponse {
+            imported: val.imported,
+            failed: val.failed,
+            items: val.items.into_iter().map(Into::into).collect(),
+        }
+    }
+}
@@ -0,0 +1,63 @@
+mod payload;
+
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    routing::{delete, get},
+    Router,
+};
+use axum_extra::extract::cookie::Cookie;
+use axum_extra::extract::CookieJar;
+use uuid::Uuid;
+
+use crate::server::routes::auth::CurrentUser;
+use crate::server::routes::errors::AppError;
+use crate::server::session::{SessionStore, COOKIE_KEY};
+use crate::server::state::AppState;
+use payload::{SessionListResponse, SessionResponse};
+
+pub struct SessionRouter {}
+
+impl SessionRouter {
+    pub fn router() -> Router<AppState> {
+        Router::new()
+            .route("/", get(SessionRouter::list))
+            .route("/:id", delete(SessionRouter::delete))
+    }
+
+    /// Lists the sessions the user is logged in with, oldest first
+    async fn list(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        jar: CookieJar,
+    ) -> Result<(StatusCode, Json<SessionListResponse>), AppError> {
+        let current = jar.get(COOKIE_KEY).map(Cookie::value_trimmed);
+        let items: Vec<SessionResponse> = state
+            .redis_sender
+            .list_sessions(user_id)
+            .await?
+            .into_iter()
+            .map(|(token, session)| SessionResponse::from(session, Some(token.as_str()) == current))
+            .collect();
+        log::info!("{:?} sessions collected", items.len());
+        Ok((StatusCode::OK, Json(SessionListResponse { items })))
+    }
+
+    /// Logs the user out of one of their sessions
+    async fn delete(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+    ) -> Result<StatusCode, AppError> {
+        let (token, _) = state
+            .redis_sender
+            .list_sessions(user_id)
+            .await?
+            .into_iter()
+            .find(|(_, session)| session.id == id)
+            .ok_or(AppError::NotFound { id: id.to_string() })?;
+        state.redis_sender.delete_session(&token).await?;
+        log::info!("Deleted session with id {:?}", id);
+        Ok(StatusCode::NO_CONTENT)
+    }
+}
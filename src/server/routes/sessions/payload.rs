@@ -0,0 +1,30 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::server::session::Session;
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct SessionResponse {
+    pub id: Uuid,
+    pub user_agent: Option<String>,
+    pub created_at: NaiveDateTime,
+    /// Whether this is the session the request was made with
+    pub current: bool,
+}
+
+impl SessionResponse {
+    pub fn from(session: Session, current: bool) -> Self {
+        SessionResponse {
+            id: session.id,
+            user_agent: session.user_agent,
+            created_at: session.created_at,
+            current,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct SessionListResponse {
+    pub items: Vec<SessionResponse>,
+}
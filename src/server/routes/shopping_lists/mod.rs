@@ -0,0 +1,473 @@
+mod payload;
+
+use std::collections::HashSet;
+
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    routing::{get, post, put},
+    Router,
+};
+use num_traits::CheckedAdd;
+use uuid::Uuid;
+
+use crate::database::pantry_items::dto::PantryItemDto;
+use crate::database::recipe_ingredients::dto::RecipeIngredientDto;
+use crate::database::shopping_lists::dto::{CreateItemDto, ShoppingListItemJoinDto, UpdateItemDto};
+use crate::server::routes::auth::CurrentUser;
+use crate::server::routes::errors::{AppError, VerifyError};
+use crate::server::state::{Access, AppState};
+use crate::units::{Measure, Measurement};
+use payload::{
+    AddRecipePayload, CreateItemPayload, CreatePayload, ListQueryParams,
+    ShoppingListItemListResponse, ShoppingListItemResponse, ShoppingListListResponse,
+    ShoppingListResponse, UpdateItemPayload, UpdatePayload,
+};
+
+pub struct ShoppingListRouter {}
+
+impl ShoppingListRouter {
+    pub fn router() -> Router<AppState> {
+        Router::new()
+            .route(
+                "/",
+                get(ShoppingListRouter::list).post(ShoppingListRouter::create),
+            )
+            .route(
+                "/:id",
+                get(ShoppingListRouter::get)
+                    .put(ShoppingListRouter::update)
+                    .delete(ShoppingListRouter::delete),
+            )
+            .route(
+                "/:id/items",
+                get(ShoppingListRouter::list_items).post(ShoppingListRouter::create_item),
+            )
+            .route(
+                "/:id/items/:item_id",
+                put(ShoppingListRouter::update_item).delete(ShoppingListRouter::delete_item),
+            )
+            .route("/:id/recipes", post(ShoppingListRouter::add_recipe))
+    }
+
+    async fn create(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Json(payload): Json<CreatePayload>,
+    ) -> Result<(StatusCode, Json<ShoppingListResponse>), AppError> {
+        let shopping_list = state
+            .db_client
+            .create_shopping_list(payload.into_dto(user_id))
+            .await?;
+        log::info!(
+            "Shopping list with id {:?} created",
+            shopping_list.id.to_string()
+        );
+        Ok((StatusCode::CREATED, Json(shopping_list.into())))
+    }
+
+    async fn list(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Query(query_params): Query<ListQueryParams>,
+    ) -> Result<(StatusCode, Json<ShoppingListListResponse>), AppError> {
+        let list_params = query_params.into_dto(user_id);
+        let shopping_lists: Vec<ShoppingListResponse> = state
+            .db_client
+            .list_shopping_lists(&list_params)
+            .await?
+            .into();
+        log::info!("{:?} shopping lists collected", shopping_lists.len());
+        let metadata = state
+            .db_client
+            .get_shopping_lists_metadata(&list_params)
+            .await?
+            .into();
+        Ok((
+            StatusCode::OK,
+            Json(ShoppingListListResponse::from(shopping_lists, metadata)),
+        ))
+    }
+
+    async fn get(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+    ) -> Result<(StatusCode, Json<ShoppingListResponse>), AppError> {
+        let shopping_list = state.db_client.get_shopping_list(id).await?;
+        if state
+            .has_access(user_id, shopping_list.user_id, None, Access::Read)
+            .await?
+        {
+            log::info!("Got shopping list with id {:?}", shopping_list.id);
+            return Ok((StatusCode::OK, Json(shopping_list.into())));
+        }
+        Err(AppError::Forbidden)
+    }
+
+    async fn update(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+        Json(payload): Json<UpdatePayload>,
+    ) -> Result<(StatusCode, Json<ShoppingListResponse>), AppError> {
+        verify_user(&state, id, user_id).await?;
+        let shopping_list = state
+            .db_client
+            .update_shopping_list(id, payload.into())
+            .await?;
+        log::info!("Updated shopping list with id {id:?}");
+        Ok((StatusCode::OK, Json(shopping_list.into())))
+    }
+
+    async fn delete(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+    ) -> Result<StatusCode, AppError> {
+        verify_user(&state, id, user_id).await?;
+        state.db_client.delete_shopping_list(id).await?;
+        log::info!("Deleted shopping list with id {:?}", id);
+        Ok(StatusCode::NO_CONTENT)
+    }
+
+    async fn list_items(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+    ) -> Result<(StatusCode, Json<ShoppingListItemListResponse>), AppError> {
+        verify_user(&state, id, user_id).await?;
+        let items: ShoppingListItemListResponse =
+            state.db_client.list_shopping_list_items(id).await?.into();
+        log::info!("{:?} shopping list items collected", items.items.len());
+        Ok((StatusCode::OK, Json(items)))
+    }
+
+    async fn create_item(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+        Json(payload): Json<CreateItemPayload>,
+    ) -> Result<(StatusCode, Json<ShoppingListItemResponse>), AppError> {
+        verify_user(&state, id, user_id).await?;
+        let mut items = state
+            .db_client
+            .create_shopping_list_items(vec![payload.into_dto(id)])
+            .await?;
+        let item = items.remove(0);
+        log::info!("Shopping list item with id {:?} created", item.id);
+        Ok((StatusCode::CREATED, Json(item.into())))
+    }
+
+    async fn update_item(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path((id, item_id)): Path<(Uuid, Uuid)>,
+        Json(payload): Json<UpdateItemPayload>,
+    ) -> Result<(StatusCode, Json<ShoppingListItemResponse>), AppError> {
+        verify_item_user(&state, id, item_id, user_id).await?;
+        let item = state
+            .db_client
+            .update_shopping_list_item(item_id, payload.into())
+            .await?;
+        log::info!("Updated shopping list item with id {item_id:?}");
+        Ok((StatusCode::OK, Json(item.into())))
+    }
+
+    async fn delete_item(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path((id, item_id)): Path<(Uuid, Uuid)>,
+    ) -> Result<StatusCode, AppError> {
+        verify_item_user(&state, id, item_id, user_id).await?;
+        state.db_client.delete_shopping_list_item(item_id).await?;
+        log::info!("Deleted shopping list item with id {:?}", item_id);
+        Ok(StatusCode::NO_CONTENT)
+    }
+
+    /// Adds the recipe's ingredients that the pantry does not cover,
+    /// along with essential pantry items that are running low. Shortfalls of ingredients
+    /// already on the list are added to the amount of their unchecked item.
+    async fn add_recipe(
+        State(state): State<AppState>,
+        CurrentUser(user_id): CurrentUser,
+        Path(id): Path<Uuid>,
+        Json(payload): Json<AddRecipePayload>,
+    ) -> Result<(StatusCode, Json<ShoppingListItemListResponse>), AppError> {
+        verify_user(&state, id, user_id).await?;
+        let recipe = state.db_client.get_recipe(payload.recipe_id).await?;
+        if !state
+            .has_access(user_id, recipe.user_id, recipe.household_id, Access::Read)
+            .await?
+        {
+            return Err(AppError::Forbidden);
+        }
+        let recipe_ingredients = state
+            .db_client
+            .list_all_recipe_ingredients(recipe.id)
+            .await?;
+        let pantry_items = state.db_client.list_all_pantry_items(user_id).await?;
+        let mut unchecked: Vec<ShoppingListItemJoinDto> = state
+            .db_client
+            .list_shopping_list_items(id)
+            .await?
+            .items
+            .into_iter()
+            .filter(|item| !item.checked)
+            .collect();
+        let on_list: HashSet<Uuid> = unchecked.iter().map(|item| item.ingredient_id).collect();
+
+        let mut new_items = Vec::new();
+        let mut merged = Vec::new();
+        for item in missing_ingredients(id, recipe.id, &recipe_ingredients, &pantry_items) {
+            let Some((existing, amount)) = unchecked
+                .iter_mut()
+                .filter(|existing| existing.ingredient_id == item.ingredient_id)
+                .find_map(|existing| {
+                    let amount =
+                        merged_amount(existing.amount.as_deref(), existing.unit.as_deref(), &item)?;
+                    Some((existing, amount))
+                })
+            else {
+                new_items.push(item);
+                continue;
+            };
+            existing.amount = Some(amount);
+            merged.push(existing.id);
+        }
+        let mut added: HashSet<Uuid> = new_items.iter().map(|item| item.ingredient_id).collect();
+        for item in running_low_essentials(id, &pantry_items) {
+            if !on_list.contains(&item.ingredient_id) && added.insert(item.ingredient_id) {
+                new_items.push(item);
+            }
+        }
+        // An item a recipe ingredient was merged into twice is only updated once
+        merged.sort_unstable();
+        merged.dedup();
+        let updated_items: Vec<(Uuid, UpdateItemDto)> = unchecked
+            .into_iter()
+            .filter(|item| merged.contains(&item.id))
+            .map(|existing| {
+                (
+                    existing.id,
+                    UpdateItemDto {
+                        amount: existing.amount,
+                        unit: existing.unit,
+                        checked: false,
+                    },
+                )
+            })
+            .collect();
+        let items = state
+            .db_client
+            .add_shopping_list_items(id, new_items, updated_items)
+            .await?;
+        log::info!(
+            "{:?} items from recipe {:?} added to shopping list {id:?}, {:?} merged",
+            items.len() - merged.len(),
+            recipe.id,
+            merged.len()
+        );
+        Ok((
+            StatusCode::CREATED,
+            Json(ShoppingListItemListResponse::from(items)),
+        ))
+    }
+}
+
+async fn verify_user(
+    state: &AppState,
+    shopping_list_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), VerifyError> {
+    let shopping_list = state.db_client.get_shopping_list(shopping_list_id).await?;
+    if state
+        .has_access(user_id, shopping_list.user_id, None, Access::Write)
+        .await?
+    {
+        log::info!("Got shopping list with id {:?}", shopping_list.id);
+        return Ok(());
+    }
+    Err(VerifyError::Forbidden)
+}
+
+async fn verify_item_user(
+    state: &AppState,
+    shopping_list_id: Uuid,
+    item_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), VerifyError> {
+    verify_user(state, shopping_list_id, user_id).await?;
+    let item = state.db_client.get_shopping_list_item(item_id).await?;
+    if item.shopping_list_id == shopping_list_id {
+        return Ok(());
+    }
+    Err(VerifyError::NotFound { id: item_id })
+}
+
+/// Returns the non-optional recipe ingredients that the pantry does not hold enough of.
+/// Shortfalls are expressed in the pantry item measure. Amounts that cannot be
+/// compared with the pantry item count any stock of the ingredient as enough.
+fn missing_ingredients(
+    shopping_list_id: Uuid,
+    recipe_id: Uuid,
+    recipe_ingredients: &[RecipeIngredientDto],
+    pantry_items: &[PantryItemDto],
+) -> Vec<CreateItemDto> {
+    let mut missing = Vec::new();
+    for recipe_ingredient in recipe_ingredients.iter().filter(|ri| !ri.optional) {
+        let held: Vec<(Measure, i32)> = pantry_items
+            .iter()
+            .filter(|item| item.ingredient_id == recipe_ingredient.ingredient_id)
+            .filter_map(|item| {
+                Measure::of_pantry_item(item.quantity, item.weight_grams, item.volume_milli_litres)
+            })
+            .collect();
+        let (amount, unit) = if let Some((measure, _)) = held.first() {
+            let needed = recipe_ingredient.amount.as_deref().and_then(|amount| {
+                Measurement::parse(amount, recipe_ingredient.unit.as_deref())?.in_measure(*measure)
+            });
+            let Some(needed) = needed else {
+                continue;
+            };
+            let held: i32 = held
+                .iter()
+                .filter(|(m, _)| m == measure)
+                .map(|(_, amount)| amount)
+                .sum();
+            if needed <= held {
+                continue;
+            }
+            (
+                Some((needed - held).to_string()),
+                measure.unit().map(|unit| unit.symbol().to_owned()),
+            )
+        } else {
+            (
+                recipe_ingredient.amount.clone(),
+                recipe_ingredient.unit.clone(),
+            )
+        };
+        missing.push(CreateItemDto {
+            shopping_list_id,
+            ingredient_id: recipe_ingredient.ingredient_id,
+            recipe_id: Some(recipe_id),
+            amount,
+            unit,
+        });
+    }
+    missing
+}
+
+/// Amount of the item on the list with the shortfall added, in the item's unit.
+/// Returns `None` if either has no amount or their units cannot be converted.
+fn merged_amount(
+    amount: Option<&str>,
+    unit: Option<&str>,
+    shortfall: &CreateItemDto,
+) -> Option<String> {
+    let on_list = Measurement::parse(amount?, unit)?;
+    let missing = Measurement::parse(shortfall.amount.as_deref()?, shortfall.unit.as_deref())?;
+    let missing = match (on_list.unit, missing.unit) {
+        (None, None) => missing.amount,
+        (Some(unit), Some(_)) => missing.convert(unit)?,
+        _ => return None,
+    };
+    on_list
+        .amount
+        .checked_add(&missing)
+        .map(|amount| amount.to_string())
+}
+
+/// Returns essential pantry items whose amount has fallen below `running_low`
+fn running_low_essentials(
+    shopping_list_id: Uuid,
+    pantry_items: &[PantryItemDto],
+) -> Vec<CreateItemDto> {
+    let mut seen = HashSet::new();
+    pantry_items
+        .iter()
+        .filter(|item| item.essential && item.running_low.is_some())
+        .filter(|item| seen.insert(item.ingredient_id))
+        .filter_map(|item| {
+            // Lots of an ingredient run low together
+            let lots: Vec<&PantryItemDto> = pantry_items
+                .iter()
+                .filter(|lot| lot.ingredient_id == item.ingredient_id)
+                .collect();
+            let running_low = lots.iter().filter_map(|lot| lot.running_low).max()?;
+            let held: Vec<(Measure, i32)> = lots
+                .iter()
+                .filter_map(|lot| {
+                    Measure::of_pantry_item(lot.quantity, lot.weight_grams, lot.volume_milli_litres)
+                })
+                .collect();
+            let measure = held
+                .first()
+                .map_or(Measure::Quantity, |(measure, _)| *measure);
+            let current: i32 = held
+                .iter()
+                .filter(|(m, _)| *m == measure)
+                .map(|(_, amount)| amount)
+                .sum();
+            let unit = measure.unit().map(|unit| unit.symbol().to_owned());
+            (current < running_low).then(|| CreateItemDto {
+                shopping_list_id,
+                ingredient_id: item.ingredient_id,
+                recipe_id: None,
+                amount: Some((running_low - current).to_string()),
+                unit,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shortfall(amount: Option<&str>, unit: Option<&str>) -> CreateItemDto {
+        CreateItemDto {
+            shopping_list_id: Uuid::new_v4(),
+            ingredient_id: Uuid::new_v4(),
+            recipe_id: Some(Uuid::new_v4()),
+            amount: amount.map(str::to_owned),
+            unit: unit.map(str::to_owned),
+        }
+    }
+
+    #[test]
+    fn shortfalls_are_added_to_items_on_the_list() {
+        let cases = [
+            ((Some("2"), None), (Some("3"), None), Some("5")),
+            (
+                (Some("200"), Some("g")),
+                (Some("300"), Some("g")),
+                Some("500"),
+            ),
+            (
+                (Some("1"), Some("kg")),
+                (Some("250"), Some("g")),
+                Some("5/4"),
+            ),
+            (
+                (Some("1 1/2"), Some("cup")),
+                (Some("1/2"), Some("cups")),
+                Some("2"),
+            ),
+            ((Some("2"), None), (Some("300"), Some("g")), None),
+            ((Some("200"), Some("g")), (Some("3"), None), None),
+            ((None, None), (Some("3"), None), None),
+            ((Some("2"), None), (None, None), None),
+            ((Some("a pinch"), None), (Some("2"), None), None),
+            ((Some("9223372036854775807"), None), (Some("1"), None), None),
+        ];
+        for ((amount, unit), (missing, missing_unit), merged) in cases {
+            assert_eq!(
+                merged_amount(amount, unit, &shortfall(missing, missing_unit)).as_deref(),
+                merged,
+                "{amount:?} {unit:?} + {missing:?} {missing_unit:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::database::shopping_lists::dto::{
+    CreateDto, CreateItemDto, ListParamsDto, ShoppingListDto, ShoppingListItemDto,
+    ShoppingListItemJoinDto, ShoppingListItemsListDto, ShoppingListsListDto, UpdateDto,
+    UpdateItemDto,
+};
+use crate::server::payload::{MetadataResponse, DEFAULT_PER_PAGE};
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CreatePayload {
+    pub name: String,
+}
+
+impl CreatePayload {
+    pub fn into_dto(self, user_id: Uuid) -> CreateDto {
+        CreateDto {
+            user_id,
+            name: self.name,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct UpdatePayload {
+    pub name: String,
+}
+
+impl From<UpdatePayload> for UpdateDto {
+    fn from(val: UpdatePayload) -> Self {
+        UpdateDto { name: val.name }
+    }
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct ListQueryParams {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+}
+
+impl ListQueryParams {
+    pub fn into_dto(self, user_id: Uuid) -> ListParamsDto {
+        ListParamsDto {
+            user_id: Some(user_id),
+            limit: self.per_page.unwrap_or(DEFAULT_PER_PAGE),
+            offset: self.per_page.unwrap_or(DEFAULT_PER_PAGE) * (self.page.unwrap_or(1) - 1),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct ShoppingListResponse {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl From<ShoppingListDto> for ShoppingListResponse {
+    fn from(val: ShoppingListDto) -> Self {
+        ShoppingListResponse {
+            id: val.id,
+            user_id: val.user_id,
+            name: val.name,
+            created_at: val.created_at,
+            updated_at: val.updated_at,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct ShoppingListListResponse {
+    #[serde(rename = "_metadata")]
+    pub metadata: MetadataResponse,
+    pub items: Vec<ShoppingListResponse>,
+}
+
+impl From<ShoppingListsListDto> for Vec<ShoppingListResponse> {
+    fn from(val: ShoppingListsListDto) -> Self {
+        val.items.into_iter().map(Into::into).collect()
+    }
+}
+
+impl ShoppingListListResponse {
+    pub fn from(items: Vec<ShoppingListResponse>, metadata: MetadataResponse) -> Self {
+        ShoppingListListResponse { metadata, items }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CreateItemPayload {
+    pub ingredient_id: Uuid,
+    pub amount: Option<String>,
+    pub unit: Option<String>,
+}
+
+impl CreateItemPayload {
+    pub fn into_dto(self, shopping_list_id: Uuid) -> CreateItemDto {
+        CreateItemDto {
+            shopping_list_id,
+            ingredient_id: self.ingredient_id,
+            recipe_id: None,
+            amount: self.amount,
+            unit: self.unit,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct UpdateItemPayload {
+    pub amount: Option<String>,
+    pub unit: Option<String>,
+    pub checked: bool,
+}
+
+impl From<UpdateItemPayload> for UpdateItemDto {
+    fn from(val: UpdateItemPayload) -> Self {
+        UpdateItemDto {
+            amount: val.amount,
+            unit: val.unit,
+            checked: val.checked,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct AddRecipePayload {
+    pub recipe_id: Uuid,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct ShoppingListItemResponse {
+    pub id: Uuid,
+    pub shopping_list_id: Uuid,
+    pub ingredient_id: Uuid,
+    pub ingredient_name: Option<String>,
+    pub recipe_id: Option<Uuid>,
+    pub amount: Option<String>,
+    pub unit: Option<String>,
+    pub checked: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl From<ShoppingListItemDto> for ShoppingListItemResponse {
+    fn from(val: ShoppingListItemDto) -> Self {
+        ShoppingListItemResponse {
+            id: val.id,
+            shopping_list_id: val.shopping_list_id,
+            ingredient_id: val.ingredient_id,
+            ingredient_name: None,
+            recipe_id: val.recipe_id,
+            amount: val.amount,
+            unit: val.unit,
+            checked: val.checked,
+            created_at: val.created_at,
+            updated_at: val.updated_at,
+        }
+    }
+}
+
+impl From<ShoppingListItemJoinDto> for ShoppingListItemResponse {
+    fn from(val: ShoppingListItemJoinDto) -> Self {
+        ShoppingListItemResponse {
+            id: val.id,
+            shopping_list_id: val.shopping_list_id,
+            ingredient_id: val.ingredient_id,
+            ingredient_name: Some(val.ingredient_name),
+            recipe_id: val.recipe_id,
+            amount: val.amount,
+            unit: val.unit,
+            checked: val.checked,
+            created_at: val.created_at,
+            updated_at: val.updated_at,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct ShoppingListItemListResponse {
+    pub items: Vec<ShoppingListItemResponse>,
+}
+
+impl From<ShoppingListItemsListDto> for ShoppingListItemListResponse {
+    fn from(val: ShoppingListItemsListDto) -> Self {
+        ShoppingListItemListResponse {
+            items: val.items.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<Vec<ShoppingListItemDto>> for ShoppingListItemListResponse {
+    fn from(val: Vec<ShoppingListItemDto>) -> Self {
+        ShoppingListItemListResponse {
+            items: val.into_iter().map(Into::into).collect(),
+        }
+    }
+}
@@ -4,17 +4,23 @@ use axum::extract::Query;
 use axum::{
     extract::{Json, Path, State},
     http::StatusCode,
-    routing::get,
+    routing::{delete, get},
     Router,
 };
-use axum_extra::extract::CookieJar;
 use uuid::Uuid;
 
-use crate::server::routes::errors::AppError;
+use crate::server::routes::auth::{api_token, AdminUser, CurrentUser, SessionUser};
+use crate::server::routes::errors::{AppError, VerifyError};
 use crate::server::routes::users::payload::ListQueryParams;
-use crate::server::routes::COOKIE_KEY;
+use crate::server::routes::utils::{generate_secret, hash_password, verify_password};
+use crate::server::session::SessionStore;
 use crate::server::state::AppState;
-use payload::{CreatePayload, UpdatePayload, UserResponse, UsersListResponse};
+use payload::{
+    CreatePayload, CreatedTokenResponse, TokenListQueryParams, TokenListResponse, TokenPayload,
+    TokenResponse, UpdatePayload, UserResponse, UsersListResponse,
+};
+
+const API_TOKEN_SECRET_LENGTH: usize = 40;
 
 pub struct UserRouter {}
 
@@ -28,6 +34,11 @@ impl UserRouter {
                     .put(UserRouter::update)
                     .delete(UserRouter::delete),
             )
+            .route(
+                "/:id/tokens",
+                get(UserRouter::list_tokens).post(UserRouter::create_token),
+            )
+            .route("/:id/tokens/:token_id", delete(UserRouter::delete_token))
     }
 
     async fn create(
@@ -41,80 +52,139 @@ impl UserRouter {
 
     async fn list(
         State(state): State<AppState>,
-        jar: CookieJar,
+        _admin: AdminUser,
         Query(query_params): Query<ListQueryParams>,
     ) -> Result<(StatusCode, Json<UsersListResponse>), AppError> {
-        if let Some(session_id) = jar.get(COOKIE_KEY) {
-            if let Some(user_id) = state.get_sessions_user(session_id.value_trimmed()).await? {
-                if state.user_is_admin(user_id).await? {
-                    let list_params = query_params.into();
-                    let users: Vec<UserResponse> =
-                        state.db_client.list_users(&list_params).await?.into();
-                    log::info!("{:?} users collected", users.len());
-                    let metadata = state
-                        .db_client
-                        .get_users_metadata(&list_params)
-                        .await?
-                        .into();
-                    return Ok((
-                        StatusCode::OK,
-                        Json(UsersListResponse::from(users, metadata)),
-                    ));
-                }
-            }
-        }
-        Err(AppError::Unauthorized)
+        let list_params = query_params.into();
+        let users: Vec<UserResponse> = state.db_client.list_users(&list_params).await?.into();
+        log::info!("{:?} users collected", users.len());
+        let metadata = state
+            .db_client
+            .get_users_metadata(&list_params)
+            .await?
+            .into();
+        Ok((
+            StatusCode::OK,
+            Json(UsersListResponse::from(users, metadata)),
+        ))
     }
 
     async fn get(
         State(state): State<AppState>,
-        jar: CookieJar,
+        CurrentUser(user_id): CurrentUser,
         Path(id): Path<Uuid>,
     ) -> Result<(StatusCode, Json<UserResponse>), AppError> {
-        if let Some(session_id) = jar.get(COOKIE_KEY) {
-            if let Some(user_id) = state.get_sessions_user(session_id.value_trimmed()).await? {
-                if user_id == id || state.user_is_admin(user_id).await? {
-                    let user = state.db_client.get_user(id).await?;
-                    log::info!("Got user with id {:?}", user.id);
-                    return Ok((StatusCode::OK, Json(user.into())));
-                }
-            }
+        if user_id == id || state.user_is_admin(user_id).await? {
+            let user = state.db_client.get_user(id).await?;
+            log::info!("Got user with id {:?}", user.id);
+            return Ok((StatusCode::OK, Json(user.into())));
         }
-        Err(AppError::Unauthorized)
+        Err(AppError::Forbidden)
     }
 
+    /// Needs a session, API tokens cannot change the password
     async fn update(
         State(state): State<AppState>,
         Path(id): Path<Uuid>,
-        jar: CookieJar,
+        SessionUser(user_id): SessionUser,
         Json(payload): Json<UpdatePayload>,
     ) -> Result<(StatusCode, Json<UserResponse>), AppError> {
-        if let Some(session_id) = jar.get(COOKIE_KEY) {
-            if let Some(user_id) = state.get_sessions_user(session_id.value_trimmed()).await? {
-                if user_id == id || state.user_is_admin(user_id).await? {
-                    let user = state.db_client.update_user(id, payload.into()).await?;
-                    log::info!("Updated user with id {id:?}");
-                    return Ok((StatusCode::OK, Json(user.into())));
-                }
+        if user_id == id || state.user_is_admin(user_id).await? {
+            let old_user = state.db_client.get_user(id).await?;
+            let password_changed = !verify_password(&payload.password, &old_user.password_hash);
+            let user = state.db_client.update_user(id, payload.into()).await?;
+            log::info!("Updated user with id {id:?}");
+            // Sessions and tokens created with the old password must not outlive it
+            if password_changed {
+                state.redis_sender.delete_user_sessions(id).await?;
+                let revoked = state.db_client.delete_user_api_tokens(id).await?;
+                log::info!("Revoked {revoked} API tokens of user {id:?}");
             }
+            return Ok((StatusCode::OK, Json(user.into())));
         }
-        Err(AppError::Unauthorized)
+        Err(AppError::Forbidden)
     }
 
     async fn delete(
         State(state): State<AppState>,
         Path(id): Path<Uuid>,
-        jar: CookieJar,
+        CurrentUser(user_id): CurrentUser,
     ) -> Result<StatusCode, AppError> {
-        if let Some(session_id) = jar.get(COOKIE_KEY) {
-            if let Some(user_id) = state.get_sessions_user(session_id.value_trimmed()).await? {
-                if user_id == id || state.user_is_admin(user_id).await? {
-                    state.db_client.delete_user(id).await?;
-                    log::info!("Deleted user with id {:?}", id);
-                    return Ok(StatusCode::NO_CONTENT);
-                }
-            }
+        if user_id == id || state.user_is_admin(user_id).await? {
+            state.db_client.delete_user(id).await?;
+            state.redis_sender.delete_user_sessions(id).await?;
+            log::info!("Deleted user with id {:?}", id);
+            return Ok(StatusCode::NO_CONTENT);
+        }
+        Err(AppError::Forbidden)
+    }
+
+    /// Creates an API token, its secret is only returned in this response.
+    /// Needs a session, API tokens cannot mint more tokens.
+    async fn create_token(
+        State(state): State<AppState>,
+        Path(id): Path<Uuid>,
+        SessionUser(user_id): SessionUser,
+        Json(payload): Json<TokenPayload>,
+    ) -> Result<(StatusCode, Json<CreatedTokenResponse>), AppError> {
+        verify_user(&state, id, user_id).await?;
+        let secret = generate_secret(API_TOKEN_SECRET_LENGTH);
+        let token = state
+            .db_client
+            .create_api_token(payload.into_dto(id, hash_password(&secret)))
+            .await?;
+        log::info!("API token with id {:?} created for user {id:?}", token.id);
+        let value = api_token(token.id, &secret);
+        Ok((
+            StatusCode::CREATED,
+            Json(CreatedTokenResponse::from(token, value)),
+        ))
+    }
+
+    async fn list_tokens(
+        State(state): State<AppState>,
+        Path(id): Path<Uuid>,
+        CurrentUser(user_id): CurrentUser,
+        Query(query_params): Query<TokenListQueryParams>,
+    ) -> Result<(StatusCode, Json<TokenListResponse>), AppError> {
+        verify_user(&state, id, user_id).await?;
+        let list_params = query_params.into_dto(id);
+        let tokens: Vec<TokenResponse> =
+            state.db_client.list_api_tokens(&list_params).await?.into();
+        log::info!("{:?} API tokens collected", tokens.len());
+        let metadata = state
+            .db_client
+            .get_api_tokens_metadata(&list_params)
+            .await?
+            .into();
+        Ok((
+            StatusCode::OK,
+            Json(TokenListResponse::from(tokens, metadata)),
+        ))
+    }
+
+    async fn delete_token(
+        State(state): State<AppState>,
+        Path((id, token_id)): Path<(Uuid, Uuid)>,
+        CurrentUser(user_id): CurrentUser,
+    ) -> Result<StatusCode, AppError> {
+        verify_user(&state, id, user_id).await?;
+        let token = state.db_client.get_api_token(token_id).await?;
+        if token.user_id != id {
+            return Err(AppError::NotFound {
+                id: token_id.to_string(),
+            });
         }
-        Err(AppError::Unauthorized)
+        state.db_client.delete_api_token(token_id).await?;
+        log::info!("Revoked API token with id {:?}", token_id);
+        Ok(StatusCode::NO_CONTENT)
+    }
+}
+
+/// Users manage their own tokens, admins manage everyone's
+async fn verify_user(state: &AppState, id: Uuid, user_id: Uuid) -> Result<(), VerifyError> {
+    if user_id == id || state.user_is_admin(user_id).await? {
+        return Ok(());
     }
+    Err(VerifyError::Forbidden)
 }
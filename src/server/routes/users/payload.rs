@@ -2,6 +2,9 @@ use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::database::api_tokens::dto::{
+    ApiTokenDto, ApiTokensListDto, CreateDto as TokenCreateDto, ListParamsDto as TokenListParamsDto,
+};
 use crate::database::users::dto::{CreateDto, ListParamsDto, UpdateDto, UserDto, UsersListDto};
 use crate::server::payload::{MetadataResponse, DEFAULT_PER_PAGE};
 use crate::server::routes::utils::hash_password;
@@ -98,3 +101,98 @@ impl UsersListResponse {
         UsersListResponse { metadata, items }
     }
 }
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct TokenPayload {
+    pub name: String,
+    /// Read-only tokens can only be used for GET requests
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+impl TokenPayload {
+    pub fn into_dto(self, user_id: Uuid, secret_hash: String) -> TokenCreateDto {
+        TokenCreateDto {
+            user_id,
+            name: self.name,
+            secret_hash,
+            read_only: self.read_only,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct TokenListQueryParams {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+}
+
+impl TokenListQueryParams {
+    pub fn into_dto(self, user_id: Uuid) -> TokenListParamsDto {
+        TokenListParamsDto {
+            user_id: Some(user_id),
+            limit: self.per_page.unwrap_or(DEFAULT_PER_PAGE),
+            offset: self.per_page.unwrap_or(DEFAULT_PER_PAGE) * (self.page.unwrap_or(1) - 1),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct TokenResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub read_only: bool,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<ApiTokenDto> for TokenResponse {
+    fn from(val: ApiTokenDto) -> Self {
+        TokenResponse {
+            id: val.id,
+            name: val.name,
+            read_only: val.read_only,
+            created_at: val.created_at,
+        }
+    }
+}
+
+/// Only returned when the token is created, the token cannot be retrieved again
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct CreatedTokenResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub read_only: bool,
+    pub created_at: NaiveDateTime,
+    pub token: String,
+}
+
+impl CreatedTokenResponse {
+    pub fn from(val: ApiTokenDto, token: String) -> Self {
+        CreatedTokenResponse {
+            id: val.id,
+            name: val.name,
+            read_only: val.read_only,
+            created_at: val.created_at,
+            token,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct TokenListResponse {
+    #[serde(rename = "_metadata")]
+    pub metadata: MetadataResponse,
+    pub items: Vec<TokenResponse>,
+}
+
+impl From<ApiTokensListDto> for Vec<TokenResponse> {
+    fn from(val: ApiTokensListDto) -> Self {
+        val.items.into_iter().map(Into::into).collect()
+    }
+}
+
+impl TokenListResponse {
+    pub fn from(items: Vec<TokenResponse>, metadata: MetadataResponse) -> Self {
+        TokenListResponse { metadata, items }
+    }
+}
@@ -3,6 +3,8 @@ use argon2::{
     PasswordHasher,
 };
 use argon2::{Argon2, PasswordHash, PasswordVerifier};
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
 
 pub fn hash_password(password: &str) -> String {
     let salt = SaltString::generate(OsRng);
@@ -21,3 +23,12 @@ pub fn verify_password(password: &str, password_hash: &str) -> bool {
         .verify_password(password.as_bytes(), &parsed_hash)
         .is_ok()
 }
+
+/// Random alphanumeric secret, for API tokens
+pub fn generate_secret(length: usize) -> String {
+    thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(length)
+        .map(char::from)
+        .collect()
+}
@@ -0,0 +1,168 @@
+//! Sessions are stored in Redis as `session:<token>`, and the tokens of every user are kept
+//! in the set `user_sessions:<user_id>` so they can be listed and revoked together.
+
+use axum_extra::extract::cookie::{Cookie, SameSite};
+use chrono::{NaiveDateTime, Utc};
+use color_eyre::eyre::eyre;
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+use time::Duration;
+use tokio::sync::mpsc::Sender;
+use uuid::Uuid;
+
+use crate::redis::{RedisCommand, RedisCommands, RedisError, RedisResult};
+
+pub const COOKIE_KEY: &str = "session_id";
+const TOKEN_LENGTH: usize = 30;
+
+#[derive(Clone, Copy, Debug)]
+pub struct SessionSettings {
+    /// Seconds a session stays valid since it was last used
+    pub ttl_secs: u64,
+    /// Only send the cookie over HTTPS
+    pub secure_cookie: bool,
+}
+
+impl SessionSettings {
+    /// Cookie holding the session token, its `Max-Age` matches the session TTL
+    pub fn cookie(&self, token: String) -> Cookie<'static> {
+        Cookie::build((COOKIE_KEY, token))
+            .path("/")
+            .http_only(true)
+            .secure(self.secure_cookie)
+            .same_site(SameSite::Lax)
+            .max_age(Duration::seconds(
+                i64::try_from(self.ttl_secs).unwrap_or(i64::MAX),
+            ))
+            .build()
+    }
+}
+
+/// Cookie that makes the browser forget the session token
+pub fn removal_cookie() -> Cookie<'static> {
+    Cookie::build(COOKIE_KEY).path("/").build()
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Session {
+    /// Identifies the session to the user without revealing its token
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub user_agent: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+fn session_key(token: &str) -> String {
+    format!("session:{token}")
+}
+
+fn user_sessions_key(user_id: Uuid) -> String {
+    format!("user_sessions:{user_id}")
+}
+
+pub trait SessionStore {
+    /// Returns the token of the new session
+    async fn create_session(
+        &self,
+        user_id: Uuid,
+        user_agent: Option<String>,
+        ttl_secs: u64,
+    ) -> RedisResult<String>;
+    async fn get_session(&self, token: &str) -> RedisResult<Option<Session>>;
+    /// Pushes the expiry of the session back to `ttl_secs` from now.
+    /// Returns `false` if the session does not exist anymore.
+    async fn refresh_session(&self, token: &str, ttl_secs: u64) -> RedisResult<bool>;
+    async fn delete_session(&self, token: &str) -> RedisResult<()>;
+    /// Returns the tokens and sessions of the user, forgetting the ones that expired
+    async fn list_sessions(&self, user_id: Uuid) -> RedisResult<Vec<(String, Session)>>;
+    /// Logs the user out everywhere
+    async fn delete_user_sessions(&self, user_id: Uuid) -> RedisResult<()>;
+}
+
+impl SessionStore for Sender<RedisCommand> {
+    async fn create_session(
+        &self,
+        user_id: Uuid,
+        user_agent: Option<String>,
+        ttl_secs: u64,
+    ) -> RedisResult<String> {
+        let token: String = thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(TOKEN_LENGTH)
+            .map(char::from)
+            .collect();
+        let session = Session {
+            id: Uuid::new_v4(),
+            user_id,
+            user_agent,
+            created_at: Utc::now().naive_utc(),
+        };
+        let value = serde_json::to_string(&session).map_err(|err| RedisError::Redis {
+            error: eyre!("Could not serialize session: {err}"),
+        })?;
+        self.set(&session_key(&token), &value, Some(ttl_secs))
+            .await?;
+        let user_sessions = user_sessions_key(user_id);
+        self.set_add(&user_sessions, &token).await?;
+        self.expire(&user_sessions, ttl_secs).await?;
+        log::info!("Session {:?} created", session.id);
+        Ok(token)
+    }
+
+    async fn get_session(&self, token: &str) -> RedisResult<Option<Session>> {
+        match self.get(&session_key(token)).await? {
+            Some(value) => Ok(Some(serde_json::from_str(&value).map_err(|err| {
+                RedisError::Redis {
+                    error: eyre!("Session value is not a session: {err}"),
+                }
+            })?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn refresh_session(&self, token: &str, ttl_secs: u64) -> RedisResult<bool> {
+        let Some(session) = self.get_session(token).await? else {
+            return Ok(false);
+        };
+        if !self.expire(&session_key(token), ttl_secs).await? {
+            return Ok(false);
+        }
+        // The set has to outlive the newest of its sessions
+        self.expire(&user_sessions_key(session.user_id), ttl_secs)
+            .await?;
+        Ok(true)
+    }
+
+    async fn delete_session(&self, token: &str) -> RedisResult<()> {
+        if let Some(session) = self.get_session(token).await? {
+            self.set_remove(&user_sessions_key(session.user_id), token)
+                .await?;
+            log::info!("Session {:?} deleted", session.id);
+        }
+        self.delete(&session_key(token)).await
+    }
+
+    async fn list_sessions(&self, user_id: Uuid) -> RedisResult<Vec<(String, Session)>> {
+        let user_sessions = user_sessions_key(user_id);
+        let mut sessions = Vec::new();
+        for token in self.set_members(&user_sessions).await? {
+            match self.get_session(&token).await? {
+                Some(session) => sessions.push((token, session)),
+                None => self.set_remove(&user_sessions, &token).await?,
+            }
+        }
+        sessions.sort_by_key(|(_, session)| session.created_at);
+        Ok(sessions)
+    }
+
+    async fn delete_user_sessions(&self, user_id: Uuid) -> RedisResult<()> {
+        let user_sessions = user_sessions_key(user_id);
+        for token in self.set_members(&user_sessions).await? {
+            self.delete(&session_key(&token)).await?;
+        }
+        self.delete(&user_sessions).await?;
+        log::info!("Deleted all sessions of user {user_id:?}");
+        Ok(())
+    }
+}
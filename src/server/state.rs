@@ -1,45 +1,130 @@
-use color_eyre::eyre::eyre;
 use sea_orm::DatabaseConnection;
-use std::str::FromStr;
 use std::sync::Arc;
 use tokio::sync::mpsc::Sender;
 use uuid::Uuid;
 
 use crate::database::errors::GetError;
+use crate::database::households::dto::HouseholdRole;
 use crate::database::{DBClient, DBTrait};
-use crate::redis::{RedisCommand, RedisCommands, RedisError, RedisResult};
+use crate::fetch::Fetcher;
+use crate::matching::IngredientMatcherCache;
+use crate::oidc::OidcClient;
+use crate::redis::{RedisCommand, RedisResult};
+use crate::scrapers::ScraperRegistry;
+use crate::server::limits::LoginLimits;
+use crate::server::session::{SessionSettings, SessionStore};
+
+/// Access a user needs to a pantry item or recipe
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
 
 #[derive(Clone)]
 pub struct AppState {
     pub db_client: Arc<dyn DBTrait + Send + Sync>,
     pub redis_sender: Sender<RedisCommand>,
+    pub fetcher: Fetcher,
+    pub scrapers: Arc<ScraperRegistry>,
+    pub recipe_cache_ttl_secs: u64,
+    pub session_settings: SessionSettings,
+    /// `None` if OIDC login is disabled
+    pub oidc: Option<OidcClient>,
+    pub login_limits: LoginLimits,
+    pub ingredient_matcher: IngredientMatcherCache,
 }
 
 impl AppState {
-    pub fn new(db_connection: DatabaseConnection, redis_sender: Sender<RedisCommand>) -> Self {
+    pub fn new(
+        db_connection: DatabaseConnection,
+        redis_sender: Sender<RedisCommand>,
+        fetcher: Fetcher,
+        recipe_cache_ttl_secs: u64,
+        session_settings: SessionSettings,
+        oidc: Option<OidcClient>,
+        login_limits: LoginLimits,
+    ) -> Self {
         let db_client = DBClient::new(db_connection);
         Self {
             db_client: Arc::new(db_client),
             redis_sender,
+            fetcher,
+            scrapers: Arc::new(ScraperRegistry::default()),
+            recipe_cache_ttl_secs,
+            session_settings,
+            oidc,
+            login_limits,
+            ingredient_matcher: IngredientMatcherCache::default(),
         }
     }
-    /// Returns the `user_id`
-    pub async fn session_is_valid(&self, session_id: &str) -> RedisResult<bool> {
-        Ok(self.redis_sender.get(session_id).await?.is_some())
+
+    /// Replaces the default scrapers, e.g. with sites pinned to a scraper
+    #[must_use]
+    pub fn with_scrapers(mut self, scrapers: ScraperRegistry) -> Self {
+        self.scrapers = Arc::new(scrapers);
+        self
     }
+
     /// Returns the `user_id`
     pub async fn get_sessions_user(&self, session_id: &str) -> RedisResult<Option<Uuid>> {
-        match self.redis_sender.get(session_id).await? {
-            Some(id) => Ok(Some(Uuid::from_str(&id).map_err(|err| {
-                RedisError::Redis {
-                    error: eyre!("Session id value is not uuid: {err}"),
-                }
-            })?)),
-            None => Ok(None),
-        }
+        Ok(self
+            .redis_sender
+            .get_session(session_id)
+            .await?
+            .map(|session| session.user_id))
     }
 
     pub async fn user_is_admin(&self, user_id: Uuid) -> Result<bool, GetError> {
         Ok(self.db_client.get_user(user_id).await?.admin)
     }
+
+    /// Returns `None` if the user is not a member of the household
+    pub async fn household_role(
+        &self,
+        household_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<HouseholdRole>, GetError> {
+        match self
+            .db_client
+            .get_household_member(household_id, user_id)
+            .await
+        {
+            Ok(member) => Ok(Some(member.role)),
+            Err(GetError::NotFound { .. }) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Viewers may only read, owners and members may also write
+    pub async fn household_allows(
+        &self,
+        household_id: Uuid,
+        user_id: Uuid,
+        access: Access,
+    ) -> Result<bool, GetError> {
+        Ok(match self.household_role(household_id, user_id).await? {
+            Some(HouseholdRole::Owner | HouseholdRole::Member) => true,
+            Some(HouseholdRole::Viewer) => access == Access::Read,
+            None => false,
+        })
+    }
+
+    /// Whether the user owns the row or may access it through the household it is shared with.
+    /// Admins may read and write every row.
+    pub async fn has_access(
+        &self,
+        user_id: Uuid,
+        owner_id: Uuid,
+        household_id: Option<Uuid>,
+        access: Access,
+    ) -> Result<bool, GetError> {
+        if owner_id == user_id || self.user_is_admin(user_id).await? {
+            return Ok(true);
+        }
+        match household_id {
+            Some(household_id) => self.household_allows(household_id, user_id, access).await,
+            None => Ok(false),
+        }
+    }
 }
@@ -41,6 +41,9 @@ pub async fn migrate_test_data(client: impl DBTrait + Send + Sync) -> AnyResult<
         .create_pantry_item(crate::database::pantry_items::dto::CreateDto {
             ingredient_id: chicken.id,
             user_id: user.id,
+            household_id: None,
+            location_id: None,
+            purchase_date: None,
             expiration_date: Some(NaiveDate::from_ymd_opt(2024, 4, 20).unwrap()),
             quantity: None,
             weight_grams: Some(400),
@@ -53,6 +56,9 @@ pub async fn migrate_test_data(client: impl DBTrait + Send + Sync) -> AnyResult<
         .create_pantry_item(crate::database::pantry_items::dto::CreateDto {
             ingredient_id: chicken.id,
             user_id: admin.id,
+            household_id: None,
+            location_id: None,
+            purchase_date: None,
             expiration_date: Some(NaiveDate::from_ymd_opt(2024, 4, 20).unwrap()),
             quantity: None,
             weight_grams: Some(400),
@@ -61,10 +67,28 @@ pub async fn migrate_test_data(client: impl DBTrait + Send + Sync) -> AnyResult<
             running_low: None,
         })
         .await?;
+    client
+        .create_pantry_item(crate::database::pantry_items::dto::CreateDto {
+            ingredient_id: chicken.id,
+            user_id: user.id,
+            household_id: None,
+            location_id: None,
+            purchase_date: Some(NaiveDate::from_ymd_opt(2024, 4, 18).unwrap()),
+            expiration_date: Some(NaiveDate::from_ymd_opt(2024, 4, 25).unwrap()),
+            quantity: None,
+            weight_grams: Some(250),
+            volume_milli_litres: None,
+            essential: false,
+            running_low: None,
+        })
+        .await?;
     client
         .create_pantry_item(crate::database::pantry_items::dto::CreateDto {
             ingredient_id: rice.id,
             user_id: user.id,
+            household_id: None,
+            location_id: None,
+            purchase_date: None,
             expiration_date: None,
             quantity: None,
             weight_grams: Some(400),
@@ -76,6 +100,7 @@ pub async fn migrate_test_data(client: impl DBTrait + Send + Sync) -> AnyResult<
 
     let chicken_recipe = client.create_recipe(crate::database::recipes::dto::CreateDto{
         user_id: user.id,
+        household_id: None,
         name: "Plain Chicken".to_owned(),
         total_time_mins: Some(20),
         link: None,
@@ -88,6 +113,7 @@ pub async fn migrate_test_data(client: impl DBTrait + Send + Sync) -> AnyResult<
     }).await?;
     let chicken_recipe_2 = client.create_recipe(crate::database::recipes::dto::CreateDto{
         user_id: admin.id,
+        household_id: None,
         name: "Plain Chicken".to_owned(),
         total_time_mins: Some(20),
         link: None,
@@ -100,6 +126,7 @@ pub async fn migrate_test_data(client: impl DBTrait + Send + Sync) -> AnyResult<
     }).await?;
     let chicken_rice_recipe = client.create_recipe(crate::database::recipes::dto::CreateDto{
         user_id: user.id,
+        household_id: None,
         name: "Chicken Rice".to_owned(),
         total_time_mins: Some(30),
         link: Some("https://iowagirleats.com/one-pot-chicken-and-rice/".to_owned()),
@@ -148,5 +175,47 @@ pub async fn migrate_test_data(client: impl DBTrait + Send + Sync) -> AnyResult<
         })
         .await?;
 
+    let dinner = client
+        .create_category(crate::database::categories::dto::CreateDto {
+            name: "Dinner".to_owned(),
+        })
+        .await?;
+    let _breakfast = client
+        .create_category(crate::database::categories::dto::CreateDto {
+            name: "Breakfast".to_owned(),
+        })
+        .await?;
+    client
+        .create_recipe_category(crate::database::recipe_categories::dto::CreateDto {
+            recipe_id: chicken_rice_recipe.id,
+            category_id: dinner.id,
+        })
+        .await?;
+
+    client
+        .create_product(crate::database::products::dto::CreateDto {
+            gtin: "05012345678900".to_owned(),
+            ingredient_id: rice.id,
+            name: Some("Long Grain Rice 1kg".to_owned()),
+            quantity: None,
+            weight_grams: Some(1000),
+            volume_milli_litres: None,
+        })
+        .await?;
+
+    let household = client
+        .create_household(crate::database::households::dto::CreateDto {
+            name: "Demo Household".to_owned(),
+            owner_id: user.id,
+        })
+        .await?;
+    client
+        .create_household_member(crate::database::households::dto::CreateMemberDto {
+            household_id: household.id,
+            user_id: admin.id,
+            role: crate::database::households::dto::HouseholdRole::Viewer,
+        })
+        .await?;
+
     Ok(())
 }
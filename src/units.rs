@@ -0,0 +1,422 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use num_rational::Ratio;
+use num_traits::{CheckedAdd, CheckedDiv, CheckedMul};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Exact recipe amount, e.g. `3/2` for "1 1/2"
+pub type Amount = Ratio<i64>;
+
+/// Grams per millilitre used when a mass has to be compared with a volume.
+/// Ingredients have no density of their own, so water is the approximation.
+const WATER_DENSITY: Amount = Ratio::new_raw(1, 1);
+
+#[derive(Error, Debug)]
+pub enum ParseUnitError {
+    #[error("Could not parse unit: unknown unit {unit}")]
+    Unknown { unit: String },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnitFamily {
+    Mass,
+    Volume,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Unit {
+    Gram,
+    Kilogram,
+    Ounce,
+    Pound,
+    Millilitre,
+    Litre,
+    Teaspoon,
+    Tablespoon,
+    FluidOunce,
+    Cup,
+    Pint,
+    Quart,
+    Gallon,
+}
+
+impl Unit {
+    pub fn family(self) -> UnitFamily {
+        match self {
+            Unit::Gram | Unit::Kilogram | Unit::Ounce | Unit::Pound => UnitFamily::Mass,
+            Unit::Millilitre
+            | Unit::Litre
+            | Unit::Teaspoon
+            | Unit::Tablespoon
+            | Unit::FluidOunce
+            | Unit::Cup
+            | Unit::Pint
+            | Unit::Quart
+            | Unit::Gallon => UnitFamily::Volume,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Unit::Gram => "g",
+            Unit::Kilogram => "kg",
+            Unit::Ounce => "oz",
+            Unit::Pound => "lb",
+            Unit::Millilitre => "ml",
+            Unit::Litre => "l",
+            Unit::Teaspoon => "tsp",
+            Unit::Tablespoon => "tbsp",
+            Unit::FluidOunce => "fl oz",
+            Unit::Cup => "cup",
+            Unit::Pint => "pt",
+            Unit::Quart => "qt",
+            Unit::Gallon => "gal",
+        }
+    }
+
+    /// Size of the unit in grams for mass and in millilitres for volume (US customary)
+    fn base_factor(self) -> Amount {
+        match self {
+            Unit::Gram | Unit::Millilitre => Ratio::from_integer(1),
+            Unit::Kilogram | Unit::Litre => Ratio::from_integer(1000),
+            Unit::Ounce => Ratio::new(45_359_237, 1_600_000),
+            Unit::Pound => Ratio::new(45_359_237, 100_000),
+            Unit::Teaspoon => Ratio::new(492_892, 100_000),
+            Unit::Tablespoon => Ratio::new(1_478_676, 100_000),
+            Unit::FluidOunce => Ratio::new(2_957_353, 100_000),
+            Unit::Cup => Ratio::new(23_658_824, 100_000),
+            Unit::Pint => Ratio::new(47_317_647, 100_000),
+            Unit::Quart => Ratio::new(94_635_295, 100_000),
+            Unit::Gallon => Ratio::new(378_541_178, 100_000),
+        }
+    }
+}
+
+impl Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+impl FromStr for Unit {
+    type Err = ParseUnitError;
+
+    fn from_str(unit: &str) -> Result<Self, Self::Err> {
+        let normalized = unit.trim().trim_end_matches('.').to_lowercase();
+        let unit = match normalized.as_str() {
+            "g" | "gr" | "gram" | "grams" | "gramme" | "grammes" => Unit::Gram,
+            "kg" | "kgs" | "kilogram" | "kilograms" => Unit::Kilogram,
+            "oz" | "ounce" | "ounces" => Unit::Ounce,
+            "lb" | "lbs" | "pound" | "pounds" => Unit::Pound,
+            "ml" | "milliliter" | "milliliters" | "millilitre" | "millilitres" => Unit::Millilitre,
+            "l" | "liter" | "liters" | "litre" | "litres" => Unit::Litre,
+            "tsp" | "tsps" | "teaspoon" | "teaspoons" => Unit::Teaspoon,
+            "tbsp" | "tbsps" | "tbs" | "tablespoon" | "tablespoons" => Unit::Tablespoon,
+            "fl oz" | "floz" | "fluid ounce" | "fluid ounces" => Unit::FluidOunce,
+            "cup" | "cups" | "c" => Unit::Cup,
+            "pt" | "pint" | "pints" => Unit::Pint,
+            "qt" | "quart" | "quarts" => Unit::Quart,
+            "gal" | "gallon" | "gallons" => Unit::Gallon,
+            _ => {
+                return Err(ParseUnitError::Unknown {
+                    unit: unit.to_owned(),
+                })
+            }
+        };
+        Ok(unit)
+    }
+}
+
+/// How a pantry item is measured
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Measure {
+    Quantity,
+    WeightGrams,
+    VolumeMilliLitres,
+}
+
+impl Measure {
+    /// Returns the pantry item measure along with the amount it holds
+    pub fn of_pantry_item(
+        quantity: Option<i32>,
+        weight_grams: Option<i32>,
+        volume_milli_litres: Option<i32>,
+    ) -> Option<(Measure, i32)> {
+        match (quantity, weight_grams, volume_milli_litres) {
+            (_, Some(grams), _) => Some((Measure::WeightGrams, grams)),
+            (_, _, Some(millilitres)) => Some((Measure::VolumeMilliLitres, millilitres)),
+            (Some(quantity), _, _) => Some((Measure::Quantity, quantity)),
+            _ => None,
+        }
+    }
+
+    pub fn unit(self) -> Option<Unit> {
+        match self {
+            Measure::Quantity => None,
+            Measure::WeightGrams => Some(Unit::Gram),
+            Measure::VolumeMilliLitres => Some(Unit::Millilitre),
+        }
+    }
+}
+
+/// Amount with an optional unit. Amounts without a unit are counts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Measurement {
+    pub amount: Amount,
+    pub unit: Option<Unit>,
+}
+
+impl Measurement {
+    /// Parses free-form recipe amount and unit strings
+    pub fn parse(amount: &str, unit: Option<&str>) -> Option<Self> {
+        let unit = match unit.map(str::trim) {
+            None | Some("") => None,
+            Some(unit) => Some(unit.parse::<Unit>().ok()?),
+        };
+        Some(Measurement {
+            amount: parse_amount(amount)?,
+            unit,
+        })
+    }
+
+    /// Converts into `to`, going through water density when the unit families differ.
+    /// Returns `None` for amounts too large to convert.
+    pub fn convert(&self, to: Unit) -> Option<Amount> {
+        let from = self.unit?;
+        let base = self.amount.checked_mul(&from.base_factor())?;
+        let base = match (from.family(), to.family()) {
+            (UnitFamily::Mass, UnitFamily::Volume) => base.checked_div(&WATER_DENSITY)?,
+            (UnitFamily::Volume, UnitFamily::Mass) => base.checked_mul(&WATER_DENSITY)?,
+            _ => base,
+        };
+        base.checked_div(&to.base_factor())
+    }
+
+    /// Expresses the measurement in the pantry item measure, rounding up
+    pub fn in_measure(&self, measure: Measure) -> Option<i32> {
+        match (measure.unit(), self.unit) {
+            (None, None) => Some(ceil(self.amount)),
+            (Some(unit), Some(_)) => self.convert(unit).map(ceil),
+            _ => None,
+        }
+    }
+
+    /// Expresses the measurement in its natural pantry measure:
+    /// counts as quantity, masses in grams, volumes in millilitres
+    pub fn in_base_measure(&self) -> (Measure, i32) {
+        let measure = match self.unit.map(Unit::family) {
+            None => Measure::Quantity,
+            Some(UnitFamily::Mass) => Measure::WeightGrams,
+            Some(UnitFamily::Volume) => Measure::VolumeMilliLitres,
+        };
+        (measure, self.in_measure(measure).unwrap_or_default())
+    }
+}
+
+/// Parses amounts such as "2", "1.5", "1/2", "1 1/2", "½" and "1½".
+/// Amounts too large to be exact are rejected.
+pub fn parse_amount(amount: &str) -> Option<Amount> {
+    let amount = amount.trim().replace(',', ".");
+    if amount.is_empty() {
+        return None;
+    }
+    let mut total = Ratio::from_integer(0);
+    let mut chars = amount.char_indices().peekable();
+    let mut start = 0;
+    // Vulgar fractions may follow the whole part with or without a space
+    while let Some((i, c)) = chars.next() {
+        if let Some(fraction) = vulgar_fraction(c) {
+            let whole = amount[start..i].trim();
+            if !whole.is_empty() {
+                total = total.checked_add(&parse_simple(whole)?)?;
+            }
+            total = total.checked_add(&fraction)?;
+            start = i + c.len_utf8();
+            if chars.peek().is_some() && !amount[start..].trim().is_empty() {
+                return None;
+            }
+            return Some(total);
+        }
+    }
+    let mut parts = amount.split_whitespace();
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(single), None, None) => parse_simple(single),
+        (Some(whole), Some(fraction), None) if fraction.contains('/') => {
+            parse_simple(whole)?.checked_add(&parse_simple(fraction)?)
+        }
+        _ => None,
+    }
+}
+
+fn parse_simple(amount: &str) -> Option<Amount> {
+    if let Some((numerator, denominator)) = amount.split_once('/') {
+        let numerator = numerator.trim().parse::<i64>().ok()?;
+        let denominator = denominator.trim().parse::<i64>().ok()?;
+        // Negative denominators would be negated, which overflows for `i64::MIN`
+        if denominator <= 0 {
+            return None;
+        }
+        return Some(Ratio::new(numerator, denominator));
+    }
+    if let Some((whole, decimals)) = amount.split_once('.') {
+        let whole = if whole.is_empty() {
+            0
+        } else {
+            whole.parse::<i64>().ok()?
+        };
+        let scale = 10_i64.checked_pow(u32::try_from(decimals.len()).ok()?)?;
+        let decimals = decimals.parse::<i64>().ok()?;
+        return Ratio::from_integer(whole).checked_add(&Ratio::new(decimals, scale));
+    }
+    amount.parse::<i64>().ok().map(Ratio::from_integer)
+}
+
+fn vulgar_fraction(c: char) -> Option<Amount> {
+    let (numerator, denominator) = match c {
+        '½' => (1, 2),
+        '⅓' => (1, 3),
+        '⅔' => (2, 3),
+        '¼' => (1, 4),
+        '¾' => (3, 4),
+        '⅕' => (1, 5),
+        '⅖' => (2, 5),
+        '⅗' => (3, 5),
+        '⅘' => (4, 5),
+        '⅙' => (1, 6),
+        '⅚' => (5, 6),
+        '⅛' => (1, 8),
+        '⅜' => (3, 8),
+        '⅝' => (5, 8),
+        '⅞' => (7, 8),
+        _ => return None,
+    };
+    Some(Ratio::new(numerator, denominator))
+}
+
+/// `Ratio::ceil` overflows for amounts close to `i64::MAX`
+#[allow(clippy::cast_possible_truncation)]
+fn ceil(amount: Amount) -> i32 {
+    let whole = amount.to_integer();
+    let whole = if amount.is_integer() || amount < Ratio::from_integer(0) {
+        whole
+    } else {
+        whole.saturating_add(1)
+    };
+    whole.clamp(0, i64::from(i32::MAX)) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amounts_are_parsed() {
+        let cases = [
+            ("2", Some(Ratio::from_integer(2))),
+            ("1.5", Some(Ratio::new(3, 2))),
+            ("1,5", Some(Ratio::new(3, 2))),
+            (".25", Some(Ratio::new(1, 4))),
+            ("1/2", Some(Ratio::new(1, 2))),
+            ("1 1/2", Some(Ratio::new(3, 2))),
+            ("½", Some(Ratio::new(1, 2))),
+            ("1½", Some(Ratio::new(3, 2))),
+            ("1 ½", Some(Ratio::new(3, 2))),
+            ("", None),
+            ("a few", None),
+            ("1/0", None),
+            ("1/-2", None),
+            ("1 2", None),
+            ("½ cup", None),
+        ];
+        for (amount, expected) in cases {
+            assert_eq!(parse_amount(amount), expected, "{amount:?}");
+        }
+    }
+
+    #[test]
+    fn amounts_that_overflow_are_rejected() {
+        let cases = [
+            "9223372036854775807.5",
+            "9223372036854775807 1/2",
+            "9223372036854775807½",
+            "9223372036854775808",
+            "1.00000000000000000001",
+            "-9223372036854775808/-1",
+        ];
+        for amount in cases {
+            assert_eq!(parse_amount(amount), None, "{amount:?}");
+        }
+        assert_eq!(
+            parse_amount("9223372036854775807"),
+            Some(Ratio::from_integer(i64::MAX))
+        );
+    }
+
+    #[test]
+    fn measurements_are_converted() {
+        let measurement = Measurement::parse("1", Some("kg")).unwrap();
+        assert_eq!(
+            measurement.convert(Unit::Gram),
+            Some(Ratio::from_integer(1000))
+        );
+        assert_eq!(measurement.in_measure(Measure::WeightGrams), Some(1000));
+        // Mass and volume are compared through the density of water
+        assert_eq!(
+            measurement.in_measure(Measure::VolumeMilliLitres),
+            Some(1000)
+        );
+        assert_eq!(measurement.in_measure(Measure::Quantity), None);
+
+        let measurement = Measurement::parse("1 1/2", Some("cups")).unwrap();
+        // 354.88236 ml, rounded up
+        assert_eq!(
+            measurement.in_measure(Measure::VolumeMilliLitres),
+            Some(355)
+        );
+
+        let measurement = Measurement::parse("2.5", None).unwrap();
+        assert_eq!(measurement.in_base_measure(), (Measure::Quantity, 3));
+        assert_eq!(measurement.in_measure(Measure::WeightGrams), None);
+
+        assert_eq!(Measurement::parse("1", Some("handful")), None);
+    }
+
+    #[test]
+    fn conversions_that_overflow_are_rejected() {
+        let measurement = Measurement::parse("0.123456789012345678", Some("cup")).unwrap();
+        assert_eq!(measurement.convert(Unit::Millilitre), None);
+        assert_eq!(measurement.in_measure(Measure::VolumeMilliLitres), None);
+
+        let measurement = Measurement::parse("9223372036854775807", Some("kg")).unwrap();
+        assert_eq!(measurement.convert(Unit::Gram), None);
+        assert_eq!(measurement.in_base_measure(), (Measure::WeightGrams, 0));
+    }
+
+    #[test]
+    fn large_amounts_are_clamped() {
+        let measurement = Measurement::parse("9223372036854775807 1/3", None);
+        assert_eq!(measurement, None);
+        let measurement = Measurement::parse("4611686018427387903 1/2", None).unwrap();
+        assert_eq!(measurement.in_measure(Measure::Quantity), Some(i32::MAX));
+        let measurement = Measurement::parse("3000000000", Some("g")).unwrap();
+        assert_eq!(measurement.in_measure(Measure::WeightGrams), Some(i32::MAX));
+    }
+
+    #[test]
+    fn units_are_parsed() {
+        let cases = [
+            ("g", Some(Unit::Gram)),
+            ("Grams", Some(Unit::Gram)),
+            ("tbsp.", Some(Unit::Tablespoon)),
+            (" fl oz ", Some(Unit::FluidOunce)),
+            ("lbs", Some(Unit::Pound)),
+            ("pinch", None),
+        ];
+        for (unit, expected) in cases {
+            assert_eq!(unit.parse::<Unit>().ok(), expected, "{unit:?}");
+        }
+    }
+}